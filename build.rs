@@ -1,4 +1,10 @@
+use std::{env, path::PathBuf};
+
 fn main() {
-    tonic_build::compile_protos("proto/chain_gateway.proto")
+    // Served by gRPC reflection when CHAIN_GATEWAY_REFLECTION is enabled
+    let descriptor_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("chain_gateway_descriptor.bin");
+    tonic_build::configure()
+        .file_descriptor_set_path(descriptor_path)
+        .compile_protos(&["proto/chain_gateway.proto"], &["proto"])
         .unwrap_or_else(|e| panic!("Failed to compile protos {:?}", e));
-}
\ No newline at end of file
+}
@@ -0,0 +1,207 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use tracing::{error, info, warn};
+
+use crate::blockchain::{BlockchainProvider, BlockchainResult, ChainConfig};
+
+/// Least time between two alerts while the balance stays low
+const ALERT_REPEAT: Duration = Duration::from_secs(3600);
+
+/// Longest a webhook delivery may take
+#[cfg(feature = "alerts")]
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body POSTed to the webhook when the payer runs low
+#[derive(Debug, Clone, Serialize)]
+pub struct LowBalanceAlert {
+    /// e.g. `solana` or `evm:137`
+    pub chain: String,
+    /// Balance of the best-funded payer, in lamports or wei
+    pub balance: u64,
+    pub threshold: u64,
+    /// Unix seconds the balance was checked
+    pub timestamp: i64,
+    pub message: String,
+}
+
+/// Client POSTing alerts to an HTTP endpoint, such as a Slack or PagerDuty webhook
+#[cfg(feature = "alerts")]
+pub struct Webhook {
+    http: reqwest::Client,
+    url: url::Url,
+}
+
+/// Stand-in when the crate is built without the `alerts` feature; it can never be created
+#[cfg(not(feature = "alerts"))]
+pub struct Webhook {
+    never: std::convert::Infallible,
+}
+
+#[cfg(feature = "alerts")]
+impl Webhook {
+    pub fn new(url: &str) -> Result<Self, String> {
+        let url = url::Url::parse(url).map_err(|e| format!("Invalid CHAIN_BALANCE_ALERT_WEBHOOK {:?}: {}", url, e))?;
+        if !matches!(url.scheme(), "http" | "https") {
+            return Err(format!("CHAIN_BALANCE_ALERT_WEBHOOK must use http:// or https://, not {}://", url.scheme()));
+        }
+        let http = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to build webhook HTTP client: {}", e))?;
+        Ok(Self { http, url })
+    }
+
+    pub async fn send(&self, alert: &LowBalanceAlert) -> Result<(), String> {
+        self.http
+            .post(self.url.clone())
+            .json(alert)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map(|_| ())
+            .map_err(|e| format!("Webhook delivery failed: {}", e.without_url()))
+    }
+}
+
+#[cfg(not(feature = "alerts"))]
+impl Webhook {
+    pub fn new(_url: &str) -> Result<Self, String> {
+        Err("CHAIN_BALANCE_ALERT_WEBHOOK is set but the gateway was built without the `alerts` feature".to_string())
+    }
+
+    pub async fn send(&self, _alert: &LowBalanceAlert) -> Result<(), String> {
+        match self.never {}
+    }
+}
+
+/// When to raise an alert: at once when the balance drops below the threshold, then at most
+/// once per `ALERT_REPEAT` while it stays there, and at once again after it has recovered
+#[derive(Debug, Default)]
+struct Debounce {
+    last_alert: Option<Instant>,
+}
+
+impl Debounce {
+    fn should_alert(&mut self, low: bool, now: Instant) -> bool {
+        if !low {
+            self.last_alert = None;
+            return false;
+        }
+        if self.last_alert.is_some_and(|at| now.saturating_duration_since(at) < ALERT_REPEAT) {
+            return false;
+        }
+        self.last_alert = Some(now);
+        true
+    }
+
+    /// Let the next low reading alert again, as after a delivery that failed
+    fn rearm(&mut self) {
+        self.last_alert = None;
+    }
+
+    fn alerting(&self) -> bool {
+        self.last_alert.is_some()
+    }
+}
+
+/// Background check of the payer balance, warning in the logs and through an optional
+/// webhook before stores start failing for lack of funds
+pub struct BalanceAlert {
+    threshold: u64,
+    interval: Duration,
+    webhook: Option<Webhook>,
+}
+
+impl BalanceAlert {
+    /// Alert configured by `CHAIN_BALANCE_ALERT_*`; `None` when no threshold is set
+    pub fn from_config(config: &ChainConfig) -> Result<Option<Self>, String> {
+        let Some(threshold) = config.balance_alert_below else {
+            return Ok(None);
+        };
+        let webhook = config.balance_alert_webhook.as_deref().map(Webhook::new).transpose()?;
+        info!(
+            threshold,
+            interval_secs = config.balance_alert_interval.as_secs(),
+            webhook = webhook.is_some(),
+            "Alerting when the payer balance runs low"
+        );
+        Ok(Some(Self {
+            threshold,
+            interval: config.balance_alert_interval,
+            webhook,
+        }))
+    }
+
+    /// Check the balance every interval until the process exits
+    pub async fn run(self, provider: Arc<dyn BlockchainProvider>) {
+        let mut interval = tokio::time::interval(self.interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut debounce = Debounce::default();
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.check(provider.as_ref(), &mut debounce).await {
+                warn!(error = %e, "Failed to check payer balance");
+            }
+        }
+    }
+
+    async fn check(&self, provider: &dyn BlockchainProvider, debounce: &mut Debounce) -> BlockchainResult<()> {
+        let Some(balance) = provider.payer_balance().await? else {
+            return Ok(());
+        };
+        let low = balance < self.threshold;
+        if !low && debounce.alerting() {
+            info!(balance, threshold = self.threshold, "Payer balance recovered");
+        }
+        if !debounce.should_alert(low, Instant::now()) {
+            return Ok(());
+        }
+
+        let chain = provider.chain_type().to_string();
+        warn!(chain = %chain, balance, threshold = self.threshold, "Payer balance is low; stores will fail once it runs out");
+        let Some(webhook) = &self.webhook else {
+            return Ok(());
+        };
+        let alert = LowBalanceAlert {
+            message: format!(
+                "Payer balance on {} is {}, below the alert threshold of {}",
+                chain, balance, self.threshold
+            ),
+            chain,
+            balance,
+            threshold: self.threshold,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|since| since.as_secs() as i64)
+                .unwrap_or(0),
+        };
+        if let Err(e) = webhook.send(&alert).await {
+            error!(error = %e, "Failed to deliver low balance alert, retrying on the next check");
+            debounce.rearm();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn alerts_are_debounced_until_the_balance_recovers() {
+        let mut debounce = Debounce::default();
+        let start = Instant::now();
+        assert!(!debounce.should_alert(false, start));
+        assert!(debounce.should_alert(true, start));
+        assert!(!debounce.should_alert(true, start + Duration::from_secs(60)));
+        assert!(debounce.should_alert(true, start + ALERT_REPEAT));
+
+        // A recovery re-arms the alert, so the next drop is reported at once
+        let later = start + ALERT_REPEAT + Duration::from_secs(60);
+        assert!(!debounce.should_alert(false, later));
+        assert!(!debounce.alerting());
+        assert!(debounce.should_alert(true, later + Duration::from_secs(1)));
+    }
+}
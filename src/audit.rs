@@ -0,0 +1,171 @@
+use std::io::Write;
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use tonic::Request;
+use tracing::{error, info};
+
+use crate::auth::API_KEY_HEADER;
+use crate::proto::ContentRecord;
+
+/// Entries waiting to be written before new ones are dropped; a burst of stores never waits on the disk
+const AUDIT_BUFFER: usize = 65_536;
+
+/// Who sent a store, as far as the transport tells
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Caller {
+    /// First 8 bytes of the SHA-256 of the API key, hex-encoded, so the key itself is never logged
+    pub api_key_id: Option<String>,
+    /// Remote address of the connection
+    pub peer: Option<String>,
+}
+
+impl Caller {
+    pub fn of<T>(request: &Request<T>) -> Self {
+        Self {
+            api_key_id: request.metadata().get(API_KEY_HEADER).map(|key| {
+                Sha256::digest(key.as_bytes())[..8].iter().map(|byte| format!("{:02x}", byte)).collect()
+            }),
+            peer: request.remote_addr().map(|addr| addr.to_string()),
+        }
+    }
+}
+
+/// How a store attempt ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    /// Confirmed on chain
+    Stored,
+    /// Submitted fire-and-forget, not confirmed yet
+    Submitted,
+    /// Queued for resubmission because the chain was unreachable
+    Queued,
+    /// Answered with an existing proof of the same record
+    Duplicate,
+    /// Simulated only
+    DryRun,
+    Failed,
+}
+
+/// One line of the audit log
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditEntry {
+    /// When the attempt finished, in milliseconds since the Unix epoch
+    pub timestamp_ms: u64,
+    /// RPC the record came in through, e.g. `Store`
+    pub rpc: &'static str,
+    /// `x-request-id` of the call; `None` for retries of queued stores
+    pub request_id: Option<String>,
+    pub api_key_id: Option<String>,
+    pub peer: Option<String>,
+    pub uid: String,
+    pub url: String,
+    pub content_hash: String,
+    pub outcome: AuditOutcome,
+    /// Empty unless a transaction was submitted or an existing one returned
+    pub transaction_id: String,
+    /// gRPC status code of a failed attempt, e.g. `Unavailable`
+    pub error_code: Option<String>,
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    /// Entry for an attempt to store `record`, timestamped now
+    pub fn new(rpc: &'static str, caller: &Caller, record: &ContentRecord, outcome: AuditOutcome) -> Self {
+        Self {
+            timestamp_ms: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|since| since.as_millis() as u64)
+                .unwrap_or(0),
+            rpc,
+            request_id: crate::request_id::current(),
+            api_key_id: caller.api_key_id.clone(),
+            peer: caller.peer.clone(),
+            uid: record.uid.clone(),
+            url: record.url.clone(),
+            content_hash: record.content_hash.clone(),
+            outcome,
+            transaction_id: String::new(),
+            error_code: None,
+            error: None,
+        }
+    }
+
+    pub fn with_transaction_id(mut self, transaction_id: impl Into<String>) -> Self {
+        self.transaction_id = transaction_id.into();
+        self
+    }
+
+    pub fn with_error(mut self, status: &tonic::Status) -> Self {
+        self.error_code = Some(format!("{:?}", status.code()));
+        self.error = Some(status.message().to_string());
+        self
+    }
+}
+
+/// Destination of audit entries. `record` is called on the store path, so it must not block.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, entry: AuditEntry);
+}
+
+/// Append-only JSON Lines file with one entry per store attempt. Entries are written by a
+/// background thread; if it falls `AUDIT_BUFFER` entries behind, further entries are dropped
+/// and counted in `audit_entries_dropped_total` rather than slowing stores down.
+pub struct AuditLog {
+    sender: SyncSender<AuditEntry>,
+}
+
+impl AuditLog {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let file = std::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| format!("Failed to open audit log {}: {}", path.display(), e))?;
+        let (sender, receiver) = mpsc::sync_channel(AUDIT_BUFFER);
+        let display = path.display().to_string();
+        std::thread::Builder::new()
+            .name("audit-log".to_string())
+            .spawn(move || write_entries(receiver, file, &display))
+            .map_err(|e| format!("Failed to start audit log writer: {}", e))?;
+        info!(path = %path.display(), "Writing audit log");
+        Ok(Self { sender })
+    }
+}
+
+impl AuditSink for AuditLog {
+    fn record(&self, entry: AuditEntry) {
+        match self.sender.try_send(entry) {
+            Ok(()) => {}
+            Err(TrySendError::Full(entry)) => {
+                error!(uid = %entry.uid, "Audit log is behind, dropping entry");
+                crate::metrics::metrics().audit_entries_dropped.inc();
+            }
+            Err(TrySendError::Disconnected(_)) => crate::metrics::metrics().audit_entries_dropped.inc(),
+        }
+    }
+}
+
+/// Write entries as they arrive, flushing whenever the buffer is drained
+fn write_entries(receiver: Receiver<AuditEntry>, file: std::fs::File, path: &str) {
+    let mut writer = std::io::BufWriter::new(file);
+    while let Ok(entry) = receiver.recv() {
+        let mut written = write_entry(&mut writer, &entry);
+        while let Ok(entry) = receiver.try_recv() {
+            written = written.and_then(|()| write_entry(&mut writer, &entry));
+        }
+        if let Err(e) = written.and_then(|()| writer.flush()) {
+            // Keep going; a full disk may recover, and the entries are lost either way
+            error!(path, error = %e, "Failed to write audit log");
+        }
+    }
+}
+
+fn write_entry(writer: &mut impl Write, entry: &AuditEntry) -> std::io::Result<()> {
+    serde_json::to_writer(&mut *writer, entry)?;
+    writer.write_all(b"\n")
+}
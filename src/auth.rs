@@ -0,0 +1,110 @@
+use std::sync::Arc;
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+use tracing::debug;
+
+/// Metadata header clients send their API key in
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// Rejects requests whose `x-api-key` is not one of the configured keys
+#[derive(Clone)]
+pub struct ApiKeyInterceptor {
+    /// `None` when authentication is disabled
+    keys: Option<Arc<Vec<Vec<u8>>>>,
+}
+
+impl ApiKeyInterceptor {
+    pub fn new(keys: Vec<String>) -> Self {
+        Self {
+            keys: Some(Arc::new(keys.into_iter().map(String::into_bytes).collect())),
+        }
+    }
+
+    /// Accept every request; for local development only
+    pub fn disabled() -> Self {
+        Self { keys: None }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.keys.is_some()
+    }
+
+    fn is_allowed(&self, key: &[u8]) -> bool {
+        let Some(keys) = &self.keys else {
+            return true;
+        };
+        // Check every key so the time taken does not reveal which one came closest
+        keys.iter().fold(false, |allowed, candidate| allowed | constant_time_eq(candidate, key))
+    }
+}
+
+impl Interceptor for ApiKeyInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        if self.keys.is_none() {
+            return Ok(request);
+        }
+
+        match request.metadata().get(API_KEY_HEADER) {
+            Some(key) if self.is_allowed(key.as_bytes()) => Ok(request),
+            Some(_) => {
+                debug!("Rejected request with an unknown API key");
+                Err(Status::unauthenticated("Invalid API key"))
+            }
+            None => Err(Status::unauthenticated(format!("Missing {} header", API_KEY_HEADER))),
+        }
+    }
+}
+
+/// Compare two byte strings in time that depends only on their lengths
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(api_key: Option<&str>) -> Request<()> {
+        let mut request = Request::new(());
+        if let Some(key) = api_key {
+            request.metadata_mut().insert(API_KEY_HEADER, key.parse().unwrap());
+        }
+        request
+    }
+
+    #[test]
+    fn only_configured_keys_are_let_through() {
+        let mut auth = ApiKeyInterceptor::new(vec!["key-1".to_string(), "key-2".to_string()]);
+        let status = auth.call(request(None)).unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+        assert!(status.message().contains(API_KEY_HEADER));
+
+        for wrong in ["key-3", "key-", "key-10", ""] {
+            let status = auth.call(request(Some(wrong))).unwrap_err();
+            assert_eq!(status.code(), tonic::Code::Unauthenticated, "accepted {:?}", wrong);
+        }
+        assert!(auth.call(request(Some("key-1"))).is_ok());
+        assert!(auth.call(request(Some("key-2"))).is_ok());
+    }
+
+    #[test]
+    fn disabled_auth_lets_everything_through() {
+        let mut auth = ApiKeyInterceptor::disabled();
+        assert!(!auth.is_enabled());
+        assert!(auth.call(request(None)).is_ok());
+        assert!(auth.call(request(Some("anything"))).is_ok());
+    }
+
+    #[test]
+    fn keys_of_other_lengths_never_compare_equal() {
+        assert!(constant_time_eq(b"key-1", b"key-1"));
+        assert!(!constant_time_eq(b"key-1", b"key-2"));
+        assert!(!constant_time_eq(b"key-1", b"key-10"));
+        assert!(!constant_time_eq(b"key-1", b"key-"));
+        assert!(!constant_time_eq(b"", b"key-1"));
+        assert!(constant_time_eq(b"", b""));
+    }
+}
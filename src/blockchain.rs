@@ -1,33 +1,2046 @@
 use async_trait::async_trait;
-use std::error::Error;
-use crate::proto::ContentRecord;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::env;
+use std::fmt;
+use std::future::Future;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+use thiserror::Error;
+use url::Url;
+use crate::proto::{ContentRecord, HashAlgorithm};
 
-pub type BlockchainResult<T> = Result<T, Box<dyn Error + Send + Sync>>;
+pub type BlockchainResult<T> = Result<T, BlockchainError>;
+
+#[derive(Debug, Clone, Error)]
+pub enum BlockchainError {
+    /// The RPC node could not be reached or did not answer
+    #[error("RPC unavailable: {0}")]
+    RpcUnavailable(String),
+    /// The RPC node did not answer a request within the configured RPC timeout
+    #[error("RPC request timed out: {0}")]
+    RpcTimeout(String),
+    /// Configuration is missing, malformed or points at unusable keys
+    #[error("invalid configuration: {0}")]
+    InvalidConfig(String),
+    /// Instruction or account data could not be encoded or decoded
+    #[error("serialization failed: {0}")]
+    Serialization(String),
+    /// The payer cannot cover fees or rent
+    #[error("insufficient funds: {0}")]
+    InsufficientFunds(String),
+    /// The transaction was submitted but not confirmed in time
+    #[error("confirmation timed out: {0}")]
+    ConfirmationTimeout(String),
+    /// The confirmation timeout passed with the transaction still unconfirmed; it was sent and may yet land
+    #[error("transaction {transaction_id} not confirmed within {}s", .timeout.as_secs())]
+    ConfirmationPending { transaction_id: String, timeout: Duration },
+    /// No account exists at the requested address
+    #[error("account not found: {0}")]
+    AccountNotFound(String),
+    /// The proof account for this record is already initialized
+    #[error("already exists: {0}")]
+    AlreadyExists(String),
+    /// A record field is unusable for storage
+    #[error("invalid record: {0}")]
+    InvalidRecord(String),
+    /// An address is malformed or does not hold a proof
+    #[error("invalid address: {0}")]
+    InvalidAddress(String),
+    /// No transaction with this ID is known to the node
+    #[error("transaction not found: {0}")]
+    TransactionNotFound(String),
+    /// The transaction exists but did not store a proof
+    #[error("not a proof transaction: {0}")]
+    NotAProof(String),
+    /// Simulation showed the program would reject the transaction
+    #[error("transaction simulation failed: {error}; program logs: {logs:?}")]
+    SimulationFailed { error: String, logs: Vec<String> },
+    /// The program rejected an instruction with one of its custom error codes; `message` is
+    /// the configured description of the code, if any
+    #[error("program rejected instruction {instruction}: {}", program_error_detail(.code, .message))]
+    ProgramError { instruction: u8, code: u32, message: Option<String> },
+    /// The RPC node could not be reached at startup, even after retrying
+    #[error("could not reach {url} after {attempts} attempt(s): {reason}")]
+    ConnectionFailed { url: String, attempts: u32, reason: String },
+    /// The chain or provider has no way to perform the operation
+    #[error("unsupported: {0}")]
+    Unsupported(String),
+    /// Anything else the chain or RPC node rejected
+    #[error("{0}")]
+    Other(String),
+}
+
+/// `custom error <code> (0x<hex>)`, followed by the code's configured message when there is one
+pub(crate) fn program_error_detail(code: &u32, message: &Option<String>) -> String {
+    match message {
+        Some(message) => format!("custom error {} ({:#x}): {}", code, code, message),
+        None => format!("custom error {} ({:#x})", code, code),
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct TransactionResult {
     pub transaction_id: String,
+    /// Account the proof was written to, when the chain stores proofs per account.
+    /// Batch chunks leave this unset and report per-record addresses instead.
+    pub account_address: Option<String>,
+    /// Block (or slot) the transaction was included in, when known
+    pub block_height: Option<u64>,
+    /// Unix timestamp of the including block, when known
+    pub block_time: Option<i64>,
+    /// Wall-clock time from submission to confirmation
+    pub confirmation_time: Option<Duration>,
+    /// Wall-clock time from submission to finalization, when the store went on to wait for it
+    pub finalization_time: Option<Duration>,
+    /// Set for dry runs, which leave `transaction_id` empty and report the simulation instead
+    pub simulation_logs: Option<Vec<String>>,
+    /// Block explorer page for the transaction, when the network has a known explorer
+    pub explorer_url: Option<String>,
+}
+
+/// Placeholder for the transaction ID in an explorer URL template
+const EXPLORER_TX_PLACEHOLDER: &str = "{tx}";
+
+/// Builds block explorer links to transactions on the configured network
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Explorer {
+    template: String,
+}
+
+impl Explorer {
+    /// `CHAIN_EXPLORER_URL` when set, otherwise the public explorer for the network the RPC URL
+    /// names; `None` for networks that cannot be recognized
+    pub fn from_config(config: &ChainConfig) -> Option<Self> {
+        if let Some(template) = &config.explorer_url {
+            return Some(Self {
+                template: template.clone(),
+            });
+        }
+
+        let url = Url::parse(&config.network_url).ok()?;
+        let host = url.host_str()?.to_ascii_lowercase();
+        // Shared providers name the network in the host or in the path
+        let network = format!("{}{}", host, url.path().to_ascii_lowercase());
+        let template = match config.chain_type {
+            ChainType::Solana if network.contains("devnet") => "https://explorer.solana.com/tx/{tx}?cluster=devnet".to_string(),
+            ChainType::Solana if network.contains("testnet") => "https://explorer.solana.com/tx/{tx}?cluster=testnet".to_string(),
+            ChainType::Solana if network.contains("mainnet") => "https://explorer.solana.com/tx/{tx}".to_string(),
+            // A local validator's URL carries no credentials, so the explorer can be pointed at it
+            ChainType::Solana if matches!(host.as_str(), "localhost" | "127.0.0.1" | "[::1]") => format!(
+                "https://explorer.solana.com/tx/{{tx}}?cluster=custom&customUrl={}",
+                url::form_urlencoded::byte_serialize(config.network_url.as_bytes()).collect::<String>()
+            ),
+            ChainType::Ethereum if network.contains("sepolia") => "https://sepolia.etherscan.io/tx/{tx}".to_string(),
+            ChainType::Ethereum if network.contains("holesky") => "https://holesky.etherscan.io/tx/{tx}".to_string(),
+            ChainType::Ethereum if network.contains("mainnet") => "https://etherscan.io/tx/{tx}".to_string(),
+            ChainType::Evm { chain_id: POLYGON_CHAIN_ID } => "https://polygonscan.com/tx/{tx}".to_string(),
+            ChainType::Evm { chain_id: POLYGON_AMOY_CHAIN_ID } => "https://amoy.polygonscan.com/tx/{tx}".to_string(),
+            _ => return None,
+        };
+        Some(Self { template })
+    }
+
+    pub fn transaction_url(&self, transaction_id: &str) -> String {
+        self.template.replace(EXPLORER_TX_PLACEHOLDER, transaction_id)
+    }
+}
+
+/// Longest `content_type` accepted; registered MIME types are far shorter
+pub const MAX_CONTENT_TYPE_LEN: usize = 255;
+
+impl HashAlgorithm {
+    /// Name used in messages
+    fn display_name(self) -> &'static str {
+        match self {
+            Self::Unspecified | Self::Sha256 => "SHA-256",
+            Self::Blake3 => "BLAKE3",
+            Self::Keccak256 => "Keccak-256",
+        }
+    }
+
+    /// Bytes in a digest
+    fn digest_len(self) -> usize {
+        match self {
+            Self::Unspecified | Self::Sha256 | Self::Blake3 | Self::Keccak256 => 32,
+        }
+    }
+
+    /// Prefix naming the algorithm of a content hash stored on chain; SHA-256 hashes have none
+    fn stored_prefix(self) -> Option<&'static str> {
+        match self {
+            Self::Unspecified | Self::Sha256 => None,
+            Self::Blake3 => Some("blake3"),
+            Self::Keccak256 => Some("keccak256"),
+        }
+    }
+
+    fn digest(self, content: &[u8]) -> Vec<u8> {
+        use sha2::Digest;
+
+        match self {
+            Self::Unspecified | Self::Sha256 => sha2::Sha256::digest(content).to_vec(),
+            Self::Blake3 => blake3::hash(content).as_bytes().to_vec(),
+            Self::Keccak256 => sha3::Keccak256::digest(content).to_vec(),
+        }
+    }
+}
+
+impl ContentRecord {
+    /// The record's hash algorithm, with unset meaning SHA-256
+    pub fn digest_algorithm(&self) -> BlockchainResult<HashAlgorithm> {
+        match HashAlgorithm::try_from(self.hash_algorithm) {
+            Ok(HashAlgorithm::Unspecified) => Ok(HashAlgorithm::Sha256),
+            Ok(algorithm) => Ok(algorithm),
+            Err(_) => Err(BlockchainError::InvalidRecord(format!(
+                "Unknown hash_algorithm {}",
+                self.hash_algorithm
+            ))),
+        }
+    }
+
+    /// Content hash as written on chain: the bare hex digest for SHA-256, as every proof from
+    /// before algorithms were recorded has it, and `<algorithm>:<hex>` otherwise, e.g. `blake3:` or
+    /// `keccak256:`
+    pub fn stored_content_hash(&self) -> String {
+        match self.digest_algorithm().ok().and_then(HashAlgorithm::stored_prefix) {
+            Some(prefix) => format!("{}:{}", prefix, self.content_hash),
+            None => self.content_hash.clone(),
+        }
+    }
+
+    /// Set `content_hash` and `hash_algorithm` from a content hash read back from chain. A bare
+    /// digest leaves the algorithm unspecified: it is SHA-256, or any algorithm for proofs stored
+    /// before algorithms were recorded.
+    pub fn set_stored_content_hash(&mut self, stored: String) {
+        let algorithm = [HashAlgorithm::Blake3, HashAlgorithm::Keccak256].into_iter().find_map(|algorithm| {
+            let prefix = algorithm.stored_prefix()?;
+            let digest = stored.strip_prefix(prefix)?.strip_prefix(':')?;
+            Some((algorithm, digest.to_string()))
+        });
+        let (algorithm, digest) = algorithm.unwrap_or((HashAlgorithm::Unspecified, stored));
+        self.hash_algorithm = algorithm as i32;
+        self.content_hash = digest;
+    }
+
+    /// Whether `stored`, read back from chain, could have been hashed with this record's algorithm
+    pub fn hash_algorithm_matches(&self, stored: &ContentRecord) -> bool {
+        stored.hash_algorithm == HashAlgorithm::Unspecified as i32 || self.digest_algorithm().ok() == stored.digest_algorithm().ok()
+    }
+
+    /// Reject records that would waste a transaction: the content hash must be
+    /// a lowercase hex digest of the length its algorithm produces, and a content type
+    /// or storage URI, when given, a MIME type and an absolute URI
+    pub fn validate(&self) -> BlockchainResult<()> {
+        let algorithm = self.digest_algorithm()?;
+        let digest_len = algorithm.digest_len();
+
+        let hash = &self.content_hash;
+        if hash.is_empty() {
+            return Err(BlockchainError::InvalidRecord("content_hash is empty".to_string()));
+        }
+        if hash.len() != digest_len * 2 {
+            return Err(BlockchainError::InvalidRecord(format!(
+                "content_hash must be {} hex characters for {}, got {}",
+                digest_len * 2,
+                algorithm.display_name(),
+                hash.len()
+            )));
+        }
+        if !hash.bytes().all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b)) {
+            return Err(BlockchainError::InvalidRecord(
+                "content_hash must be lowercase hex".to_string(),
+            ));
+        }
+
+        let content_type = &self.content_type;
+        if !content_type.is_empty() {
+            if content_type.len() > MAX_CONTENT_TYPE_LEN {
+                return Err(BlockchainError::InvalidRecord(format!(
+                    "content_type is {} bytes, longer than the {} byte limit",
+                    content_type.len(),
+                    MAX_CONTENT_TYPE_LEN
+                )));
+            }
+            let essence = content_type.split(';').next().unwrap_or_default().trim();
+            let well_formed = content_type.bytes().all(|b| b == b' ' || b.is_ascii_graphic())
+                && matches!(essence.split_once('/'), Some((kind, subtype)) if !kind.is_empty() && !subtype.is_empty());
+            if !well_formed {
+                return Err(BlockchainError::InvalidRecord(format!(
+                    "content_type {:?} is not a MIME type such as \"text/html\"",
+                    content_type
+                )));
+            }
+        }
+        if !self.storage_uri.is_empty() {
+            Url::parse(&self.storage_uri)
+                .map_err(|e| BlockchainError::InvalidRecord(format!("storage_uri is not a valid URI: {}", e)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Derive `content_hash` and `content_length` from the content itself, using the record's
+    /// `hash_algorithm`. A `content_hash` or non-zero `content_length` the client already set
+    /// must match the computed digest and the byte count.
+    pub fn apply_content(&mut self, content: &[u8]) -> BlockchainResult<()> {
+        if self.content_length != 0 && self.content_length != content.len() as u64 {
+            return Err(BlockchainError::InvalidRecord(format!(
+                "content_length {} does not match the content, which is {} bytes",
+                self.content_length,
+                content.len()
+            )));
+        }
+
+        let digest = self.digest_algorithm()?.digest(content);
+        let computed: String = digest.iter().map(|byte| format!("{:02x}", byte)).collect();
+        if !self.content_hash.is_empty() && self.content_hash != computed {
+            return Err(BlockchainError::InvalidRecord(format!(
+                "content_hash {} does not match the content, which hashes to {}",
+                self.content_hash, computed
+            )));
+        }
+        self.content_hash = computed;
+        self.content_length = content.len() as u64;
+        Ok(())
+    }
+
+    /// Reject records whose protobuf encoding exceeds `max_bytes`, before any parsing or RPC work
+    pub fn check_size(&self, max_bytes: usize) -> BlockchainResult<()> {
+        use prost::Message;
+
+        let size = self.encoded_len();
+        if size > max_bytes {
+            return Err(BlockchainError::InvalidRecord(format!(
+                "record is {} bytes serialized, larger than the {} byte limit",
+                size, max_bytes
+            )));
+        }
+        Ok(())
+    }
+
+    /// Trim and canonicalize the URL (lowercase scheme and host, resolved `..` segments),
+    /// rejecting URLs that do not parse, have no host, or exceed `max_len` bytes once normalized
+    pub fn normalize_url(&mut self, max_len: usize) -> BlockchainResult<()> {
+        let trimmed = self.url.trim();
+        if trimmed.is_empty() {
+            return Err(BlockchainError::InvalidRecord("url is empty".to_string()));
+        }
+
+        let parsed = Url::parse(trimmed)
+            .map_err(|e| BlockchainError::InvalidRecord(format!("url is not a valid URL: {}", e)))?;
+        if !parsed.has_host() {
+            return Err(BlockchainError::InvalidRecord("url has no host".to_string()));
+        }
+
+        let normalized = String::from(parsed);
+        if normalized.len() > max_len {
+            return Err(BlockchainError::InvalidRecord(format!(
+                "url is {} bytes, longer than the {} byte limit",
+                normalized.len(),
+                max_len
+            )));
+        }
+
+        self.url = normalized;
+        Ok(())
+    }
 }
 
+/// Which chain and network a provider is connected to
 #[derive(Debug, Clone)]
-pub struct SolanaConfig {
-    pub rpc_url: String,
-    pub program_id: String,
-    pub keypair_path: String,
-    pub proof_account_keypair_path: String,
+pub struct NetworkInfo {
+    pub chain_type: ChainType,
+    /// RPC endpoint reduced to scheme and host, so API keys in the path or query stay private
+    pub network_url: String,
+    pub genesis_hash: String,
+    /// Current slot on Solana, latest block number on Ethereum
+    pub latest_block: u64,
+    pub node_version: String,
+    /// EIP-155 chain ID; `None` on chains without one
+    pub chain_id: Option<u64>,
+}
+
+impl NetworkInfo {
+    /// Whether this is a local or development network, where storing test records costs nothing
+    /// real: any Solana cluster but mainnet-beta and testnet, or an EVM chain with a local
+    /// (1337, 31337) or public test network chain ID
+    pub fn is_development(&self) -> bool {
+        match self.chain_type {
+            ChainType::Solana => !SOLANA_NON_DEV_GENESIS_HASHES.contains(&self.genesis_hash.as_str()),
+            ChainType::Ethereum | ChainType::Evm { .. } => {
+                matches!(self.chain_id, Some(chain_id) if EVM_DEV_CHAIN_IDS.contains(&chain_id))
+            }
+        }
+    }
+}
+
+/// Expected cost of storing one record, in the chain's smallest unit
+#[derive(Debug, Clone)]
+pub struct FeeEstimate {
+    /// Paid into the proof account (Solana rent-exempt minimum); zero where storage is paid in gas
+    pub storage_fee: u64,
+    /// Network fee for the transaction, including any priority fee
+    pub transaction_fee: u64,
+    /// `"lamports"` or `"wei"`
+    pub unit: &'static str,
+    pub gas_limit: Option<u64>,
+    pub gas_price: Option<u64>,
+}
+
+impl FeeEstimate {
+    pub fn total(&self) -> u64 {
+        self.storage_fee.saturating_add(self.transaction_fee)
+    }
+}
+
+/// Balance of one account paying for stores
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountBalance {
+    pub address: String,
+    /// In the chain's smallest unit
+    pub balance: u128,
+}
+
+/// Funds available to pay for stores
+#[derive(Debug, Clone)]
+pub struct BalanceInfo {
+    /// Every paying account, the primary first
+    pub accounts: Vec<AccountBalance>,
+    /// `"lamports"` or `"wei"`
+    pub unit: &'static str,
+    /// Smallest units per whole unit of the native currency, as a power of ten
+    pub decimals: u32,
+}
+
+impl BalanceInfo {
+    pub fn total(&self) -> u128 {
+        self.accounts.iter().fold(0, |total, account| total.saturating_add(account.balance))
+    }
+
+    /// `balance` in whole native currency, e.g. `"1.5"` for 1.5 SOL in lamports
+    pub fn amount(&self, balance: u128) -> String {
+        let scale = 10u128.pow(self.decimals);
+        let fraction = format!("{:0width$}", balance % scale, width = self.decimals as usize);
+        let fraction = fraction.trim_end_matches('0');
+        if fraction.is_empty() {
+            (balance / scale).to_string()
+        } else {
+            format!("{}.{}", balance / scale, fraction)
+        }
+    }
+}
+
+/// Strip credentials, path and query from an RPC URL, keeping `scheme://host[:port]`
+pub fn redacted_url(url: &str) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    if scheme.is_empty() {
+        host.to_string()
+    } else {
+        format!("{}://{}", scheme, host)
+    }
+}
+
+/// Per-request settings for a store
+#[derive(Debug, Clone, Default)]
+pub struct StoreOptions {
+    /// Wait for this commitment instead of the configured default
+    pub commitment: Option<Commitment>,
+    /// Build and simulate the transaction without submitting it
+    pub dry_run: bool,
+    /// Return as soon as the transaction is submitted instead of waiting for confirmation
+    pub fire_and_forget: bool,
+}
+
+/// Where a submitted transaction stands, as reported by the node
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TransactionStatus {
+    /// Seen by the node but not yet at the configured commitment
+    Pending,
+    /// Reached the configured commitment
+    Confirmed { block_height: Option<u64> },
+    /// Executed and rejected by the chain
+    Failed(String),
+    /// Not known to the node: not yet received, dropped, or expired
+    Unknown,
+}
+
+/// A proof decoded from the instruction data of a store transaction
+#[derive(Debug, Clone)]
+pub struct DecodedProof {
+    /// Only the fields written on chain are set, plus the UID when the instruction carries it
+    pub record: ContentRecord,
+    /// Account the instruction wrote the proof to, when the chain stores proofs per account
+    pub account_address: Option<String>,
+    /// Account that signed and paid for the transaction: the Ethereum sender or Solana fee payer
+    pub payer: Option<String>,
+}
+
+/// The proofs a store transaction wrote and where it landed
+#[derive(Debug, Clone)]
+pub struct TransactionProofs {
+    /// In instruction order; a batch transaction carries several
+    pub proofs: Vec<DecodedProof>,
+    pub block_height: Option<u64>,
+    pub block_time: Option<i64>,
+}
+
+/// A proof account that was closed and the rent it returned
+#[derive(Debug, Clone)]
+pub struct ClosedProof {
+    pub transaction_id: String,
+    /// Payer the account's lamports were returned to
+    pub recipient: String,
+    /// Lamports the account held
+    pub reclaimed: u64,
+    pub block_height: Option<u64>,
+}
+
+/// One transaction of a batch store and the records it carried
+#[derive(Debug)]
+pub struct BatchChunkResult {
+    /// Indices into the submitted records covered by this transaction
+    pub record_indices: Vec<usize>,
+    /// Proof account of each covered record, in the same order (empty when unknown)
+    pub account_addresses: Vec<String>,
+    pub result: BlockchainResult<TransactionResult>,
 }
 
 #[derive(Debug, Clone)]
+pub struct VerificationResult {
+    pub matches: bool,
+    /// Names of the fields whose on-chain value differs from the supplied record
+    pub mismatched_fields: Vec<String>,
+}
+
+impl VerificationResult {
+    /// Compare the fields that are written on chain
+    pub fn compare(expected: &ContentRecord, stored: &ContentRecord) -> Self {
+        let mut mismatched_fields = Vec::new();
+        if expected.url != stored.url {
+            mismatched_fields.push("url".to_string());
+        }
+        if expected.content_hash != stored.content_hash {
+            mismatched_fields.push("content_hash".to_string());
+        }
+        // A digest only means something under the algorithm that produced it
+        if !expected.hash_algorithm_matches(stored) {
+            mismatched_fields.push("hash_algorithm".to_string());
+        }
+        if expected.content_length != stored.content_length {
+            mismatched_fields.push("content_length".to_string());
+        }
+
+        Self {
+            matches: mismatched_fields.is_empty(),
+            mismatched_fields,
+        }
+    }
+}
+
+/// Chain ID of Polygon PoS mainnet, what `CHAIN_TYPE=polygon` stands for
+const POLYGON_CHAIN_ID: u64 = 137;
+
+/// Chain ID of Polygon's Amoy testnet
+const POLYGON_AMOY_CHAIN_ID: u64 = 80002;
+
+/// Genesis hashes of mainnet-beta and testnet, where airdrops and self-tests are never run
+pub(crate) const SOLANA_NON_DEV_GENESIS_HASHES: [&str; 2] = [
+    "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d",
+    "4uhcVJyU9pJkvQyS88uRDiswHXSCkY3zQawwpjk2NsNY",
+];
+
+/// Chain IDs of local EVM nodes (Ganache, Hardhat and Anvil) and of the Sepolia, Holesky,
+/// Hoodi and Amoy test networks
+const EVM_DEV_CHAIN_IDS: [u64; 6] = [1337, 31337, 11_155_111, 17000, 560_048, POLYGON_AMOY_CHAIN_ID];
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Deserialize)]
+#[serde(try_from = "String")]
 pub enum ChainType {
     Solana,
     Ethereum,
-    // Add more chains as needed
+    /// Any other EVM network, such as Polygon, stored through the Ethereum provider. The node
+    /// must report `chain_id` at startup. Written `evm:<chain_id>`; `polygon` is `evm:137`.
+    Evm { chain_id: u64 },
+}
+
+impl ChainType {
+    /// Cargo feature that builds this chain's provider
+    pub fn cargo_feature(&self) -> &'static str {
+        match self {
+            ChainType::Solana => "solana",
+            ChainType::Ethereum | ChainType::Evm { .. } => "ethereum",
+        }
+    }
+}
+
+impl FromStr for ChainType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lowercase = s.to_ascii_lowercase();
+        match lowercase.as_str() {
+            "solana" => Ok(ChainType::Solana),
+            "ethereum" => Ok(ChainType::Ethereum),
+            "polygon" => Ok(ChainType::Evm { chain_id: POLYGON_CHAIN_ID }),
+            _ => match lowercase.strip_prefix("evm:").map(str::parse) {
+                Some(Ok(chain_id)) => Ok(ChainType::Evm { chain_id }),
+                _ => Err(format!(
+                    "Unknown chain type {:?}: expected \"solana\", \"ethereum\", \"polygon\" or \"evm:<chain_id>\"",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for ChainType {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for ChainType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChainType::Solana => write!(f, "solana"),
+            ChainType::Ethereum => write!(f, "ethereum"),
+            ChainType::Evm { chain_id } => write!(f, "evm:{}", chain_id),
+        }
+    }
+}
+
+/// How final a transaction must be before a store reports success
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Commitment {
+    /// Seen by the connected node; may still be rolled back
+    Processed,
+    /// Voted on by a supermajority of the cluster
+    #[default]
+    Confirmed,
+    /// Rooted; cannot be rolled back
+    Finalized,
+}
+
+impl FromStr for Commitment {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "processed" => Ok(Commitment::Processed),
+            "confirmed" => Ok(Commitment::Confirmed),
+            "finalized" => Ok(Commitment::Finalized),
+            _ => Err(format!(
+                "Unknown commitment {:?}: expected \"processed\", \"confirmed\" or \"finalized\"",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for Commitment {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Commitment::Processed => write!(f, "processed"),
+            Commitment::Confirmed => write!(f, "confirmed"),
+            Commitment::Finalized => write!(f, "finalized"),
+        }
+    }
+}
+
+/// How the per-compute-unit priority fee is chosen (Solana only)
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(try_from = "PriorityFeeValue")]
+pub enum PriorityFee {
+    /// Fixed price in micro-lamports per compute unit
+    Fixed(u64),
+    /// A percentile of recently paid prioritization fees
+    Auto,
+}
+
+impl FromStr for PriorityFee {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("auto") {
+            return Ok(PriorityFee::Auto);
+        }
+        s.parse()
+            .map(PriorityFee::Fixed)
+            .map_err(|_| format!("Invalid priority fee {:?}: expected micro-lamports or \"auto\"", s))
+    }
+}
+
+/// Priority fee as written in a config file: a number or `"auto"`
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum PriorityFeeValue {
+    Fixed(u64),
+    Named(String),
+}
+
+impl TryFrom<PriorityFeeValue> for PriorityFee {
+    type Error = String;
+
+    fn try_from(value: PriorityFeeValue) -> Result<Self, Self::Error> {
+        match value {
+            PriorityFeeValue::Fixed(micro_lamports) => Ok(PriorityFee::Fixed(micro_lamports)),
+            PriorityFeeValue::Named(name) => name.parse(),
+        }
+    }
+}
+
+/// Headroom `padded` reserves in each proof account when no byte count is given
+pub const DEFAULT_ACCOUNT_HEADROOM: usize = 128;
+
+/// Most headroom a proof account may be padded with, the system program's limit on one allocation increase
+const MAX_ACCOUNT_HEADROOM: usize = 10 * 1024;
+
+/// How much space proof accounts are allocated beyond the record written into them; either
+/// way the account is funded rent-exempt for all of it (Solana keypair mode only)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(try_from = "String")]
+pub enum RentStrategy {
+    /// Exactly the serialized record, the cheapest
+    #[default]
+    Exact,
+    /// The serialized record plus `headroom` zero bytes, leaving room for larger layouts
+    Padded { headroom: usize },
+}
+
+impl RentStrategy {
+    /// Bytes to allocate for a record that serializes to `record_space` bytes
+    pub fn space(&self, record_space: usize) -> usize {
+        match self {
+            RentStrategy::Exact => record_space,
+            RentStrategy::Padded { headroom } => record_space.saturating_add(*headroom),
+        }
+    }
+}
+
+impl FromStr for RentStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lowercase = s.to_ascii_lowercase();
+        match lowercase.as_str() {
+            "exact" => Ok(RentStrategy::Exact),
+            "padded" => Ok(RentStrategy::Padded {
+                headroom: DEFAULT_ACCOUNT_HEADROOM,
+            }),
+            _ => match lowercase.strip_prefix("padded:").map(str::parse) {
+                Some(Ok(headroom)) => Ok(RentStrategy::Padded { headroom }),
+                _ => Err(format!(
+                    "Unknown rent strategy {:?}: expected \"exact\", \"padded\" or \"padded:<bytes>\"",
+                    s
+                )),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for RentStrategy {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl fmt::Display for RentStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RentStrategy::Exact => write!(f, "exact"),
+            RentStrategy::Padded { headroom } => write!(f, "padded:{}", headroom),
+        }
+    }
+}
+
+/// Messages for the proof program's custom error codes (Solana only)
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(try_from = "HashMap<String, String>")]
+pub struct ProgramErrors(HashMap<u32, String>);
+
+impl ProgramErrors {
+    /// Configured message for a custom error code
+    pub fn message(&self, code: u32) -> Option<&str> {
+        self.0.get(&code).map(String::as_str)
+    }
+
+    fn parse_code(code: &str) -> Result<u32, String> {
+        let code = code.trim();
+        match code.strip_prefix("0x").or_else(|| code.strip_prefix("0X")) {
+            Some(hex) => u32::from_str_radix(hex, 16),
+            None => code.parse(),
+        }
+        .map_err(|_| format!("Invalid program error code {:?}: expected a decimal or 0x-prefixed hex number", code))
+    }
+}
+
+/// `code=message` entries separated by `;`, e.g. `6000=URL too long;0x1771=Hash mismatch`
+impl FromStr for ProgramErrors {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut errors = HashMap::new();
+        for entry in s.split(';').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (code, message) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid program error {:?}: expected code=message", entry))?;
+            errors.insert(Self::parse_code(code)?, message.trim().to_string());
+        }
+        Ok(ProgramErrors(errors))
+    }
+}
+
+/// A config file table, whose keys are always strings
+impl TryFrom<HashMap<String, String>> for ProgramErrors {
+    type Error = String;
+
+    fn try_from(table: HashMap<String, String>) -> Result<Self, Self::Error> {
+        table
+            .into_iter()
+            .map(|(code, message)| Ok((Self::parse_code(&code)?, message)))
+            .collect::<Result<_, String>>()
+            .map(ProgramErrors)
+    }
+}
+
+/// In-flight store limit used when none is configured
+const DEFAULT_MAX_IN_FLIGHT: usize = 16;
+
+/// Idempotency window used when none is configured
+const DEFAULT_IDEMPOTENCY_WINDOW: Duration = Duration::from_secs(600);
+
+/// URL length limit used when none is configured; keeps proof accounts and rent small
+const DEFAULT_MAX_URL_LENGTH: usize = 512;
+
+/// Serialized record size limit used when none is configured; a record this size still fits a
+/// Solana transaction alongside its signatures and accounts
+pub const DEFAULT_MAX_RECORD_SIZE: usize = 768;
+
+/// Retries per minute used when none are configured; one a second on average, in bursts of up to a minute's worth
+const DEFAULT_RETRY_BUDGET: u32 = 60;
+
+/// Ethereum confirmation depth used when none is configured: the inclusion block alone
+const DEFAULT_CONFIRMATIONS: u64 = 1;
+
+/// Per-request RPC timeout used when none is configured, shorter than the SDK default of
+/// 30 seconds so a hung node fails over quickly
+const DEFAULT_RPC_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest a store waits for its transaction to confirm when no timeout is configured
+const DEFAULT_CONFIRMATION_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Longest a confirmed store waits on to be finalized when no timeout is configured;
+/// finalization normally follows confirmation within about 15 seconds
+const DEFAULT_FINALIZATION_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Interval between payer balance checks when a balance alert is configured without one
+const DEFAULT_BALANCE_ALERT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Longest `CHAIN_SUBMITTER` accepted on Solana, where it is written into every proof account
+pub const MAX_SUBMITTER_LEN: usize = 64;
+
+/// Largest serialized transaction a Solana validator accepts (`PACKET_DATA_SIZE`)
+pub const MAX_SOLANA_TRANSACTION_SIZE: usize = 1232;
+
+/// Resends of a transaction whose blockhash expired when not configured
+const DEFAULT_MAX_BLOCKHASH_RESENDS: u32 = 3;
+
+/// Records packed into one batch transaction when not configured, keeping it well inside the compute limit
+const DEFAULT_MAX_BATCH_RECORDS: usize = 8;
+
+/// Lamports requested per airdrop when the amount is not configured: 1 SOL
+const DEFAULT_AIRDROP_LAMPORTS: u64 = 1_000_000_000;
+
+/// Tip paid with each Jito bundle when the amount is not configured
+const DEFAULT_JITO_TIP_LAMPORTS: u64 = 10_000;
+
+/// Smallest tip the Jito block engine accepts with a bundle
+pub const MIN_JITO_TIP_LAMPORTS: u64 = 1_000;
+
+/// Connection retries used when none are configured: ten attempts, the delay doubling from
+/// three seconds up to thirty, with jitter
+const DEFAULT_CONNECT_RETRY: ConnectRetry = ConnectRetry {
+    retries: 9,
+    initial_backoff: Duration::from_secs(3),
+    max_backoff: Duration::from_secs(30),
+    jitter: true,
+};
+
+/// How a provider keeps trying to reach its RPC node at startup
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectRetry {
+    /// Attempts after the first; 0 fails on the first unreachable probe
+    pub retries: u32,
+    /// Delay before the first retry, doubled for each further one
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay; equal to `initial_backoff` for a fixed delay
+    pub max_backoff: Duration,
+    /// Sleep a random time between half the delay and all of it, so replicas started
+    /// together against one node spread their retries out
+    pub jitter: bool,
+}
+
+impl ConnectRetry {
+    /// Delay before retry number `retry`, counting from 1
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry.saturating_sub(1));
+        self.initial_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+
+    /// Time actually slept for a `backoff`, with jitter applied when enabled
+    pub fn jittered(&self, backoff: Duration) -> Duration {
+        if !self.jitter {
+            return backoff;
+        }
+        let half = backoff / 2;
+        half + half.mul_f64(rand::random::<f64>())
+    }
+
+    /// Run `probe` until it succeeds or the retries are used up. The error names the
+    /// endpoint, with credentials in its path or query redacted.
+    pub async fn connect<F, Fut>(&self, url: &str, mut probe: F) -> BlockchainResult<()>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = BlockchainResult<()>>,
+    {
+        let attempts = self.retries.saturating_add(1);
+        for attempt in 1..=attempts {
+            match probe().await {
+                Ok(()) => return Ok(()),
+                Err(e) if attempt < attempts => {
+                    if attempt == 1 {
+                        let schedule_ms: Vec<u64> = (1..attempts).map(|retry| self.backoff(retry).as_millis() as u64).collect();
+                        tracing::info!(?schedule_ms, jitter = self.jitter, "Retrying connection with backoff");
+                    }
+                    let backoff = self.jittered(self.backoff(attempt));
+                    tracing::warn!(
+                        attempt,
+                        max_attempts = attempts,
+                        backoff_ms = backoff.as_millis() as u64,
+                        error = %e,
+                        "Connection attempt failed"
+                    );
+                    tokio::time::sleep(backoff).await;
+                }
+                Err(e) => {
+                    return Err(BlockchainError::ConnectionFailed {
+                        url: redacted_url(url),
+                        attempts,
+                        reason: e.to_string(),
+                    })
+                }
+            }
+        }
+        unreachable!("the last attempt always returns")
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ChainConfig {
+    pub chain_type: ChainType,
+    /// Primary RPC endpoint, preferred whenever it is healthy
+    pub network_url: String,
+    /// Endpoints tried in order when the primary is unreachable (Solana only)
+    pub fallback_urls: Vec<String>,
+    /// WebSocket endpoint for signature subscriptions; confirmations are polled without one (Solana only)
+    pub ws_url: Option<String>,
+    /// Program ID on Solana, proof contract address on Ethereum
+    pub program_id: String,
+    /// Key file for the payer/sender; on Solana `CHAIN_KEYPAIR_BASE58` takes precedence
+    pub private_key_path: Option<String>,
+    /// Keypair files, or directories of `*.json` keypairs, pooled as payers used in turn; replaces
+    /// the single payer when set (Solana only)
+    pub payer_keypairs: Vec<String>,
+    /// Derive proof accounts as PDAs from the record UID instead of random keypairs (Solana only)
+    pub use_pda: bool,
+    /// Address lookup table transactions are compiled against as v0 messages; legacy
+    /// transactions are sent when unset (Solana only)
+    pub lookup_table: Option<String>,
+    /// Space keypair-mode proof accounts are allocated beyond their record (Solana only)
+    pub rent_strategy: RentStrategy,
+    /// How long to keep trying to reach the RPC node at startup
+    pub connect_retry: ConnectRetry,
+    /// Longest one RPC request may take, per endpoint, before it fails or moves on to a fallback
+    pub rpc_timeout: Duration,
+    /// Attempts at submitting a transaction before giving up on retryable errors
+    pub max_send_attempts: u32,
+    /// Times a transaction whose blockhash expired before it landed is re-signed against a
+    /// fresh one and resent; these resends use neither send attempts nor the retry budget (Solana only)
+    pub max_blockhash_resends: u32,
+    /// Retries per minute shared by every request, covering resends and rate-limited RPC
+    /// requests; once spent, failures are returned at once (Solana only)
+    pub retry_budget: u32,
+    /// Most records `StoreBatch` packs into one transaction; larger batches are split (Solana only)
+    pub max_batch_records: usize,
+    /// Largest serialized batch transaction, at most the 1232-byte packet limit; a record that
+    /// will not fit beside the others starts a new transaction (Solana only)
+    pub max_batch_bytes: usize,
+    /// Add an SPL Memo with each record's UID so it is readable on explorers (Solana only)
+    pub memo: bool,
+    /// Send without the RPC node's preflight simulation (Solana only)
+    pub skip_preflight: bool,
+    /// Times the RPC node rebroadcasts a sent transaction; `None` keeps the node's default (Solana only)
+    pub max_rpc_retries: Option<usize>,
+    /// Jito block engine stores are submitted to as tipped bundles, falling back to the RPC node
+    /// when a bundle is refused or does not land; `None` always sends through the RPC node (Solana only)
+    pub jito_block_engine_url: Option<String>,
+    /// Lamports tipped to Jito with each bundle (Solana only)
+    pub jito_tip_lamports: u64,
+    /// Priority fee added to each transaction; `None` sends without one
+    pub priority_fee: Option<PriorityFee>,
+    /// Compute unit limit requested for each transaction; `None` keeps the runtime default
+    pub compute_unit_limit: Option<u32>,
+    /// Commitment stores wait for unless a request overrides it
+    pub commitment: Commitment,
+    /// EIP-1559 fee cap in wei; `None` estimates it from the node (Ethereum only)
+    pub max_fee_per_gas: Option<u64>,
+    /// EIP-1559 priority fee in wei; `None` estimates it from the node (Ethereum only)
+    pub max_priority_fee_per_gas: Option<u64>,
+    /// Blocks a store's transaction must be buried under, counting its own, before it succeeds (Ethereum only)
+    pub confirmations: u64,
+    /// Earliest block searched for `ProofStored` logs when reading proofs back, normally the
+    /// contract's deployment block. Required on EVM chains, so looking up a missing proof never
+    /// scans back to genesis (Ethereum only)
+    pub logs_from_block: Option<u64>,
+    /// Longest a store waits for confirmation, resends included, before giving up with `ConfirmationPending`
+    pub confirmation_timeout: Duration,
+    /// After a store reaches a lower commitment, keep polling until it is `finalized` before
+    /// reporting success (Solana only)
+    pub wait_for_finalized: bool,
+    /// Longest that extra wait may take before the store gives up with `ConfirmationPending` (Solana only)
+    pub finalization_timeout: Duration,
+    /// Airdrop to a payer whose balance falls below this many lamports before it stores; `None`
+    /// never airdrops. Refused outside devnet and local validators (Solana only)
+    pub airdrop_below: Option<u64>,
+    /// Lamports requested per airdrop (Solana only)
+    pub airdrop_lamports: u64,
+    /// Messages reported for the proof program's custom error codes (Solana only)
+    pub program_errors: ProgramErrors,
+    /// Simulate each transaction before sending it to catch program errors early (Solana only)
+    pub simulate_transactions: bool,
+    /// Simulate every store instead of submitting it; nothing is spent or written
+    pub dry_run: bool,
+    /// Store requests allowed to be submitting or awaiting confirmation at once
+    pub max_in_flight: usize,
+    /// How long a completed store is remembered under its idempotency key
+    pub idempotency_window: Duration,
+    /// Longest record URL accepted for storage, in bytes after normalization
+    pub max_url_length: usize,
+    /// Largest record accepted for storage, in protobuf-encoded bytes
+    pub max_record_size: usize,
+    /// Answer a store whose URL and content hash are already proven with the existing proof instead of a new one
+    pub skip_duplicates: bool,
+    /// Serve `CloseProof`, which deletes proofs to reclaim their rent
+    pub allow_close_proofs: bool,
+    /// Gateway identity written into each proof alongside the submission time
+    pub submitter: Option<String>,
+    /// Keypair file of a submitter identity separate from the fee payers. Its public key is
+    /// recorded as the submitter and it co-signs every store (Solana only)
+    pub submitter_keypair: Option<String>,
+    /// Explorer URL for transactions with `{tx}` in place of the ID; detected from `network_url` when unset
+    pub explorer_url: Option<String>,
+    /// IPFS node RPC API that `StoreRequest.content` is pinned to; requires the `ipfs` feature
+    pub ipfs_api_url: Option<String>,
+    /// SQLite file indexing record UIDs to proof accounts; requires the `index` feature
+    pub index_path: Option<String>,
+    /// SQLite file queueing stores that failed because the chain was unreachable; requires the `queue` feature
+    pub retry_queue_path: Option<String>,
+    /// JSON Lines file every store attempt is appended to
+    pub audit_log_path: Option<String>,
+    /// Warn when the best-funded payer holds less than this, in lamports or wei; `None` never checks
+    pub balance_alert_below: Option<u64>,
+    /// URL a low-balance alert is POSTed to as JSON; requires the `alerts` feature
+    pub balance_alert_webhook: Option<String>,
+    /// Interval between balance checks
+    pub balance_alert_interval: Duration,
+    /// Store and read back a synthetic record before serving; only allowed on development networks
+    pub startup_self_test: bool,
+}
+
+impl ChainConfig {
+    /// Load the configuration from `CHAIN_*` variables, plus `SOLANA_*` tuning options.
+    /// Every missing or malformed variable is reported in a single error.
+    pub fn from_env() -> BlockchainResult<Self> {
+        let mut missing = Vec::new();
+        let mut invalid = Vec::new();
+
+        let chain_type = optional_var("CHAIN_TYPE", &mut invalid).unwrap_or(ChainType::Solana);
+        let network_url = required_var("CHAIN_NETWORK_URL", &mut missing);
+        let program_id = required_var("CHAIN_PROGRAM_ID", &mut missing);
+
+        let config = ChainConfig {
+            chain_type,
+            network_url,
+            fallback_urls: env::var("CHAIN_FALLBACK_URLS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|url| !url.is_empty())
+                .map(str::to_string)
+                .collect(),
+            ws_url: optional_var("SOLANA_WS_URL", &mut invalid),
+            program_id,
+            private_key_path: optional_var("CHAIN_KEYPAIR_PATH", &mut invalid),
+            payer_keypairs: env::var("SOLANA_PAYER_KEYPAIRS")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|path| !path.is_empty())
+                .map(str::to_string)
+                .collect(),
+            use_pda: optional_var("SOLANA_USE_PDA", &mut invalid).unwrap_or(false),
+            lookup_table: optional_var("SOLANA_LOOKUP_TABLE", &mut invalid),
+            rent_strategy: optional_var("SOLANA_RENT_STRATEGY", &mut invalid).unwrap_or_default(),
+            max_send_attempts: optional_var("SOLANA_MAX_SEND_ATTEMPTS", &mut invalid).unwrap_or(3),
+            max_blockhash_resends: optional_var("SOLANA_MAX_BLOCKHASH_RESENDS", &mut invalid).unwrap_or(DEFAULT_MAX_BLOCKHASH_RESENDS),
+            retry_budget: optional_var("SOLANA_RETRY_BUDGET", &mut invalid).unwrap_or(DEFAULT_RETRY_BUDGET),
+            max_batch_records: optional_var("SOLANA_MAX_BATCH_RECORDS", &mut invalid).unwrap_or(DEFAULT_MAX_BATCH_RECORDS),
+            max_batch_bytes: optional_var("SOLANA_MAX_BATCH_BYTES", &mut invalid).unwrap_or(MAX_SOLANA_TRANSACTION_SIZE),
+            connect_retry: connect_retry(
+                optional_var("CHAIN_CONNECT_RETRIES", &mut invalid),
+                optional_var("CHAIN_CONNECT_BACKOFF_MS", &mut invalid),
+                optional_var("CHAIN_CONNECT_MAX_BACKOFF_MS", &mut invalid),
+                optional_var("CHAIN_CONNECT_JITTER", &mut invalid),
+            ),
+            rpc_timeout: optional_var("CHAIN_RPC_TIMEOUT_MS", &mut invalid)
+                .map(Duration::from_millis)
+                .unwrap_or(DEFAULT_RPC_TIMEOUT),
+            memo: optional_var("SOLANA_MEMO", &mut invalid).unwrap_or(false),
+            skip_preflight: optional_var("SOLANA_SKIP_PREFLIGHT", &mut invalid).unwrap_or(false),
+            max_rpc_retries: optional_var("SOLANA_MAX_RPC_RETRIES", &mut invalid),
+            jito_block_engine_url: optional_var("SOLANA_JITO_BLOCK_ENGINE_URL", &mut invalid),
+            jito_tip_lamports: optional_var("SOLANA_JITO_TIP_LAMPORTS", &mut invalid).unwrap_or(DEFAULT_JITO_TIP_LAMPORTS),
+            priority_fee: optional_var("SOLANA_PRIORITY_FEE", &mut invalid),
+            compute_unit_limit: optional_var("SOLANA_COMPUTE_UNIT_LIMIT", &mut invalid),
+            commitment: optional_var("CHAIN_COMMITMENT", &mut invalid).unwrap_or_default(),
+            max_fee_per_gas: optional_var("ETHEREUM_MAX_FEE_PER_GAS", &mut invalid),
+            max_priority_fee_per_gas: optional_var("ETHEREUM_MAX_PRIORITY_FEE_PER_GAS", &mut invalid),
+            confirmations: optional_var("ETHEREUM_CONFIRMATIONS", &mut invalid).unwrap_or(DEFAULT_CONFIRMATIONS),
+            logs_from_block: optional_var("ETHEREUM_LOGS_FROM_BLOCK", &mut invalid),
+            confirmation_timeout: optional_var("CHAIN_CONFIRMATION_TIMEOUT_SECS", &mut invalid)
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_CONFIRMATION_TIMEOUT),
+            wait_for_finalized: optional_var("SOLANA_WAIT_FOR_FINALIZED", &mut invalid).unwrap_or(false),
+            finalization_timeout: optional_var("SOLANA_FINALIZATION_TIMEOUT_SECS", &mut invalid)
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_FINALIZATION_TIMEOUT),
+            airdrop_below: optional_var("SOLANA_AIRDROP_BELOW", &mut invalid),
+            airdrop_lamports: optional_var("SOLANA_AIRDROP_LAMPORTS", &mut invalid).unwrap_or(DEFAULT_AIRDROP_LAMPORTS),
+            program_errors: optional_var("SOLANA_PROGRAM_ERRORS", &mut invalid).unwrap_or_default(),
+            simulate_transactions: optional_var("SOLANA_SIMULATE", &mut invalid).unwrap_or(false),
+            dry_run: optional_var("CHAIN_DRY_RUN", &mut invalid).unwrap_or(false),
+            max_in_flight: optional_var("CHAIN_MAX_IN_FLIGHT", &mut invalid).unwrap_or(DEFAULT_MAX_IN_FLIGHT),
+            idempotency_window: optional_var("CHAIN_IDEMPOTENCY_WINDOW_SECS", &mut invalid)
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_IDEMPOTENCY_WINDOW),
+            max_url_length: optional_var("CHAIN_MAX_URL_LENGTH", &mut invalid).unwrap_or(DEFAULT_MAX_URL_LENGTH),
+            max_record_size: optional_var("CHAIN_MAX_RECORD_SIZE", &mut invalid).unwrap_or(DEFAULT_MAX_RECORD_SIZE),
+            submitter: optional_var("CHAIN_SUBMITTER", &mut invalid),
+            submitter_keypair: optional_var("SOLANA_SUBMITTER_KEYPAIR", &mut invalid),
+            explorer_url: optional_var("CHAIN_EXPLORER_URL", &mut invalid),
+            ipfs_api_url: optional_var("CHAIN_IPFS_API_URL", &mut invalid),
+            skip_duplicates: optional_var("CHAIN_SKIP_DUPLICATES", &mut invalid).unwrap_or(false),
+            allow_close_proofs: optional_var("CHAIN_ALLOW_CLOSE_PROOFS", &mut invalid).unwrap_or(false),
+            index_path: optional_var("CHAIN_INDEX_PATH", &mut invalid),
+            retry_queue_path: optional_var("CHAIN_RETRY_QUEUE_PATH", &mut invalid),
+            audit_log_path: optional_var("CHAIN_AUDIT_LOG_PATH", &mut invalid),
+            balance_alert_below: optional_var("CHAIN_BALANCE_ALERT_BELOW", &mut invalid),
+            balance_alert_webhook: optional_var("CHAIN_BALANCE_ALERT_WEBHOOK", &mut invalid),
+            balance_alert_interval: optional_var("CHAIN_BALANCE_ALERT_INTERVAL_SECS", &mut invalid)
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_BALANCE_ALERT_INTERVAL),
+            startup_self_test: optional_var("CHAIN_STARTUP_SELF_TEST", &mut invalid).unwrap_or(false),
+        };
+
+        let mut problems = Vec::new();
+        if !missing.is_empty() {
+            problems.push(format!("missing required variables: {}", missing.join(", ")));
+        }
+        problems.extend(invalid);
+        if !problems.is_empty() {
+            return Err(BlockchainError::InvalidConfig(problems.join("; ")));
+        }
+
+        Ok(config)
+    }
+
+    /// Start a configuration for `network_url` and `program_id` with every other setting at
+    /// its default: Solana, confirmed commitment, no fees or limits beyond the defaults
+    pub fn builder(network_url: impl Into<String>, program_id: impl Into<String>) -> ChainConfigBuilder {
+        ChainConfigBuilder {
+            config: ChainConfig {
+                chain_type: ChainType::Solana,
+                network_url: network_url.into(),
+                fallback_urls: Vec::new(),
+                ws_url: None,
+                program_id: program_id.into(),
+                private_key_path: None,
+                payer_keypairs: Vec::new(),
+                use_pda: false,
+                lookup_table: None,
+                rent_strategy: RentStrategy::default(),
+                connect_retry: DEFAULT_CONNECT_RETRY,
+                rpc_timeout: DEFAULT_RPC_TIMEOUT,
+                max_send_attempts: 3,
+                max_blockhash_resends: DEFAULT_MAX_BLOCKHASH_RESENDS,
+                retry_budget: DEFAULT_RETRY_BUDGET,
+                max_batch_records: DEFAULT_MAX_BATCH_RECORDS,
+                max_batch_bytes: MAX_SOLANA_TRANSACTION_SIZE,
+                memo: false,
+                skip_preflight: false,
+                max_rpc_retries: None,
+                jito_block_engine_url: None,
+                jito_tip_lamports: DEFAULT_JITO_TIP_LAMPORTS,
+                priority_fee: None,
+                compute_unit_limit: None,
+                commitment: Commitment::default(),
+                max_fee_per_gas: None,
+                max_priority_fee_per_gas: None,
+                confirmations: DEFAULT_CONFIRMATIONS,
+                logs_from_block: None,
+                confirmation_timeout: DEFAULT_CONFIRMATION_TIMEOUT,
+                wait_for_finalized: false,
+                finalization_timeout: DEFAULT_FINALIZATION_TIMEOUT,
+                airdrop_below: None,
+                airdrop_lamports: DEFAULT_AIRDROP_LAMPORTS,
+                program_errors: ProgramErrors::default(),
+                simulate_transactions: false,
+                dry_run: false,
+                max_in_flight: DEFAULT_MAX_IN_FLIGHT,
+                idempotency_window: DEFAULT_IDEMPOTENCY_WINDOW,
+                max_url_length: DEFAULT_MAX_URL_LENGTH,
+                max_record_size: DEFAULT_MAX_RECORD_SIZE,
+                submitter: None,
+                submitter_keypair: None,
+                explorer_url: None,
+                ipfs_api_url: None,
+                skip_duplicates: false,
+                allow_close_proofs: false,
+                index_path: None,
+                retry_queue_path: None,
+                audit_log_path: None,
+                balance_alert_below: None,
+                balance_alert_webhook: None,
+                balance_alert_interval: DEFAULT_BALANCE_ALERT_INTERVAL,
+                startup_self_test: false,
+            },
+        }
+    }
+
+    /// Reject settings no provider can run with. Providers check again on construction, so
+    /// configurations assembled field by field are held to the same rules.
+    pub fn validate(&self) -> BlockchainResult<()> {
+        let invalid = |reason: String| Err(BlockchainError::InvalidConfig(reason));
+
+        if self.network_url.trim().is_empty() {
+            return invalid("CHAIN_NETWORK_URL is required".to_string());
+        }
+        if self.program_id.trim().is_empty() {
+            return invalid("CHAIN_PROGRAM_ID is required".to_string());
+        }
+        if let Some(ws_url) = self.ws_url.as_deref() {
+            match Url::parse(ws_url) {
+                Ok(parsed) if matches!(parsed.scheme(), "ws" | "wss") => {}
+                Ok(parsed) => return invalid(format!("SOLANA_WS_URL must use ws:// or wss://, not {}://", parsed.scheme())),
+                Err(e) => return invalid(format!("Invalid SOLANA_WS_URL: {}", e)),
+            }
+        }
+        if let Some(block_engine_url) = self.jito_block_engine_url.as_deref() {
+            match Url::parse(block_engine_url) {
+                Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+                Ok(parsed) => {
+                    return invalid(format!("SOLANA_JITO_BLOCK_ENGINE_URL must use http:// or https://, not {}://", parsed.scheme()))
+                }
+                Err(e) => return invalid(format!("Invalid SOLANA_JITO_BLOCK_ENGINE_URL: {}", e)),
+            }
+            if self.jito_tip_lamports < MIN_JITO_TIP_LAMPORTS {
+                return invalid(format!(
+                    "SOLANA_JITO_TIP_LAMPORTS must be at least {}, the smallest tip Jito accepts, not {}",
+                    MIN_JITO_TIP_LAMPORTS, self.jito_tip_lamports
+                ));
+            }
+        }
+        if self.submitter.is_some() && self.submitter_keypair.is_some() {
+            return invalid("CHAIN_SUBMITTER and SOLANA_SUBMITTER_KEYPAIR both set the submitter; set one".to_string());
+        }
+        if let (ChainType::Solana, Some(submitter)) = (&self.chain_type, self.submitter.as_deref()) {
+            if submitter.len() > MAX_SUBMITTER_LEN {
+                return invalid(format!(
+                    "CHAIN_SUBMITTER is {} bytes, longer than the {} byte limit",
+                    submitter.len(),
+                    MAX_SUBMITTER_LEN
+                ));
+            }
+        }
+        if let Some(template) = self.explorer_url.as_deref() {
+            if !template.contains(EXPLORER_TX_PLACEHOLDER) {
+                return invalid(format!(
+                    "CHAIN_EXPLORER_URL {:?} has no {} placeholder for the transaction ID",
+                    template, EXPLORER_TX_PLACEHOLDER
+                ));
+            }
+        }
+        if let RentStrategy::Padded { headroom } = self.rent_strategy {
+            if headroom > MAX_ACCOUNT_HEADROOM {
+                return invalid(format!(
+                    "SOLANA_RENT_STRATEGY headroom of {} bytes exceeds the {} byte limit",
+                    headroom, MAX_ACCOUNT_HEADROOM
+                ));
+            }
+        }
+        if self.max_batch_records == 0 {
+            return invalid("SOLANA_MAX_BATCH_RECORDS must be at least 1".to_string());
+        }
+        if self.max_batch_bytes == 0 || self.max_batch_bytes > MAX_SOLANA_TRANSACTION_SIZE {
+            return invalid(format!(
+                "SOLANA_MAX_BATCH_BYTES must be between 1 and {}, not {}",
+                MAX_SOLANA_TRANSACTION_SIZE, self.max_batch_bytes
+            ));
+        }
+        if self.confirmations == 0 {
+            return invalid("ETHEREUM_CONFIRMATIONS must be at least 1".to_string());
+        }
+        if self.chain_type != ChainType::Solana && self.logs_from_block.is_none() {
+            return invalid(format!(
+                "ETHEREUM_LOGS_FROM_BLOCK (logs_from_block in a profile) is required on {}; set it to the contract's deployment block",
+                self.chain_type
+            ));
+        }
+        if self.rpc_timeout.is_zero() {
+            return invalid("CHAIN_RPC_TIMEOUT_MS must be at least 1".to_string());
+        }
+        if self.confirmation_timeout.is_zero() {
+            return invalid("CHAIN_CONFIRMATION_TIMEOUT_SECS must be at least 1".to_string());
+        }
+        if self.finalization_timeout.is_zero() {
+            return invalid("SOLANA_FINALIZATION_TIMEOUT_SECS must be at least 1".to_string());
+        }
+        if self.balance_alert_interval.is_zero() {
+            return invalid("CHAIN_BALANCE_ALERT_INTERVAL_SECS must be at least 1".to_string());
+        }
+        if self.balance_alert_webhook.is_some() && self.balance_alert_below.is_none() {
+            return invalid("CHAIN_BALANCE_ALERT_WEBHOOK needs CHAIN_BALANCE_ALERT_BELOW".to_string());
+        }
+        if let (Some(max_fee), Some(priority_fee)) = (self.max_fee_per_gas, self.max_priority_fee_per_gas) {
+            if priority_fee > max_fee {
+                return invalid(format!(
+                    "ETHEREUM_MAX_PRIORITY_FEE_PER_GAS {} exceeds ETHEREUM_MAX_FEE_PER_GAS {}",
+                    priority_fee, max_fee
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Every configured RPC endpoint, primary first
+    pub fn rpc_urls(&self) -> Vec<String> {
+        std::iter::once(self.network_url.clone())
+            .chain(self.fallback_urls.iter().cloned())
+            .collect()
+    }
+}
+
+/// Fluent construction of a [`ChainConfig`], validated by [`build`](Self::build).
+/// Start one with [`ChainConfig::builder`].
+#[derive(Debug, Clone)]
+pub struct ChainConfigBuilder {
+    config: ChainConfig,
+}
+
+impl ChainConfigBuilder {
+    pub fn chain_type(mut self, chain_type: ChainType) -> Self {
+        self.config.chain_type = chain_type;
+        self
+    }
+
+    pub fn fallback_urls(mut self, urls: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.config.fallback_urls = urls.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn ws_url(mut self, ws_url: impl Into<String>) -> Self {
+        self.config.ws_url = Some(ws_url.into());
+        self
+    }
+
+    pub fn private_key_path(mut self, path: impl Into<String>) -> Self {
+        self.config.private_key_path = Some(path.into());
+        self
+    }
+
+    pub fn payer_keypairs(mut self, paths: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.config.payer_keypairs = paths.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn use_pda(mut self, use_pda: bool) -> Self {
+        self.config.use_pda = use_pda;
+        self
+    }
+
+    pub fn lookup_table(mut self, address: impl Into<String>) -> Self {
+        self.config.lookup_table = Some(address.into());
+        self
+    }
+
+    pub fn connect_retry(mut self, connect_retry: ConnectRetry) -> Self {
+        self.config.connect_retry = connect_retry;
+        self
+    }
+
+    pub fn rpc_timeout(mut self, timeout: Duration) -> Self {
+        self.config.rpc_timeout = timeout;
+        self
+    }
+
+    pub fn max_send_attempts(mut self, attempts: u32) -> Self {
+        self.config.max_send_attempts = attempts;
+        self
+    }
+
+    pub fn max_blockhash_resends(mut self, resends: u32) -> Self {
+        self.config.max_blockhash_resends = resends;
+        self
+    }
+
+    pub fn retry_budget(mut self, retries_per_minute: u32) -> Self {
+        self.config.retry_budget = retries_per_minute;
+        self
+    }
+
+    /// Split batches into transactions of at most `records` records and `bytes` serialized bytes
+    pub fn max_batch_size(mut self, records: usize, bytes: usize) -> Self {
+        self.config.max_batch_records = records;
+        self.config.max_batch_bytes = bytes;
+        self
+    }
+
+    pub fn memo(mut self, memo: bool) -> Self {
+        self.config.memo = memo;
+        self
+    }
+
+    pub fn skip_preflight(mut self, skip_preflight: bool) -> Self {
+        self.config.skip_preflight = skip_preflight;
+        self
+    }
+
+    pub fn max_rpc_retries(mut self, retries: usize) -> Self {
+        self.config.max_rpc_retries = Some(retries);
+        self
+    }
+
+    /// Submit stores as bundles to the Jito block engine at `url`, tipping `tip_lamports` each
+    pub fn jito(mut self, url: impl Into<String>, tip_lamports: u64) -> Self {
+        self.config.jito_block_engine_url = Some(url.into());
+        self.config.jito_tip_lamports = tip_lamports;
+        self
+    }
+
+    pub fn priority_fee(mut self, priority_fee: PriorityFee) -> Self {
+        self.config.priority_fee = Some(priority_fee);
+        self
+    }
+
+    pub fn rent_strategy(mut self, rent_strategy: RentStrategy) -> Self {
+        self.config.rent_strategy = rent_strategy;
+        self
+    }
+
+    pub fn compute_unit_limit(mut self, limit: u32) -> Self {
+        self.config.compute_unit_limit = Some(limit);
+        self
+    }
+
+    pub fn commitment(mut self, commitment: Commitment) -> Self {
+        self.config.commitment = commitment;
+        self
+    }
+
+    pub fn max_fee_per_gas(mut self, wei: u64) -> Self {
+        self.config.max_fee_per_gas = Some(wei);
+        self
+    }
+
+    pub fn max_priority_fee_per_gas(mut self, wei: u64) -> Self {
+        self.config.max_priority_fee_per_gas = Some(wei);
+        self
+    }
+
+    pub fn confirmations(mut self, confirmations: u64) -> Self {
+        self.config.confirmations = confirmations;
+        self
+    }
+
+    pub fn logs_from_block(mut self, block: u64) -> Self {
+        self.config.logs_from_block = Some(block);
+        self
+    }
+
+    pub fn confirmation_timeout(mut self, timeout: Duration) -> Self {
+        self.config.confirmation_timeout = timeout;
+        self
+    }
+
+    /// Wait up to `timeout` for each confirmed store to be finalized before it succeeds
+    pub fn wait_for_finalized(mut self, timeout: Duration) -> Self {
+        self.config.wait_for_finalized = true;
+        self.config.finalization_timeout = timeout;
+        self
+    }
+
+    /// Airdrop `lamports` to a payer whose balance is below `below` (devnet and local validators only)
+    pub fn airdrop(mut self, below: u64, lamports: u64) -> Self {
+        self.config.airdrop_below = Some(below);
+        self.config.airdrop_lamports = lamports;
+        self
+    }
+
+    pub fn program_errors(mut self, program_errors: ProgramErrors) -> Self {
+        self.config.program_errors = program_errors;
+        self
+    }
+
+    pub fn simulate_transactions(mut self, simulate: bool) -> Self {
+        self.config.simulate_transactions = simulate;
+        self
+    }
+
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.config.dry_run = dry_run;
+        self
+    }
+
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.config.max_in_flight = max_in_flight;
+        self
+    }
+
+    pub fn idempotency_window(mut self, window: Duration) -> Self {
+        self.config.idempotency_window = window;
+        self
+    }
+
+    pub fn max_url_length(mut self, bytes: usize) -> Self {
+        self.config.max_url_length = bytes;
+        self
+    }
+
+    pub fn max_record_size(mut self, bytes: usize) -> Self {
+        self.config.max_record_size = bytes;
+        self
+    }
+
+    pub fn submitter(mut self, submitter: impl Into<String>) -> Self {
+        self.config.submitter = Some(submitter.into());
+        self
+    }
+
+    pub fn submitter_keypair(mut self, path: impl Into<String>) -> Self {
+        self.config.submitter_keypair = Some(path.into());
+        self
+    }
+
+    /// Explorer URL template with `{tx}` in place of the transaction ID
+    pub fn explorer_url(mut self, template: impl Into<String>) -> Self {
+        self.config.explorer_url = Some(template.into());
+        self
+    }
+
+    pub fn ipfs_api_url(mut self, url: impl Into<String>) -> Self {
+        self.config.ipfs_api_url = Some(url.into());
+        self
+    }
+
+    pub fn skip_duplicates(mut self, skip_duplicates: bool) -> Self {
+        self.config.skip_duplicates = skip_duplicates;
+        self
+    }
+
+    pub fn allow_close_proofs(mut self, allow: bool) -> Self {
+        self.config.allow_close_proofs = allow;
+        self
+    }
+
+    pub fn index_path(mut self, path: impl Into<String>) -> Self {
+        self.config.index_path = Some(path.into());
+        self
+    }
+
+    pub fn retry_queue_path(mut self, path: impl Into<String>) -> Self {
+        self.config.retry_queue_path = Some(path.into());
+        self
+    }
+
+    pub fn audit_log_path(mut self, path: impl Into<String>) -> Self {
+        self.config.audit_log_path = Some(path.into());
+        self
+    }
+
+    /// Check the payer balance every `interval` and alert when it is below `below`
+    pub fn balance_alert(mut self, below: u64, interval: Duration) -> Self {
+        self.config.balance_alert_below = Some(below);
+        self.config.balance_alert_interval = interval;
+        self
+    }
+
+    pub fn balance_alert_webhook(mut self, url: impl Into<String>) -> Self {
+        self.config.balance_alert_webhook = Some(url.into());
+        self
+    }
+
+    pub fn startup_self_test(mut self, self_test: bool) -> Self {
+        self.config.startup_self_test = self_test;
+        self
+    }
+
+    /// Finish the configuration, failing with `InvalidConfig` as [`ChainConfig::validate`] does
+    pub fn build(self) -> BlockchainResult<ChainConfig> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+/// Config file holding one `[profiles.<name>]` table per chain/network
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    default_profile: Option<String>,
+    #[serde(default)]
+    profiles: HashMap<String, ProfileConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ProfileConfig {
+    chain_type: ChainType,
+    network_url: String,
+    #[serde(default)]
+    fallback_urls: Vec<String>,
+    ws_url: Option<String>,
+    program_id: Option<String>,
+    private_key_path: Option<String>,
+    #[serde(default)]
+    payer_keypairs: Vec<String>,
+    #[serde(default)]
+    use_pda: bool,
+    lookup_table: Option<String>,
+    #[serde(default)]
+    rent_strategy: RentStrategy,
+    connect_retries: Option<u32>,
+    connect_backoff_ms: Option<u64>,
+    connect_max_backoff_ms: Option<u64>,
+    connect_jitter: Option<bool>,
+    rpc_timeout_ms: Option<u64>,
+    max_send_attempts: Option<u32>,
+    max_blockhash_resends: Option<u32>,
+    retry_budget: Option<u32>,
+    max_batch_records: Option<usize>,
+    max_batch_bytes: Option<usize>,
+    #[serde(default)]
+    memo: bool,
+    #[serde(default)]
+    skip_preflight: bool,
+    max_rpc_retries: Option<usize>,
+    jito_block_engine_url: Option<String>,
+    jito_tip_lamports: Option<u64>,
+    max_fee_per_gas: Option<u64>,
+    max_priority_fee_per_gas: Option<u64>,
+    confirmations: Option<u64>,
+    logs_from_block: Option<u64>,
+    confirmation_timeout_secs: Option<u64>,
+    #[serde(default)]
+    wait_for_finalized: bool,
+    finalization_timeout_secs: Option<u64>,
+    priority_fee: Option<PriorityFee>,
+    compute_unit_limit: Option<u32>,
+    #[serde(default)]
+    commitment: Commitment,
+    airdrop_below: Option<u64>,
+    airdrop_lamports: Option<u64>,
+    #[serde(default)]
+    program_errors: ProgramErrors,
+    #[serde(default)]
+    simulate_transactions: bool,
+    #[serde(default)]
+    dry_run: bool,
+    max_in_flight: Option<usize>,
+    idempotency_window_secs: Option<u64>,
+    max_url_length: Option<usize>,
+    max_record_size: Option<usize>,
+    submitter: Option<String>,
+    submitter_keypair: Option<String>,
+    explorer_url: Option<String>,
+    ipfs_api_url: Option<String>,
+    #[serde(default)]
+    skip_duplicates: bool,
+    #[serde(default)]
+    allow_close_proofs: bool,
+    index_path: Option<String>,
+    retry_queue_path: Option<String>,
+    audit_log_path: Option<String>,
+    balance_alert_below: Option<u64>,
+    balance_alert_webhook: Option<String>,
+    balance_alert_interval_secs: Option<u64>,
+    #[serde(default)]
+    startup_self_test: bool,
+}
+
+impl ChainConfig {
+    /// Load the named profile from a TOML config file, falling back to the
+    /// file's `default_profile` when no profile is given
+    pub fn from_toml(path: impl AsRef<Path>, profile: Option<&str>) -> BlockchainResult<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| BlockchainError::InvalidConfig(format!("Failed to read config file {}: {}", path.display(), e)))?;
+        let mut file: ConfigFile = toml::from_str(&contents)
+            .map_err(|e| BlockchainError::InvalidConfig(format!("Failed to parse config file {}: {}", path.display(), e)))?;
+
+        let name = match profile.map(str::to_string).or(file.default_profile) {
+            Some(name) => name,
+            None => {
+                return Err(BlockchainError::InvalidConfig(format!(
+                    "No profile selected for {} and no default_profile set",
+                    path.display()
+                )))
+            }
+        };
+        let profile = match file.profiles.remove(&name) {
+            Some(profile) => profile,
+            None => {
+                let mut available: Vec<_> = file.profiles.keys().cloned().collect();
+                available.sort();
+                return Err(BlockchainError::InvalidConfig(format!(
+                    "Profile {:?} not found in {} (available: {})",
+                    name,
+                    path.display(),
+                    available.join(", ")
+                )));
+            }
+        };
+
+        let program_id = match (profile.program_id, &profile.chain_type) {
+            (Some(program_id), _) => program_id,
+            (None, ChainType::Solana) => {
+                return Err(BlockchainError::InvalidConfig(format!(
+                    "Profile {:?} is a solana profile but has no program_id",
+                    name
+                )))
+            }
+            (None, chain_type @ (ChainType::Ethereum | ChainType::Evm { .. })) => {
+                return Err(BlockchainError::InvalidConfig(format!(
+                    "Profile {:?} is an {} profile but has no contract address in program_id",
+                    name, chain_type
+                )))
+            }
+        };
+
+        Ok(ChainConfig {
+            chain_type: profile.chain_type,
+            network_url: profile.network_url,
+            fallback_urls: profile.fallback_urls,
+            ws_url: profile.ws_url,
+            program_id,
+            private_key_path: profile.private_key_path,
+            payer_keypairs: profile.payer_keypairs,
+            use_pda: profile.use_pda,
+            lookup_table: profile.lookup_table,
+            rent_strategy: profile.rent_strategy,
+            max_send_attempts: profile.max_send_attempts.unwrap_or(3),
+            max_blockhash_resends: profile.max_blockhash_resends.unwrap_or(DEFAULT_MAX_BLOCKHASH_RESENDS),
+            retry_budget: profile.retry_budget.unwrap_or(DEFAULT_RETRY_BUDGET),
+            max_batch_records: profile.max_batch_records.unwrap_or(DEFAULT_MAX_BATCH_RECORDS),
+            max_batch_bytes: profile.max_batch_bytes.unwrap_or(MAX_SOLANA_TRANSACTION_SIZE),
+            connect_retry: connect_retry(
+                profile.connect_retries,
+                profile.connect_backoff_ms,
+                profile.connect_max_backoff_ms,
+                profile.connect_jitter,
+            ),
+            rpc_timeout: profile.rpc_timeout_ms.map(Duration::from_millis).unwrap_or(DEFAULT_RPC_TIMEOUT),
+            memo: profile.memo,
+            skip_preflight: profile.skip_preflight,
+            max_rpc_retries: profile.max_rpc_retries,
+            jito_block_engine_url: profile.jito_block_engine_url,
+            jito_tip_lamports: profile.jito_tip_lamports.unwrap_or(DEFAULT_JITO_TIP_LAMPORTS),
+            max_fee_per_gas: profile.max_fee_per_gas,
+            max_priority_fee_per_gas: profile.max_priority_fee_per_gas,
+            confirmations: profile.confirmations.unwrap_or(DEFAULT_CONFIRMATIONS),
+            logs_from_block: profile.logs_from_block,
+            confirmation_timeout: profile
+                .confirmation_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_CONFIRMATION_TIMEOUT),
+            wait_for_finalized: profile.wait_for_finalized,
+            finalization_timeout: profile
+                .finalization_timeout_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_FINALIZATION_TIMEOUT),
+            priority_fee: profile.priority_fee,
+            compute_unit_limit: profile.compute_unit_limit,
+            commitment: profile.commitment,
+            airdrop_below: profile.airdrop_below,
+            airdrop_lamports: profile.airdrop_lamports.unwrap_or(DEFAULT_AIRDROP_LAMPORTS),
+            program_errors: profile.program_errors,
+            simulate_transactions: profile.simulate_transactions,
+            dry_run: profile.dry_run,
+            max_in_flight: profile.max_in_flight.unwrap_or(DEFAULT_MAX_IN_FLIGHT),
+            idempotency_window: profile
+                .idempotency_window_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_IDEMPOTENCY_WINDOW),
+            max_url_length: profile.max_url_length.unwrap_or(DEFAULT_MAX_URL_LENGTH),
+            max_record_size: profile.max_record_size.unwrap_or(DEFAULT_MAX_RECORD_SIZE),
+            submitter: profile.submitter,
+            submitter_keypair: profile.submitter_keypair,
+            explorer_url: profile.explorer_url,
+            ipfs_api_url: profile.ipfs_api_url,
+            skip_duplicates: profile.skip_duplicates,
+            allow_close_proofs: profile.allow_close_proofs,
+            index_path: profile.index_path,
+            retry_queue_path: profile.retry_queue_path,
+            audit_log_path: profile.audit_log_path,
+            balance_alert_below: profile.balance_alert_below,
+            balance_alert_webhook: profile.balance_alert_webhook,
+            balance_alert_interval: profile
+                .balance_alert_interval_secs
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_BALANCE_ALERT_INTERVAL),
+            startup_self_test: profile.startup_self_test,
+        })
+    }
+}
+
+/// Retry policy from its optional settings; without a cap the delay grows to the default cap,
+/// or stays fixed when it starts above it
+fn connect_retry(
+    retries: Option<u32>,
+    backoff_ms: Option<u64>,
+    max_backoff_ms: Option<u64>,
+    jitter: Option<bool>,
+) -> ConnectRetry {
+    let initial_backoff = backoff_ms.map(Duration::from_millis).unwrap_or(DEFAULT_CONNECT_RETRY.initial_backoff);
+    ConnectRetry {
+        retries: retries.unwrap_or(DEFAULT_CONNECT_RETRY.retries),
+        initial_backoff,
+        max_backoff: max_backoff_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_CONNECT_RETRY.max_backoff.max(initial_backoff)),
+        jitter: jitter.unwrap_or(DEFAULT_CONNECT_RETRY.jitter),
+    }
+}
+
+fn required_var(name: &str, missing: &mut Vec<String>) -> String {
+    match env::var(name) {
+        Ok(value) if !value.trim().is_empty() => value.trim().to_string(),
+        _ => {
+            missing.push(name.to_string());
+            String::new()
+        }
+    }
+}
+
+/// Parse an optional variable, treating an empty value as unset
+fn optional_var<T>(name: &str, invalid: &mut Vec<String>) -> Option<T>
+where
+    T: FromStr,
+    T::Err: fmt::Display,
+{
+    let value = env::var(name).ok().filter(|v| !v.trim().is_empty())?;
+    match value.trim().parse() {
+        Ok(parsed) => Some(parsed),
+        Err(e) => {
+            invalid.push(format!("{}: {}", name, e));
+            None
+        }
+    }
 }
 
 /// Simplified blockchain interface for content storage only
 #[async_trait]
 pub trait BlockchainProvider: Send + Sync {
-    /// Store a content record on the blockchain
-    async fn store_record(&self, record: &ContentRecord) -> BlockchainResult<TransactionResult>;
+    /// Chain this provider stores proofs on
+    fn chain_type(&self) -> ChainType;
+
+    /// Wait until the network is reachable; called once at startup
+    async fn initialize(&self) -> BlockchainResult<()> {
+        self.check_health().await
+    }
+
+    /// Probe whether the network connection is currently usable
+    async fn check_health(&self) -> BlockchainResult<()>;
+
+    /// Describe the chain and network this provider is connected to
+    async fn get_network_info(&self) -> BlockchainResult<NetworkInfo>;
+
+    /// What storing this record would cost right now, without submitting anything
+    async fn estimate_fee(&self, record: &ContentRecord) -> BlockchainResult<FeeEstimate>;
+
+    /// Store a content record on the blockchain. `options.dry_run` (or the configured
+    /// dry-run mode) builds and simulates the transaction without submitting it.
+    async fn store_record(&self, record: &ContentRecord, options: &StoreOptions) -> BlockchainResult<TransactionResult>;
+
+    /// Store several records, packing as many into each transaction as the chain allows.
+    /// Each chunk reports its own outcome, so earlier confirmed chunks survive later failures.
+    async fn store_batch(&self, records: &[ContentRecord]) -> Vec<BatchChunkResult> {
+        let mut chunks = Vec::with_capacity(records.len());
+        for (index, record) in records.iter().enumerate() {
+            let result = self.store_record(record, &StoreOptions::default()).await;
+            chunks.push(BatchChunkResult {
+                record_indices: vec![index],
+                account_addresses: result.iter().filter_map(|result| result.account_address.clone()).collect(),
+                result,
+            });
+        }
+        chunks
+    }
+
+    /// Read a content record back from the account it was stored in.
+    /// Fails with `AccountNotFound` when no such account exists.
+    async fn retrieve_record(&self, account_address: &str) -> BlockchainResult<ContentRecord>;
+
+    /// Current status of each submitted transaction, in the order given
+    async fn transaction_statuses(&self, transaction_ids: &[String]) -> BlockchainResult<Vec<TransactionStatus>>;
+
+    /// Decode the proofs a confirmed transaction stored. Fails with `TransactionNotFound`
+    /// for unknown transactions and `NotAProof` for transactions that stored none.
+    async fn get_proof_by_transaction(&self, transaction_id: &str) -> BlockchainResult<TransactionProofs>;
+
+    /// Current balance of the accounts paying for stores
+    async fn get_balance(&self) -> BlockchainResult<BalanceInfo>;
+
+    /// Close the proof account at `account_address` and return its rent to a payer. Only
+    /// accounts owned by the proof program are closed. Fails with `Unsupported` on chains
+    /// that keep proofs in no account of their own.
+    async fn close_proof(&self, _account_address: &str) -> BlockchainResult<ClosedProof> {
+        Err(BlockchainError::Unsupported(format!(
+            "{} proofs are not stored in accounts that can be closed",
+            self.chain_type()
+        )))
+    }
+
+    /// Current balance of the account paying for stores, in the chain's smallest unit.
+    /// Returns `Ok(None)` when the provider does not report one.
+    async fn payer_balance(&self) -> BlockchainResult<Option<u64>> {
+        Ok(None)
+    }
+
+    /// Whether this gateway submitted `proof`: paid for by one of its own accounts and, where
+    /// proofs record one, under its own submitter. Providers that cannot tell answer false.
+    fn is_own_submission(&self, _proof: &DecodedProof) -> bool {
+        false
+    }
+
+    /// Derive the account address a record with this UID is stored at.
+    /// Returns `Ok(None)` when the provider does not use deterministic addresses.
+    fn derive_account_address(&self, _uid: &str) -> BlockchainResult<Option<String>> {
+        Ok(None)
+    }
+
+    /// Check a content record against the one stored at `account_address`.
+    /// Fails with `AccountNotFound` when no such account exists.
+    async fn verify_record(
+        &self,
+        record: &ContentRecord,
+        account_address: &str,
+    ) -> BlockchainResult<VerificationResult> {
+        let stored = self.retrieve_record(account_address).await?;
+        Ok(VerificationResult::compare(record, &stored))
+    }
+}
+ 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn development_networks_are_told_from_production() {
+        let mut network = NetworkInfo {
+            chain_type: ChainType::Solana,
+            network_url: "http://localhost:8899".to_string(),
+            genesis_hash: "local-genesis".to_string(),
+            latest_block: 0,
+            node_version: "test".to_string(),
+            chain_id: None,
+        };
+        assert!(network.is_development());
+        // Solana mainnet-beta
+        network.genesis_hash = "5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d".to_string();
+        assert!(!network.is_development());
+
+        network.chain_type = ChainType::Ethereum;
+        network.chain_id = Some(31337);
+        assert!(network.is_development());
+        network.chain_id = Some(1);
+        assert!(!network.is_development());
+    }
+
+    #[test]
+    fn evm_chains_need_the_block_to_search_logs_from() {
+        let contract = "0x0000000000000000000000000000000000000000";
+        for chain_type in [ChainType::Ethereum, ChainType::Evm { chain_id: 42161 }] {
+            let error = ChainConfig::builder("https://rpc.example.com", contract)
+                .chain_type(chain_type.clone())
+                .build()
+                .unwrap_err();
+            assert!(
+                matches!(&error, BlockchainError::InvalidConfig(message) if message.contains("ETHEREUM_LOGS_FROM_BLOCK")),
+                "unexpected error {:?}",
+                error
+            );
+            let config = ChainConfig::builder("https://rpc.example.com", contract)
+                .chain_type(chain_type)
+                .logs_from_block(0)
+                .build()
+                .unwrap();
+            assert_eq!(config.logs_from_block, Some(0));
+        }
+        // Solana reads proofs from accounts and never searches logs
+        ChainConfig::builder("http://127.0.0.1:8899", "B973pRh2NL9DkdvcT8VhSJywqqueknd8JXMkVVn18T3j")
+            .build()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn connect_retry_backs_off_exponentially_and_names_the_url() {
+        let retry = ConnectRetry {
+            retries: 3,
+            initial_backoff: Duration::from_millis(1),
+            max_backoff: Duration::from_millis(3),
+            jitter: false,
+        };
+        let delays: Vec<_> = (1..=3).map(|retry_number| retry.backoff(retry_number)).collect();
+        assert_eq!(delays, [1, 2, 3].map(Duration::from_millis));
+        let jittered = ConnectRetry { jitter: true, ..retry };
+        for _ in 0..100 {
+            let delay = jittered.jittered(Duration::from_secs(4));
+            assert!((Duration::from_secs(2)..=Duration::from_secs(4)).contains(&delay), "{:?}", delay);
+        }
+
+        let mut attempts = 0;
+        let error = retry
+            .connect("https://rpc.example.com/secret-key", || {
+                attempts += 1;
+                async { Err(BlockchainError::RpcUnavailable("connection refused".to_string())) }
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(attempts, 4);
+        match error {
+            BlockchainError::ConnectionFailed { url, attempts, .. } => {
+                assert_eq!(url, "https://rpc.example.com");
+                assert_eq!(attempts, 4);
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
 }
- 
\ No newline at end of file
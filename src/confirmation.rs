@@ -0,0 +1,180 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+use crate::blockchain::{BlockchainProvider, TransactionStatus};
+use crate::events::StoredEvents;
+use crate::metrics::{metrics, ConfirmationOutcome};
+
+/// Interval between status polls of pending transactions
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A transaction the node still has never seen this long after submission is given up on.
+/// Solana blockhashes expire after roughly 60-90 seconds, so it can no longer land.
+pub(crate) const UNSEEN_EXPIRY: Duration = Duration::from_secs(150);
+
+/// `commitment` label of tracked stores in `confirmation_seconds`; their latency is only as
+/// precise as `POLL_INTERVAL`
+const TRACKED_COMMITMENT: &str = "fire_and_forget";
+
+/// How long settled outcomes stay queryable
+const SETTLED_RETENTION: Duration = Duration::from_secs(3600);
+
+/// Upper bound on tracked transactions; settled entries are evicted first, then the oldest
+const MAX_ENTRIES: usize = 10_000;
+
+/// Status of a transaction submitted without waiting for confirmation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrackedStatus {
+    Pending,
+    Confirmed {
+        block_height: Option<u64>,
+        /// Time from submission until a poll saw the confirmation
+        confirmation_time: Duration,
+    },
+    Failed(String),
+    /// Never seen by the node before `UNSEEN_EXPIRY`
+    Expired,
+}
+
+/// Record carried by a tracked transaction, announced to `StoredEvents` once it confirms
+#[derive(Debug, Clone)]
+pub struct TrackedRecord {
+    pub uid: String,
+    /// Empty when unknown
+    pub account_address: String,
+}
+
+struct Entry {
+    submitted_at: Instant,
+    settled_at: Option<Instant>,
+    status: TrackedStatus,
+    records: Vec<TrackedRecord>,
+}
+
+/// Follows fire-and-forget stores to their final outcome by polling the provider in the background.
+///
+/// Like the idempotency cache the tracker lives only in this process. With
+/// `CHAIN_RETRY_QUEUE_PATH` set, transactions submitted before a restart are still settled:
+/// `reconcile_pending_stores` picks them up from the retry queue.
+pub struct ConfirmationTracker {
+    entries: Mutex<HashMap<String, Entry>>,
+    events: StoredEvents,
+}
+
+impl ConfirmationTracker {
+    pub fn new(events: StoredEvents) -> Self {
+        Self {
+            entries: Mutex::default(),
+            events,
+        }
+    }
+
+    /// Start following a transaction that was just submitted, carrying `records`
+    pub fn track(&self, transaction_id: String, records: Vec<TrackedRecord>) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= MAX_ENTRIES {
+            entries.retain(|_, entry| entry.settled_at.is_none_or(|settled_at| now - settled_at < SETTLED_RETENTION));
+        }
+        if entries.len() >= MAX_ENTRIES {
+            let evicted = entries
+                .iter()
+                .min_by_key(|(_, entry)| (entry.settled_at.is_none(), entry.submitted_at))
+                .map(|(id, _)| id.clone());
+            if let Some(evicted) = evicted {
+                entries.remove(&evicted);
+            }
+        }
+        entries.insert(
+            transaction_id,
+            Entry {
+                submitted_at: now,
+                settled_at: None,
+                status: TrackedStatus::Pending,
+                records,
+            },
+        );
+    }
+
+    /// Last known status, or `None` for transactions this tracker never saw
+    pub fn status(&self, transaction_id: &str) -> Option<TrackedStatus> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries.get(transaction_id).map(|entry| entry.status.clone())
+    }
+
+    /// Poll the provider until the process exits
+    pub async fn run(self: Arc<Self>, provider: Arc<dyn BlockchainProvider>) {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.poll(provider.as_ref()).await;
+        }
+    }
+
+    /// Refresh every pending transaction once
+    pub async fn poll(&self, provider: &dyn BlockchainProvider) {
+        let pending: Vec<String> = {
+            let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+            entries
+                .iter()
+                .filter(|(_, entry)| entry.status == TrackedStatus::Pending)
+                .map(|(id, _)| id.clone())
+                .collect()
+        };
+        if pending.is_empty() {
+            return;
+        }
+
+        let statuses = match provider.transaction_statuses(&pending).await {
+            Ok(statuses) => statuses,
+            Err(e) => {
+                warn!(pending = pending.len(), error = %e, "Failed to poll transaction statuses");
+                return;
+            }
+        };
+
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        for (id, status) in pending.iter().zip(statuses) {
+            let Some(entry) = entries.get_mut(id) else {
+                continue;
+            };
+            entry.status = match status {
+                TransactionStatus::Pending => continue,
+                TransactionStatus::Unknown if now - entry.submitted_at < UNSEEN_EXPIRY => continue,
+                TransactionStatus::Unknown => {
+                    warn!(transaction_id = %id, "Submitted transaction never landed");
+                    metrics().record_confirmation(TRACKED_COMMITMENT, ConfirmationOutcome::Expired, now - entry.submitted_at);
+                    TrackedStatus::Expired
+                }
+                TransactionStatus::Confirmed { block_height } => {
+                    let confirmation_time = now - entry.submitted_at;
+                    info!(
+                        transaction_id = %id,
+                        block_height = ?block_height,
+                        confirmation_ms = confirmation_time.as_millis() as u64,
+                        "Submitted transaction confirmed"
+                    );
+                    metrics().record_confirmation(TRACKED_COMMITMENT, ConfirmationOutcome::Confirmed, confirmation_time);
+                    for record in &entry.records {
+                        self.events.publish(&provider.chain_type(), &record.uid, id, &record.account_address, block_height);
+                    }
+                    TrackedStatus::Confirmed {
+                        block_height,
+                        confirmation_time,
+                    }
+                }
+                TransactionStatus::Failed(reason) => {
+                    warn!(transaction_id = %id, reason = %reason, "Submitted transaction failed");
+                    metrics().record_confirmation(TRACKED_COMMITMENT, ConfirmationOutcome::Failed, now - entry.submitted_at);
+                    TrackedStatus::Failed(reason)
+                }
+            };
+            entry.settled_at = Some(now);
+        }
+        debug!(pending = pending.len(), "Polled transaction statuses");
+    }
+}
@@ -0,0 +1,126 @@
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use futures::Stream;
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::sync::watch;
+use tonic::Status;
+use tracing::warn;
+
+use crate::blockchain::ChainType;
+use crate::proto::{self, StoredEvent};
+
+/// Events held for a subscriber that is not keeping up; past this it loses the oldest
+const EVENT_BUFFER: usize = 1024;
+
+/// Fan-out of confirmed proofs to `SubscribeStored` callers. Publishing never waits on
+/// subscribers: with none the event is dropped, and one that falls `EVENT_BUFFER` events
+/// behind skips ahead and is sent a lag notice counting what it missed.
+#[derive(Clone)]
+pub struct StoredEvents {
+    sender: broadcast::Sender<StoredEvent>,
+    /// Set at shutdown, ending every subscription so none holds the server open
+    closed: Arc<watch::Sender<bool>>,
+}
+
+impl StoredEvents {
+    pub fn new() -> Self {
+        Self::with_capacity(EVENT_BUFFER)
+    }
+
+    fn with_capacity(capacity: usize) -> Self {
+        Self {
+            sender: broadcast::channel(capacity).0,
+            closed: Arc::new(watch::channel(false).0),
+        }
+    }
+
+    /// Announce a record confirmed on `chain` to every current subscriber
+    pub fn publish(&self, chain: &ChainType, uid: &str, transaction_id: &str, account_address: &str, block_height: Option<u64>) {
+        let (chain, evm_chain_id) = match chain {
+            ChainType::Solana => (proto::Chain::Solana, 0),
+            ChainType::Ethereum => (proto::Chain::Ethereum, 0),
+            ChainType::Evm { chain_id } => (proto::Chain::Evm, *chain_id),
+        };
+        let event = StoredEvent {
+            uid: uid.to_string(),
+            transaction_id: transaction_id.to_string(),
+            account_address: account_address.to_string(),
+            block_height,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|since| since.as_secs() as i64)
+                .unwrap_or(0),
+            missed_events: 0,
+            chain: chain as i32,
+            evm_chain_id,
+        };
+        // Fails only when nobody is subscribed
+        let _ = self.sender.send(event);
+    }
+
+    /// End every subscription, current and future
+    pub fn close(&self) {
+        self.closed.send_replace(true);
+    }
+
+    /// Events published from now on, until `close` is called
+    pub fn subscribe(&self) -> impl Stream<Item = Result<StoredEvent, Status>> + Send + 'static {
+        let state = (self.sender.subscribe(), self.closed.subscribe());
+        futures::stream::unfold(state, |(mut receiver, mut closed)| async move {
+            let received = tokio::select! {
+                biased;
+                _ = closed.wait_for(|closed| *closed) => return None,
+                received = receiver.recv() => received,
+            };
+            let event = match received {
+                Ok(event) => event,
+                Err(RecvError::Lagged(missed)) => {
+                    warn!(missed, "Stored event subscriber fell behind, skipping ahead");
+                    crate::metrics::metrics().stored_events_missed.inc_by(missed);
+                    StoredEvent {
+                        missed_events: missed,
+                        ..Default::default()
+                    }
+                }
+                Err(RecvError::Closed) => return None,
+            };
+            Some((Ok(event), (receiver, closed)))
+        })
+    }
+}
+
+impl Default for StoredEvents {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+
+    #[tokio::test]
+    async fn slow_subscribers_get_a_lag_notice_instead_of_blocking_publishers() {
+        let events = StoredEvents::with_capacity(2);
+        events.publish(&ChainType::Solana, "before", "tx-0", "account-0", None);
+        let mut subscription = Box::pin(events.subscribe());
+        for index in 1..=5 {
+            events.publish(&ChainType::Solana, &format!("page-{}", index), &format!("tx-{}", index), "account", Some(index));
+        }
+
+        let notice = subscription.next().await.unwrap().unwrap();
+        assert_eq!(notice.missed_events, 3);
+        assert!(notice.uid.is_empty());
+        let event = subscription.next().await.unwrap().unwrap();
+        assert_eq!(event.uid, "page-4");
+        assert_eq!(event.transaction_id, "tx-4");
+        assert_eq!(event.block_height, Some(4));
+        assert_eq!(subscription.next().await.unwrap().unwrap().uid, "page-5");
+
+        events.close();
+        assert!(subscription.next().await.is_none());
+        assert!(Box::pin(events.subscribe()).next().await.is_none());
+    }
+}
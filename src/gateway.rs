@@ -0,0 +1,2577 @@
+use futures::StreamExt;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tokio::sync::{Semaphore, SemaphorePermit};
+use tonic::{Request, Response, Status, Streaming};
+use tracing::{debug, error, info, instrument, warn, Span};
+
+use crate::audit::{AuditEntry, AuditLog, AuditOutcome, AuditSink, Caller};
+use crate::blockchain::{
+    self, BatchChunkResult, BlockchainError, BlockchainProvider, BlockchainResult, ChainConfig, ChainType, Commitment, DecodedProof, StoreOptions,
+    TransactionResult, TransactionStatus, VerificationResult,
+};
+use crate::confirmation::{ConfirmationTracker, TrackedRecord, TrackedStatus, UNSEEN_EXPIRY};
+use crate::events::StoredEvents;
+use crate::idempotency::{Claim, IdempotencyCache, Reservation};
+use crate::index::{IndexedProof, ProofIndex};
+use crate::ipfs::IpfsClient;
+use crate::merkle::{self, MerkleTree, ProofStep};
+use crate::metrics;
+use crate::proto::{
+    self, chain_gateway_server::ChainGateway, BatchRecordStatus, BatchTransaction, CloseProofRequest, CloseProofResponse, ContentRecord, EstimateFeeRequest, EstimateFeeResponse,
+    GetBalanceRequest, GetBalanceResponse, GetProofByTransactionRequest, GetProofByTransactionResponse, GetTransactionStatusRequest,
+    GetTransactionStatusResponse, ListProofsRequest, ListProofsResponse, ListedProof, MerkleBatch, MerkleInclusion, NetworkInfoRequest, NetworkInfoResponse,
+    RetrieveRequest, RetrieveResponse, StoreBatchRequest, StoreBatchResponse, StoreItemResult, StoreRequest, StoreResponse, StoreStreamResponse,
+    StoredEvent, SubscribeStoredRequest,
+    TransactionProof, VerifyInclusionRequest, VerifyInclusionResponse, VerifyRequest, VerifyResponse,
+};
+use crate::providers;
+use crate::retry_queue::{PendingStore, QueuedStore, RetryQueue};
+
+/// ListProofs page size when the request sets none, and the largest accepted
+const DEFAULT_LIST_LIMIT: usize = 100;
+const MAX_LIST_LIMIT: usize = 1000;
+
+/// Records from a single StoreStream submitted to the provider at once
+const STORE_STREAM_CONCURRENCY: usize = 8;
+
+/// How long a store waits for an in-flight slot before being rejected
+const STORE_PERMIT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How often the retry queue is checked for due stores
+const RETRY_QUEUE_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Queued stores resubmitted per pass; the rest wait for the next tick
+const RETRY_QUEUE_BATCH: usize = 16;
+
+/// Persisted fire-and-forget stores checked per pass, one status lookup's worth on Solana
+const RECONCILE_BATCH: usize = 256;
+
+/// Response metadata naming the transaction of a store that timed out awaiting confirmation
+pub const TRANSACTION_ID_METADATA: &str = "x-transaction-id";
+
+/// An in-flight slot, reflected in the `stores_in_flight` gauge while held
+struct StorePermit<'a> {
+    _permit: SemaphorePermit<'a>,
+}
+
+impl Drop for StorePermit<'_> {
+    fn drop(&mut self) {
+        metrics::metrics().stores_in_flight.dec();
+    }
+}
+
+/// gRPC front end; every chain interaction goes through the `BlockchainProvider` trait,
+/// so this type holds no chain-specific logic
+pub struct MyChainGateway {
+    /// Primary chain: every request goes through it unless a store selects another chain
+    provider: Arc<dyn BlockchainProvider>,
+    /// Further chains stores can select with `StoreRequest.chain`
+    additional_chains: Vec<Arc<dyn BlockchainProvider>>,
+    /// Bounds the transactions submitted or awaiting confirmation at once
+    store_permits: Semaphore,
+    max_in_flight: usize,
+    /// Responses of recently completed stores, replayed for repeated idempotency keys
+    completed_stores: IdempotencyCache,
+    /// Fire-and-forget stores followed to their outcome in the background
+    confirmations: Arc<ConfirmationTracker>,
+    /// Proofs announced to `SubscribeStored` callers as they are confirmed
+    events: StoredEvents,
+    /// UID lookup for proofs at addresses that cannot be derived; `None` unless configured
+    index: Option<ProofIndex>,
+    /// Node `StoreRequest.content` is pinned to before the proof is stored; `None` unless configured
+    ipfs: Option<IpfsClient>,
+    /// Stores that failed while the chain was unreachable, resubmitted in the background; `None` unless configured
+    retry_queue: Option<RetryQueue>,
+    /// Where every store attempt is recorded; `None` unless configured
+    audit: Option<Arc<dyn AuditSink>>,
+    max_url_length: usize,
+    max_record_size: usize,
+    /// Return the existing proof for records already stored instead of storing them again
+    skip_duplicates: bool,
+    /// Serve `CloseProof`; off unless configured, since closing deletes the proof
+    allow_close_proofs: bool,
+}
+
+impl MyChainGateway {
+    pub fn new(config: ChainConfig) -> BlockchainResult<Self> {
+        let max_in_flight = config.max_in_flight;
+        let idempotency_window = config.idempotency_window;
+        let max_url_length = config.max_url_length;
+        let max_record_size = config.max_record_size;
+        let skip_duplicates = config.skip_duplicates;
+        let allow_close_proofs = config.allow_close_proofs;
+        let index = match config.index_path.as_deref() {
+            Some(path) => Some(ProofIndex::open(std::path::Path::new(path)).map_err(BlockchainError::InvalidConfig)?),
+            None => None,
+        };
+        let ipfs = match config.ipfs_api_url.as_deref() {
+            Some(url) => Some(IpfsClient::new(url).map_err(BlockchainError::InvalidConfig)?),
+            None => None,
+        };
+        let retry_queue = match config.retry_queue_path.as_deref() {
+            Some(path) => Some(RetryQueue::open(std::path::Path::new(path)).map_err(BlockchainError::InvalidConfig)?),
+            None => None,
+        };
+        let audit = match config.audit_log_path.as_deref() {
+            Some(path) => Some(AuditLog::open(std::path::Path::new(path)).map_err(BlockchainError::InvalidConfig)?),
+            None => None,
+        };
+        let provider = providers::create_provider(config)?;
+        let mut gateway = Self::with_provider(provider, max_in_flight, idempotency_window, max_url_length)
+            .with_max_record_size(max_record_size)
+            .with_skip_duplicates(skip_duplicates)
+            .with_allow_close_proofs(allow_close_proofs);
+        if let Some(index) = index {
+            gateway = gateway.with_index(index);
+        }
+        if let Some(ipfs) = ipfs {
+            gateway = gateway.with_ipfs(ipfs);
+        }
+        if let Some(retry_queue) = retry_queue {
+            gateway = gateway.with_retry_queue(retry_queue);
+        }
+        if let Some(audit) = audit {
+            gateway = gateway.with_audit_sink(Arc::new(audit));
+        }
+        Ok(gateway)
+    }
+
+    /// Build the gateway around an already constructed provider
+    pub fn with_provider(
+        provider: Arc<dyn BlockchainProvider>,
+        max_in_flight: usize,
+        idempotency_window: Duration,
+        max_url_length: usize,
+    ) -> Self {
+        let max_in_flight = max_in_flight.max(1);
+        let events = StoredEvents::new();
+        Self {
+            provider,
+            additional_chains: Vec::new(),
+            store_permits: Semaphore::new(max_in_flight),
+            max_in_flight,
+            completed_stores: IdempotencyCache::new(idempotency_window),
+            confirmations: Arc::new(ConfirmationTracker::new(events.clone())),
+            events,
+            index: None,
+            ipfs: None,
+            retry_queue: None,
+            audit: None,
+            max_url_length,
+            max_record_size: blockchain::DEFAULT_MAX_RECORD_SIZE,
+            skip_duplicates: false,
+            allow_close_proofs: false,
+        }
+    }
+
+    /// Also serve the chain `provider` is for, to stores that select it with `StoreRequest.chain`.
+    /// Fails when the gateway already serves that chain.
+    pub fn with_additional_chain(mut self, provider: Arc<dyn BlockchainProvider>) -> BlockchainResult<Self> {
+        let chain_type = provider.chain_type();
+        if self.chain_provider(&chain_type).is_some() {
+            return Err(BlockchainError::InvalidConfig(format!("Chain {} is configured more than once", chain_type)));
+        }
+        info!(chain = %chain_type, "Serving an additional chain");
+        self.additional_chains.push(provider);
+        Ok(self)
+    }
+
+    /// Reject records larger than `max_record_size` protobuf-encoded bytes
+    pub fn with_max_record_size(mut self, max_record_size: usize) -> Self {
+        self.max_record_size = max_record_size;
+        self
+    }
+
+    /// Answer stores of a URL and content hash that are already proven with the existing proof.
+    /// Duplicates are found through the proof index and, for providers with derived addresses, by UID.
+    pub fn with_skip_duplicates(mut self, skip_duplicates: bool) -> Self {
+        self.skip_duplicates = skip_duplicates;
+        self
+    }
+
+    /// Serve `CloseProof`, which deletes proof accounts to return their rent to the payer
+    pub fn with_allow_close_proofs(mut self, allow_close_proofs: bool) -> Self {
+        self.allow_close_proofs = allow_close_proofs;
+        self
+    }
+
+    /// Record every stored proof in `index` and resolve UIDs through it
+    pub fn with_index(mut self, index: ProofIndex) -> Self {
+        self.index = Some(index);
+        self
+    }
+
+    /// Pin `StoreRequest.content` to `ipfs` before storing its proof
+    pub fn with_ipfs(mut self, ipfs: IpfsClient) -> Self {
+        self.ipfs = Some(ipfs);
+        self
+    }
+
+    /// Queue stores that fail because the chain is unreachable instead of rejecting them
+    pub fn with_retry_queue(mut self, retry_queue: RetryQueue) -> Self {
+        self.retry_queue = Some(retry_queue);
+        self
+    }
+
+    /// Record every store attempt, successful or not, in `audit`
+    pub fn with_audit_sink(mut self, audit: Arc<dyn AuditSink>) -> Self {
+        self.audit = Some(audit);
+        self
+    }
+
+    /// Whether failed stores are queued for `run_retry_queue`
+    pub fn has_retry_queue(&self) -> bool {
+        self.retry_queue.is_some()
+    }
+
+    /// Follow a transaction the provider gave up waiting for, as for fire-and-forget stores,
+    /// returning its ID
+    fn track_unconfirmed<'e>(&self, e: &'e BlockchainError, records: Vec<TrackedRecord>) -> Option<&'e str> {
+        let BlockchainError::ConfirmationPending { transaction_id, .. } = e else {
+            return None;
+        };
+        self.confirmations.track(transaction_id.clone(), records);
+        Some(transaction_id)
+    }
+
+    /// Keep a fire-and-forget store in the retry queue file until `reconcile_pending_stores`
+    /// sees its transaction settle, so its outcome survives a restart
+    fn persist_pending(&self, record: &ContentRecord, result: &TransactionResult) {
+        let Some(queue) = &self.retry_queue else {
+            return;
+        };
+        let account_address = result.account_address.as_deref().unwrap_or_default();
+        if let Err(e) = queue.push_pending(&result.transaction_id, account_address, record, SystemTime::now()) {
+            warn!(error = %e, "Failed to persist pending transaction");
+        }
+    }
+
+    /// Record one store attempt in the audit log, if there is one; `detail` fills in the outcome
+    fn audit(
+        &self,
+        caller: &Caller,
+        rpc: &'static str,
+        record: &ContentRecord,
+        outcome: AuditOutcome,
+        detail: impl FnOnce(AuditEntry) -> AuditEntry,
+    ) {
+        if let Some(audit) = &self.audit {
+            audit.record(detail(AuditEntry::new(rpc, caller, record, outcome)));
+        }
+    }
+
+    /// Audit every record of a batch rejected as a whole
+    fn audit_batch_failure(&self, caller: &Caller, records: &[ContentRecord], status: &Status) {
+        for record in records {
+            self.audit(caller, "StoreBatch", record, AuditOutcome::Failed, |entry| entry.with_error(status));
+        }
+    }
+
+    /// Report one batch transaction's outcome, counting, auditing and indexing the records it carried
+    fn settle_batch_chunk(&self, caller: &Caller, records: &[ContentRecord], chunk: BatchChunkResult) -> BatchTransaction {
+        let metrics = metrics::metrics();
+        let mut transaction = BatchTransaction {
+            record_indices: chunk.record_indices.iter().map(|&index| index as u64).collect(),
+            account_addresses: chunk.account_addresses,
+            ..Default::default()
+        };
+        let record_count = chunk.record_indices.len() as u64;
+        match chunk.result {
+            Ok(result) => {
+                metrics.stores_succeeded.inc_by(record_count);
+                for &index in &chunk.record_indices {
+                    self.audit(caller, "StoreBatch", &records[index], AuditOutcome::Stored, |entry| {
+                        entry.with_transaction_id(result.transaction_id.as_str())
+                    });
+                }
+                for (&index, account_address) in chunk.record_indices.iter().zip(&transaction.account_addresses) {
+                    self.index_proof(
+                        &records[index],
+                        IndexedProof {
+                            account_address: account_address.clone(),
+                            transaction_id: result.transaction_id.clone(),
+                            block_height: result.block_height,
+                        },
+                    );
+                }
+                transaction.success = true;
+                transaction.transaction_id = result.transaction_id;
+                transaction.block_height = result.block_height;
+            }
+            Err(e) => {
+                error!(error = %e, records = ?chunk.record_indices, "Batch transaction failed");
+                let status = status_from_error(&e, "Failed to store on blockchain");
+                metrics.record_failures(status.code(), record_count);
+                transaction.error = status.message().to_string();
+                let tracked = chunk
+                    .record_indices
+                    .iter()
+                    .enumerate()
+                    .map(|(position, &index)| TrackedRecord {
+                        uid: records[index].uid.clone(),
+                        account_address: transaction.account_addresses.get(position).cloned().unwrap_or_default(),
+                    })
+                    .collect();
+                if let Some(transaction_id) = self.track_unconfirmed(&e, tracked) {
+                    transaction.transaction_id = transaction_id.to_string();
+                }
+                for &index in &chunk.record_indices {
+                    self.audit(caller, "StoreBatch", &records[index], AuditOutcome::Failed, |entry| {
+                        entry.with_transaction_id(transaction.transaction_id.as_str()).with_error(&status)
+                    });
+                }
+            }
+        }
+        transaction
+    }
+
+    /// Store only the Merkle root of `records`, in one transaction, and answer with the path
+    /// from each record to it. The records themselves are audited but not indexed: their
+    /// account holds the root, not them.
+    async fn store_merkle_root(&self, caller: &Caller, records: &[ContentRecord]) -> StoreBatchResponse {
+        let tree = MerkleTree::new(records.iter().map(merkle::leaf_hash).collect());
+        let root_record = merkle::root_record(&tree.root(), records.len());
+        let result = self.provider.store_record(&root_record, &StoreOptions::default()).await;
+        let account_address = result.as_ref().ok().and_then(|result| result.account_address.clone());
+        if let (Ok(result), Some(account_address)) = (&result, &account_address) {
+            self.index_proof(
+                &root_record,
+                IndexedProof {
+                    account_address: account_address.clone(),
+                    transaction_id: result.transaction_id.clone(),
+                    block_height: result.block_height,
+                },
+            );
+        }
+        // No addresses yet, so the records are not indexed under the root's account
+        let chunk = BatchChunkResult {
+            record_indices: (0..records.len()).collect(),
+            account_addresses: Vec::new(),
+            result,
+        };
+        let mut transaction = self.settle_batch_chunk(caller, records, chunk);
+        if let Some(account_address) = account_address {
+            transaction.account_addresses = vec![account_address; records.len()];
+        }
+        info!(root = %root_record.content_hash, records = records.len(), success = transaction.success, "Stored Merkle root of batch");
+        self.publish_batch(records, std::slice::from_ref(&transaction));
+
+        StoreBatchResponse {
+            records: record_statuses(records.len(), std::slice::from_ref(&transaction)),
+            transactions: vec![transaction],
+            merkle: Some(MerkleBatch {
+                root: root_record.content_hash,
+                inclusions: records
+                    .iter()
+                    .enumerate()
+                    .map(|(index, record)| MerkleInclusion {
+                        leaf: merkle::to_hex(&merkle::leaf_hash(record)),
+                        path: tree.path(index).into_iter().map(Into::into).collect(),
+                    })
+                    .collect(),
+            }),
+        }
+    }
+
+    /// Whether `proof` is a Merkle root this gateway stored: submitted by the gateway itself and
+    /// stored under the root's UID, or where the chain keeps no UID, at the account derived from it
+    fn is_own_merkle_root(&self, proof: &DecodedProof) -> bool {
+        if !proof.record.url.starts_with(merkle::MERKLE_ROOT_URL_PREFIX) || !self.provider.is_own_submission(proof) {
+            return false;
+        }
+        let uid = merkle::root_uid(&proof.record.content_hash);
+        if !proof.record.uid.is_empty() {
+            return proof.record.uid == uid;
+        }
+        match self.provider.derive_account_address(&uid) {
+            Ok(Some(address)) => proof.account_address.as_deref() == Some(address.as_str()),
+            Ok(None) => true,
+            Err(_) => false,
+        }
+    }
+
+    /// Announce every record of the confirmed batch `transactions`
+    fn publish_batch(&self, records: &[ContentRecord], transactions: &[BatchTransaction]) {
+        for transaction in transactions.iter().filter(|transaction| transaction.success) {
+            for (position, &index) in transaction.record_indices.iter().enumerate() {
+                self.events.publish(
+                    &self.provider.chain_type(),
+                    &records[index as usize].uid,
+                    &transaction.transaction_id,
+                    transaction.account_addresses.get(position).map(String::as_str).unwrap_or_default(),
+                    transaction.block_height,
+                );
+            }
+        }
+    }
+
+    /// Remember where a record was stored; the store itself already succeeded, so failures are only logged
+    fn index_proof(&self, record: &ContentRecord, proof: IndexedProof) {
+        let Some(index) = &self.index else {
+            return;
+        };
+        if record.uid.is_empty() || proof.account_address.is_empty() {
+            return;
+        }
+        if let Err(e) = index.insert(record, &proof) {
+            warn!(error = %e, "Failed to index stored proof");
+        }
+    }
+
+    /// Store a synthetic record and read it back, proving the program, payer and instruction
+    /// format all work before serving. Refuses to run on networks that are not for development,
+    /// where the record would cost real funds. The provider must already be initialized.
+    pub async fn self_test(&self) -> BlockchainResult<()> {
+        let network = self.provider.get_network_info().await?;
+        if !network.is_development() {
+            return Err(BlockchainError::InvalidConfig(format!(
+                "CHAIN_STARTUP_SELF_TEST only runs on local and development networks, not {} (genesis hash {})",
+                network.network_url, network.genesis_hash
+            )));
+        }
+
+        let id = uuid::Uuid::new_v4();
+        let record = ContentRecord {
+            uid: format!("self-test-{}", id),
+            url: format!("https://self-test.chain-gateway.invalid/{}", id),
+            ..Default::default()
+        };
+        let record = self
+            .checked_record(Some(&record), format!("chain-gateway self-test {}", id).as_bytes())
+            .map_err(|status| BlockchainError::InvalidRecord(status.message().to_string()))?;
+        info!(uid = %record.uid, "Running startup self-test");
+        let stored = self.provider.store_record(&record, &StoreOptions::default()).await?;
+
+        let result = match &stored.account_address {
+            Some(account_address) => self.provider.verify_record(&record, account_address).await?,
+            // Proofs without an account are read back from the transaction
+            None => {
+                let proofs = self.provider.get_proof_by_transaction(&stored.transaction_id).await?;
+                match proofs.proofs.first() {
+                    Some(proof) => VerificationResult::compare(&record, &proof.record),
+                    None => return Err(BlockchainError::NotAProof(stored.transaction_id)),
+                }
+            }
+        };
+        if !result.matches {
+            return Err(BlockchainError::Other(format!(
+                "Self-test record read back from transaction {} differs in {}",
+                stored.transaction_id,
+                result.mismatched_fields.join(", ")
+            )));
+        }
+        info!(transaction_id = %stored.transaction_id, "Startup self-test passed");
+        Ok(())
+    }
+
+    /// The provider every chain interaction goes through
+    pub fn provider(&self) -> &Arc<dyn BlockchainProvider> {
+        &self.provider
+    }
+
+    /// Chains added with `with_additional_chain`, besides the primary `provider`
+    pub fn additional_chains(&self) -> &[Arc<dyn BlockchainProvider>] {
+        &self.additional_chains
+    }
+
+    fn chain_provider(&self, chain_type: &ChainType) -> Option<&Arc<dyn BlockchainProvider>> {
+        std::iter::once(&self.provider)
+            .chain(&self.additional_chains)
+            .find(|provider| provider.chain_type() == *chain_type)
+    }
+
+    /// Provider of the chain `request` selects, the primary one when it selects none
+    fn requested_chain(&self, request: &StoreRequest) -> Result<&Arc<dyn BlockchainProvider>, Status> {
+        let chain_type = match proto::Chain::try_from(request.chain) {
+            Ok(proto::Chain::Unspecified) => return Ok(&self.provider),
+            Ok(proto::Chain::Solana) => ChainType::Solana,
+            Ok(proto::Chain::Ethereum) => ChainType::Ethereum,
+            Ok(proto::Chain::Evm) if request.evm_chain_id == 0 => {
+                return Err(Status::invalid_argument("CHAIN_EVM needs evm_chain_id"))
+            }
+            Ok(proto::Chain::Evm) => ChainType::Evm { chain_id: request.evm_chain_id },
+            Err(_) => return Err(Status::invalid_argument(format!("Unknown chain {}", request.chain))),
+        };
+        self.chain_provider(&chain_type)
+            .ok_or_else(|| Status::invalid_argument(format!("Chain {} is not configured on this gateway", chain_type)))
+    }
+
+    /// Background task following fire-and-forget stores to their outcome; spawn it once per gateway
+    pub fn track_confirmations(&self) -> impl std::future::Future<Output = ()> + Send + 'static {
+        Arc::clone(&self.confirmations).run(Arc::clone(&self.provider))
+    }
+
+    /// End every `SubscribeStored` stream, which would otherwise keep the server from shutting down
+    pub fn close_subscriptions(&self) {
+        self.events.close();
+    }
+
+    /// Store requests currently submitting or awaiting confirmation
+    pub fn in_flight_stores(&self) -> usize {
+        self.max_in_flight - self.store_permits.available_permits()
+    }
+
+    /// Wait briefly for an in-flight slot, shedding load with RESOURCE_EXHAUSTED when saturated
+    async fn acquire_store_permit(&self) -> Result<StorePermit<'_>, Status> {
+        match tokio::time::timeout(STORE_PERMIT_TIMEOUT, self.store_permits.acquire()).await {
+            Ok(Ok(permit)) => {
+                metrics::metrics().stores_in_flight.inc();
+                Ok(StorePermit { _permit: permit })
+            }
+            Ok(Err(_)) => Err(Status::unavailable("Gateway is shutting down")),
+            Err(_) => {
+                warn!(timeout_ms = STORE_PERMIT_TIMEOUT.as_millis() as u64, "Store request rejected, too many in flight");
+                Err(Status::resource_exhausted("Too many store requests in flight; retry later"))
+            }
+        }
+    }
+
+    /// Use the explicit account address when given, otherwise derive it from the UID
+    fn resolve_account_address(&self, account_address: &str, uid: &str) -> Result<String, Status> {
+        if !account_address.is_empty() {
+            return Ok(account_address.to_string());
+        }
+        if uid.is_empty() {
+            return Err(Status::invalid_argument("Account address or UID is required"));
+        }
+
+        match self.provider.derive_account_address(uid) {
+            Ok(Some(address)) => Ok(address),
+            Ok(None) => match self.index.as_ref().map(|index| index.get(uid)) {
+                Some(Ok(Some(proof))) => Ok(proof.account_address),
+                Some(Ok(None)) => Err(Status::not_found(format!("No proof indexed for UID {:?}", uid))),
+                Some(Err(e)) => {
+                    error!(error = %e, "Proof index lookup failed");
+                    Err(Status::internal("Failed to look up UID in the proof index"))
+                }
+                None => Err(Status::invalid_argument(
+                    "Provider cannot derive account addresses from UID; pass account_address",
+                )),
+            },
+            Err(e) => Err(status_from_error(&e, "Failed to derive account address")),
+        }
+    }
+
+    /// Proof already stored for the record's URL and content hash, looked up in the index and
+    /// then at the address derived from its UID. Proofs found only on chain have no transaction ID.
+    async fn existing_proof(&self, record: &ContentRecord) -> Result<Option<IndexedProof>, Status> {
+        if let Some(index) = &self.index {
+            match index.find_content(record) {
+                Ok(Some(proof)) => return Ok(Some(proof)),
+                Ok(None) => {}
+                Err(e) => {
+                    error!(error = %e, "Proof index lookup failed");
+                    return Err(Status::internal("Failed to look up the record in the proof index"));
+                }
+            }
+        }
+        if record.uid.is_empty() {
+            return Ok(None);
+        }
+        let account_address = match self.provider.derive_account_address(&record.uid) {
+            Ok(Some(address)) => address,
+            Ok(None) => return Ok(None),
+            Err(e) => return Err(status_from_error(&e, "Failed to derive account address")),
+        };
+        match self.provider.retrieve_record(&account_address).await {
+            Ok(stored)
+                if stored.url == record.url
+                    && stored.content_hash == record.content_hash
+                    && record.hash_algorithm_matches(&stored) =>
+            {
+                Ok(Some(IndexedProof {
+                    account_address,
+                    transaction_id: String::new(),
+                    block_height: None,
+                }))
+            }
+            // A different record under the UID is left for the store itself to reject
+            Ok(_) | Err(BlockchainError::AccountNotFound(_)) => Ok(None),
+            Err(e) => Err(status_from_error(&e, "Failed to look up existing proof")),
+        }
+    }
+
+    /// Pin `content` to IPFS, returning its CID. When the CID is a single raw block its digest
+    /// is the content's SHA-256, which is cross-checked against a SHA-256 `content_hash`.
+    async fn pin_content(&self, ipfs: &IpfsClient, record: &ContentRecord, content: &[u8]) -> Result<String, Status> {
+        let pinned = ipfs.add(content.to_vec()).await.map_err(|e| {
+            error!(error = %e, "Failed to pin content to IPFS");
+            Status::unavailable("Failed to pin content to IPFS")
+        })?;
+        let sha256 = matches!(
+            proto::HashAlgorithm::try_from(record.hash_algorithm),
+            Ok(proto::HashAlgorithm::Unspecified | proto::HashAlgorithm::Sha256)
+        );
+        if let Some(digest) = pinned.sha256_digest.as_ref().filter(|_| sha256) {
+            if *digest != record.content_hash {
+                warn!(cid = %pinned.cid, digest = %digest, "Pinned content does not match content_hash");
+                return Err(Status::invalid_argument(format!(
+                    "content has SHA-256 {} but content_hash is {}",
+                    digest, record.content_hash
+                )));
+            }
+        }
+        info!(cid = %pinned.cid, bytes = content.len(), verified = pinned.sha256_digest.is_some(), "Pinned content to IPFS");
+        Ok(pinned.cid)
+    }
+
+    /// Normalize and validate a record before it is priced or stored. Non-empty `content`
+    /// (`StoreRequest.content`) sets the hash and length from the bytes themselves.
+    fn checked_record(&self, record: Option<&ContentRecord>, content: &[u8]) -> Result<ContentRecord, Status> {
+        let mut record = match record {
+            Some(record) => record.clone(),
+            None => return Err(Status::invalid_argument("Record is missing")),
+        };
+        record
+            .check_size(self.max_record_size)
+            .and_then(|()| if content.is_empty() { Ok(()) } else { record.apply_content(content) })
+            .and_then(|()| record.normalize_url(self.max_url_length))
+            .and_then(|()| record.validate())
+            .map_err(|e| status_from_error(&e, "Invalid record"))?;
+        // Only the gateway stores Merkle roots, so a client record can never pass for one
+        if merkle::is_reserved_url(&record.url) {
+            return Err(Status::invalid_argument("url uses the merkle: scheme, which is reserved for Merkle roots"));
+        }
+        // Set by the gateway at submission; client values are never stored
+        record.timestamp = 0;
+        record.submitter.clear();
+        Ok(record)
+    }
+
+    /// Validate and store one record on behalf of the gateway itself, with no caller to audit
+    pub async fn store_request(&self, request: &StoreRequest) -> Result<StoreResponse, Status> {
+        self.store_request_from(&Caller::default(), "Store", request).await
+    }
+
+    /// Validate and store one record that `caller` sent through `rpc`; shared by the unary and
+    /// streaming RPCs. Dry runs are left out of the store metrics but are audited.
+    pub async fn store_request_from(
+        &self,
+        caller: &Caller,
+        rpc: &'static str,
+        request: &StoreRequest,
+    ) -> Result<StoreResponse, Status> {
+        let record = self.checked_record(request.record.as_ref(), &request.content);
+        let result = match &record {
+            Ok(record) => self.process_store_request(request, record.clone()).await,
+            Err(status) => Err(status.clone()),
+        };
+        if let Some(audit) = &self.audit {
+            // A record that failed validation is audited as it was sent
+            let record = record.as_ref().ok().or(request.record.as_ref()).cloned().unwrap_or_default();
+            audit.record(store_audit_entry(rpc, caller, &record, &result));
+        }
+        if !request.dry_run {
+            let metrics = metrics::metrics();
+            metrics.store_requests.inc();
+            match &result {
+                // Counted once the retry worker actually stores it
+                Ok(response) if response.queued => {}
+                Ok(response) if response.already_exists => metrics.stores_deduplicated.inc(),
+                Ok(_) => metrics.stores_succeeded.inc(),
+                Err(status) => metrics.record_failures(status.code(), 1),
+            }
+        }
+        result
+    }
+
+    #[instrument(
+        name = "store",
+        skip_all,
+        fields(uid, chain, transaction_id, slot, confirmation_ms)
+    )]
+    async fn process_store_request(&self, request: &StoreRequest, record: ContentRecord) -> Result<StoreResponse, Status> {
+        let provider = self.requested_chain(request)?;
+        let chain = provider.chain_type();
+        Span::current()
+            .record("uid", record.uid.as_str())
+            .record("chain", chain.to_string());
+        debug!(url = %record.url, content_hash = %record.content_hash, "Received StoreRequest");
+
+        // The proof index, duplicate skipping, retry queue and confirmation tracking follow the primary chain only
+        let primary = Arc::ptr_eq(provider, &self.provider);
+        if request.fire_and_forget && !primary {
+            return Err(Status::invalid_argument(
+                "fire_and_forget is only supported on the primary chain, which confirmations are tracked on",
+            ));
+        }
+
+        if request.fire_and_forget && request.commitment != proto::Commitment::Unspecified as i32 {
+            return Err(Status::invalid_argument(
+                "Commitment cannot be combined with fire_and_forget; confirmation is tracked at the configured level",
+            ));
+        }
+
+        let options = StoreOptions {
+            commitment: match proto::Commitment::try_from(request.commitment) {
+                Ok(proto::Commitment::Unspecified) => None,
+                Ok(proto::Commitment::Processed) => Some(Commitment::Processed),
+                Ok(proto::Commitment::Confirmed) => Some(Commitment::Confirmed),
+                Ok(proto::Commitment::Finalized) => Some(Commitment::Finalized),
+                Err(_) => return Err(Status::invalid_argument(format!("Unknown commitment {}", request.commitment))),
+            },
+            dry_run: request.dry_run,
+            fire_and_forget: request.fire_and_forget,
+        };
+
+        // Repeats of a key in flight wait here, without holding a store permit
+        let reservation = match Some(request.idempotency_key.as_str()).filter(|key| !key.is_empty()) {
+            Some(key) => match self.completed_stores.claim(&chain, key).await {
+                Claim::Replay(response) => {
+                    info!(transaction_id = %response.transaction_id, "Replaying stored response for repeated idempotency key");
+                    return Ok(response);
+                }
+                Claim::Reserved(reservation) => Some(reservation),
+            },
+            None => None,
+        };
+
+        // Held until the provider returns, i.e. through confirmation unless fire-and-forget
+        let _permit = self.acquire_store_permit().await?;
+
+        if self.skip_duplicates && primary && !request.dry_run {
+            if let Some(proof) = self.existing_proof(&record).await? {
+                info!(account_address = %proof.account_address, transaction_id = %proof.transaction_id, "Record already proven, returning existing proof");
+                return Ok(StoreResponse {
+                    success: true,
+                    transaction_id: proof.transaction_id,
+                    account_address: proof.account_address,
+                    block_height: proof.block_height,
+                    status: proto::TransactionStatus::Confirmed as i32,
+                    already_exists: true,
+                    ..Default::default()
+                });
+            }
+        }
+
+        // Dry runs change nothing, so the content is not pinned either
+        let ipfs_cid = match &self.ipfs {
+            Some(ipfs) if !request.content.is_empty() && !request.dry_run => {
+                self.pin_content(ipfs, &record, &request.content).await?
+            }
+            _ => String::new(),
+        };
+
+        // Call provider to store the record
+        match provider.store_record(&record, &options).await {
+            Ok(result) => {
+                let span = Span::current();
+                span.record("transaction_id", result.transaction_id.as_str());
+                if let Some(slot) = result.block_height {
+                    span.record("slot", slot);
+                }
+                if let Some(confirmation_time) = result.confirmation_time {
+                    span.record("confirmation_ms", confirmation_time.as_millis() as u64);
+                }
+                let status = if result.simulation_logs.is_some() {
+                    proto::TransactionStatus::Unspecified
+                } else if request.fire_and_forget {
+                    let tracked = TrackedRecord {
+                        uid: record.uid.clone(),
+                        account_address: result.account_address.clone().unwrap_or_default(),
+                    };
+                    self.confirmations.track(result.transaction_id.clone(), vec![tracked]);
+                    self.persist_pending(&record, &result);
+                    proto::TransactionStatus::Pending
+                } else {
+                    proto::TransactionStatus::Confirmed
+                };
+                let response = StoreResponse {
+                    success: true,
+                    transaction_id: result.transaction_id,
+                    account_address: result.account_address.unwrap_or_default(),
+                    block_height: result.block_height,
+                    block_time: result.block_time,
+                    confirmation_time_ms: result.confirmation_time.map(|t| t.as_millis() as u64),
+                    finalization_time_ms: result.finalization_time.map(|t| t.as_millis() as u64),
+                    dry_run: result.simulation_logs.is_some(),
+                    simulation_logs: result.simulation_logs.unwrap_or_default(),
+                    status: status as i32,
+                    queued: false,
+                    ipfs_cid,
+                    explorer_url: result.explorer_url.unwrap_or_default(),
+                    already_exists: false,
+                };
+                if primary && !response.dry_run {
+                    self.index_proof(
+                        &record,
+                        IndexedProof {
+                            account_address: response.account_address.clone(),
+                            transaction_id: response.transaction_id.clone(),
+                            block_height: response.block_height,
+                        },
+                    );
+                }
+                if status == proto::TransactionStatus::Confirmed {
+                    self.events.publish(&chain, &record.uid, &response.transaction_id, &response.account_address, response.block_height);
+                }
+                // Dry runs change nothing, so a later real store under the same key must go through
+                if let Some(reservation) = reservation.filter(|_| !response.dry_run) {
+                    reservation.complete(response.clone());
+                }
+                Ok(response)
+            }
+            Err(e) => {
+                let unreachable = matches!(e, BlockchainError::RpcUnavailable(_) | BlockchainError::ConnectionFailed { .. });
+                match self.retry_queue.as_ref().filter(|_| primary && unreachable && !request.dry_run) {
+                    Some(queue) => self.queue_store(queue, &record, &e, reservation, ipfs_cid),
+                    None => {
+                        error!(error = %e, "Blockchain transaction failed");
+                        if primary {
+                            let tracked = TrackedRecord {
+                                uid: record.uid.clone(),
+                                account_address: self.provider.derive_account_address(&record.uid).ok().flatten().unwrap_or_default(),
+                            };
+                            self.track_unconfirmed(&e, vec![tracked]);
+                        }
+                        Err(status_from_error(&e, "Failed to store on blockchain"))
+                    }
+                }
+            }
+        }
+    }
+
+    /// Accept a store the chain could not be reached for by queueing it for `run_retry_queue`
+    fn queue_store(
+        &self,
+        queue: &RetryQueue,
+        record: &ContentRecord,
+        error: &BlockchainError,
+        reservation: Option<Reservation<'_>>,
+        ipfs_cid: String,
+    ) -> Result<StoreResponse, Status> {
+        if let Err(queue_error) = queue.push(record, &error.to_string()) {
+            error!(error = %error, queue_error = %queue_error, "Blockchain unreachable and the record could not be queued");
+            return Err(status_from_error(error, "Failed to store on blockchain"));
+        }
+        warn!(error = %error, "Blockchain unreachable, queued record for retry");
+        let response = StoreResponse {
+            success: true,
+            status: proto::TransactionStatus::Pending as i32,
+            queued: true,
+            ipfs_cid,
+            ..Default::default()
+        };
+        // The record is already queued, so a retry under the same key must not queue it again
+        if let Some(reservation) = reservation {
+            reservation.complete(response.clone());
+        }
+        Ok(response)
+    }
+
+    /// Background task resubmitting queued stores once the chain is reachable; spawn it once
+    /// per gateway when a retry queue is configured
+    pub async fn run_retry_queue(self: Arc<Self>) {
+        let mut interval = tokio::time::interval(RETRY_QUEUE_INTERVAL);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.reconcile_pending_stores().await;
+            self.retry_queued_stores().await;
+        }
+    }
+
+    /// Settle persisted fire-and-forget stores, including those submitted before a restart.
+    /// Confirmed transactions update the index, failed ones are dropped, and ones the chain
+    /// never saw are moved to the retry queue once they can no longer land.
+    async fn reconcile_pending_stores(&self) {
+        let Some(queue) = &self.retry_queue else {
+            return;
+        };
+        let pending = match queue.pending(RECONCILE_BATCH) {
+            Ok(pending) if pending.is_empty() => return,
+            Ok(pending) => pending,
+            Err(e) => {
+                error!(error = %e, "Failed to read pending transactions");
+                return;
+            }
+        };
+        let transaction_ids: Vec<String> = pending.iter().map(|entry| entry.transaction_id.clone()).collect();
+        let statuses = match self.provider.transaction_statuses(&transaction_ids).await {
+            Ok(statuses) => statuses,
+            Err(e) => {
+                debug!(error = %e, pending = pending.len(), "Could not check pending transactions, retrying later");
+                return;
+            }
+        };
+
+        for (entry, status) in pending.into_iter().zip(statuses) {
+            let outcome = match status {
+                TransactionStatus::Pending => Ok(()),
+                TransactionStatus::Confirmed { block_height } => self.settle_confirmed(queue, &entry, block_height),
+                TransactionStatus::Failed(reason) => {
+                    warn!(transaction_id = %entry.transaction_id, reason = %reason, "Pending transaction failed on chain");
+                    queue.settle_pending(entry.id)
+                }
+                TransactionStatus::Unknown if entry.submitted_at.elapsed().unwrap_or_default() < UNSEEN_EXPIRY => Ok(()),
+                // Status lookups only cover recent transactions, so an older one is looked up in full history
+                TransactionStatus::Unknown => match self.provider.get_proof_by_transaction(&entry.transaction_id).await {
+                    Ok(proofs) => self.settle_confirmed(queue, &entry, proofs.block_height),
+                    Err(BlockchainError::TransactionNotFound(_)) => {
+                        warn!(transaction_id = %entry.transaction_id, "Pending transaction was dropped, queueing the record again");
+                        queue.requeue_pending(&entry, "transaction dropped before confirmation")
+                    }
+                    Err(BlockchainError::NotAProof(reason)) => {
+                        warn!(transaction_id = %entry.transaction_id, reason = %reason, "Pending transaction failed on chain");
+                        queue.settle_pending(entry.id)
+                    }
+                    Err(e) => {
+                        debug!(error = %e, transaction_id = %entry.transaction_id, "Could not look up pending transaction");
+                        Ok(())
+                    }
+                },
+            };
+            if let Err(e) = outcome {
+                error!(error = %e, "Failed to update pending transactions");
+            }
+        }
+    }
+
+    fn settle_confirmed(&self, queue: &RetryQueue, entry: &PendingStore, block_height: Option<u64>) -> Result<(), String> {
+        info!(transaction_id = %entry.transaction_id, block_height, "Pending transaction confirmed");
+        // Announced by the tracker instead, unless it was submitted before a restart
+        if self.confirmations.status(&entry.transaction_id).is_none() {
+            self.events.publish(&self.provider.chain_type(), &entry.record.uid, &entry.transaction_id, &entry.account_address, block_height);
+        }
+        self.index_proof(
+            &entry.record,
+            IndexedProof {
+                account_address: entry.account_address.clone(),
+                transaction_id: entry.transaction_id.clone(),
+                block_height,
+            },
+        );
+        queue.settle_pending(entry.id)
+    }
+
+    /// One pass over the due entries of the retry queue
+    async fn retry_queued_stores(&self) {
+        let Some(queue) = &self.retry_queue else {
+            return;
+        };
+        let due = match queue.due(RETRY_QUEUE_BATCH) {
+            Ok(due) if due.is_empty() => return,
+            Ok(due) => due,
+            Err(e) => {
+                error!(error = %e, "Failed to read retry queue");
+                return;
+            }
+        };
+        // Leave the backoff of every entry alone while the chain is still down
+        if let Err(e) = self.provider.check_health().await {
+            debug!(error = %e, queued = due.len(), "Blockchain still unreachable, deferring queued stores");
+            return;
+        }
+        for entry in due {
+            let Ok(_permit) = self.acquire_store_permit().await else {
+                // Saturated by live traffic; pick up where we left off on the next tick
+                return;
+            };
+            self.retry_queued_store(queue, entry).await;
+        }
+    }
+
+    #[instrument(skip_all, fields(uid = %entry.record.uid, queue_id = entry.id, attempts = entry.attempts))]
+    async fn retry_queued_store(&self, queue: &RetryQueue, entry: QueuedStore) {
+        let result = self.provider.store_record(&entry.record, &StoreOptions::default()).await;
+        let outcome = match result {
+            Ok(result) => {
+                info!(transaction_id = %result.transaction_id, "Stored queued record");
+                metrics::metrics().stores_succeeded.inc();
+                self.events.publish(
+                    &self.provider.chain_type(),
+                    &entry.record.uid,
+                    &result.transaction_id,
+                    result.account_address.as_deref().unwrap_or_default(),
+                    result.block_height,
+                );
+                self.audit(&Caller::default(), "RetryQueue", &entry.record, AuditOutcome::Stored, |audit| {
+                    audit.with_transaction_id(result.transaction_id.as_str())
+                });
+                self.index_proof(
+                    &entry.record,
+                    IndexedProof {
+                        account_address: result.account_address.unwrap_or_default(),
+                        transaction_id: result.transaction_id,
+                        block_height: result.block_height,
+                    },
+                );
+                queue.remove(entry.id)
+            }
+            Err(BlockchainError::AlreadyExists(reason)) => {
+                // Most likely an earlier attempt landed without being confirmed
+                info!(reason = %reason, "Queued record is already stored");
+                queue.remove(entry.id)
+            }
+            Err(
+                e @ (BlockchainError::InvalidRecord(_)
+                | BlockchainError::SimulationFailed { .. }
+                | BlockchainError::ProgramError { .. }),
+            ) => {
+                error!(error = %e, "Dropping queued record the chain rejected");
+                let status = status_from_error(&e, "Failed to store on blockchain");
+                metrics::metrics().record_failures(status.code(), 1);
+                self.audit(&Caller::default(), "RetryQueue", &entry.record, AuditOutcome::Failed, |audit| {
+                    audit.with_error(&status)
+                });
+                queue.remove(entry.id)
+            }
+            Err(e) => {
+                warn!(error = %e, "Queued record failed again, retrying later");
+                queue.retry_later(&entry, &e.to_string())
+            }
+        };
+        if let Err(e) = outcome {
+            error!(error = %e, "Failed to update retry queue");
+        }
+    }
+}
+
+#[tonic::async_trait]
+impl ChainGateway for MyChainGateway {
+    type SubscribeStoredStream = Pin<Box<dyn futures::Stream<Item = Result<StoredEvent, Status>> + Send + 'static>>;
+
+    async fn store(&self, request: Request<StoreRequest>) -> Result<Response<StoreResponse>, Status> {
+        let caller = Caller::of(&request);
+        self.store_request_from(&caller, "Store", request.get_ref()).await.map(Response::new)
+    }
+
+    #[instrument(skip_all)]
+    async fn store_stream(
+        &self,
+        request: Request<Streaming<StoreRequest>>,
+    ) -> Result<Response<StoreStreamResponse>, Status> {
+        let caller = &Caller::of(&request);
+        let mut results: Vec<StoreItemResult> = request
+            .into_inner()
+            .enumerate()
+            .map(|(index, item)| async move {
+                let mut result = StoreItemResult {
+                    index: index as u64,
+                    ..Default::default()
+                };
+                let outcome = match item {
+                    Ok(request) => {
+                        result.uid = request.record.as_ref().map(|r| r.uid.clone()).unwrap_or_default();
+                        self.store_request_from(caller, "StoreStream", &request).await
+                    }
+                    Err(status) => Err(status),
+                };
+                match outcome {
+                    Ok(response) => {
+                        result.success = true;
+                        result.transaction_id = response.transaction_id;
+                        result.account_address = response.account_address;
+                    }
+                    Err(status) => result.error = status.message().to_string(),
+                }
+                result
+            })
+            .buffer_unordered(STORE_STREAM_CONCURRENCY)
+            .collect()
+            .await;
+        results.sort_by_key(|result| result.index);
+
+        let stored_count = results.iter().filter(|result| result.success).count() as u64;
+        let failed_count = results.len() as u64 - stored_count;
+        info!(stored_count, failed_count, "StoreStream finished");
+
+        Ok(Response::new(StoreStreamResponse {
+            stored_count,
+            failed_count,
+            results,
+        }))
+    }
+
+    #[instrument(skip_all, fields(records = request.get_ref().records.len()))]
+    async fn store_batch(&self, request: Request<StoreBatchRequest>) -> Result<Response<StoreBatchResponse>, Status> {
+        let caller = Caller::of(&request);
+        let StoreBatchRequest { records, merkle_root } = request.into_inner();
+        if records.is_empty() {
+            return Err(Status::invalid_argument("Batch contains no records"));
+        }
+        let metrics = metrics::metrics();
+        metrics.store_requests.inc_by(records.len() as u64);
+        let checked = records
+            .iter()
+            .enumerate()
+            .map(|(index, record)| {
+                self.checked_record(Some(record), &[])
+                    .map_err(|status| Status::new(status.code(), format!("Record {}: {}", index, status.message())))
+            })
+            .collect::<Result<Vec<_>, _>>();
+        let records = match checked {
+            Ok(checked) => checked,
+            Err(status) => {
+                metrics.record_failures(status.code(), records.len() as u64);
+                self.audit_batch_failure(&caller, &records, &status);
+                return Err(status);
+            }
+        };
+
+        let _permit = match self.acquire_store_permit().await {
+            Ok(permit) => permit,
+            Err(status) => {
+                metrics.record_failures(status.code(), records.len() as u64);
+                self.audit_batch_failure(&caller, &records, &status);
+                return Err(status);
+            }
+        };
+        if merkle_root {
+            return Ok(Response::new(self.store_merkle_root(&caller, &records).await));
+        }
+        let transactions: Vec<BatchTransaction> = self
+            .provider
+            .store_batch(&records)
+            .await
+            .into_iter()
+            .map(|chunk| self.settle_batch_chunk(&caller, &records, chunk))
+            .collect();
+
+        self.publish_batch(&records, &transactions);
+        // Confirmed chunks stand even when others failed; the per-record statuses say which landed
+        let statuses = record_statuses(records.len(), &transactions);
+        let stored = statuses.iter().filter(|status| status.success).count();
+        if stored < records.len() {
+            warn!(
+                stored,
+                failed = records.len() - stored,
+                transactions = transactions.len(),
+                "Batch was only partly stored"
+            );
+        }
+        Ok(Response::new(StoreBatchResponse {
+            transactions,
+            merkle: None,
+            records: statuses,
+        }))
+    }
+
+    #[instrument(skip_all, fields(uid))]
+    async fn estimate_fee(&self, request: Request<EstimateFeeRequest>) -> Result<Response<EstimateFeeResponse>, Status> {
+        let record = self.checked_record(request.get_ref().record.as_ref(), &[])?;
+        Span::current().record("uid", record.uid.as_str());
+
+        match self.provider.estimate_fee(&record).await {
+            Ok(estimate) => Ok(Response::new(EstimateFeeResponse {
+                storage_fee: estimate.storage_fee,
+                transaction_fee: estimate.transaction_fee,
+                total_fee: estimate.total(),
+                unit: estimate.unit.to_string(),
+                gas_limit: estimate.gas_limit,
+                gas_price: estimate.gas_price,
+            })),
+            Err(e) => {
+                error!(error = %e, "Fee estimation failed");
+                Err(status_from_error(&e, "Failed to estimate fee"))
+            }
+        }
+    }
+
+    #[instrument(skip_all, fields(transaction_id = %request.get_ref().transaction_id))]
+    async fn get_transaction_status(
+        &self,
+        request: Request<GetTransactionStatusRequest>,
+    ) -> Result<Response<GetTransactionStatusResponse>, Status> {
+        let transaction_id = &request.get_ref().transaction_id;
+        if transaction_id.is_empty() {
+            return Err(Status::invalid_argument("Transaction ID is required"));
+        }
+
+        let mut response = GetTransactionStatusResponse::default();
+        let status = match self.confirmations.status(transaction_id) {
+            Some(TrackedStatus::Pending) => proto::TransactionStatus::Pending,
+            Some(TrackedStatus::Confirmed {
+                block_height,
+                confirmation_time,
+            }) => {
+                response.block_height = block_height;
+                response.confirmation_time_ms = Some(confirmation_time.as_millis() as u64);
+                proto::TransactionStatus::Confirmed
+            }
+            Some(TrackedStatus::Failed(reason)) => {
+                response.error = reason;
+                proto::TransactionStatus::Failed
+            }
+            Some(TrackedStatus::Expired) => proto::TransactionStatus::Expired,
+            // Not submitted fire-and-forget by this process; ask the chain directly
+            None => match self.provider.transaction_statuses(std::slice::from_ref(transaction_id)).await {
+                Ok(statuses) => match statuses.into_iter().next() {
+                    Some(blockchain::TransactionStatus::Pending) => proto::TransactionStatus::Pending,
+                    Some(blockchain::TransactionStatus::Confirmed { block_height }) => {
+                        response.block_height = block_height;
+                        proto::TransactionStatus::Confirmed
+                    }
+                    Some(blockchain::TransactionStatus::Failed(reason)) => {
+                        response.error = reason;
+                        proto::TransactionStatus::Failed
+                    }
+                    Some(blockchain::TransactionStatus::Unknown) | None => {
+                        return Err(Status::not_found(format!("Transaction {} is not known", transaction_id)));
+                    }
+                },
+                // Malformed IDs are rejected as invalid addresses; say what was actually wrong
+                Err(BlockchainError::InvalidAddress(reason)) => return Err(Status::invalid_argument(reason)),
+                Err(e) => {
+                    error!(error = %e, "Transaction status query failed");
+                    return Err(status_from_error(&e, "Failed to query transaction status"));
+                }
+            },
+        };
+        response.status = status as i32;
+        Ok(Response::new(response))
+    }
+
+    async fn get_proof_by_transaction(
+        &self,
+        request: Request<GetProofByTransactionRequest>,
+    ) -> Result<Response<GetProofByTransactionResponse>, Status> {
+        let transaction_id = &request.get_ref().transaction_id;
+        if transaction_id.is_empty() {
+            return Err(Status::invalid_argument("Transaction ID is required"));
+        }
+
+        match self.provider.get_proof_by_transaction(transaction_id).await {
+            Ok(found) => Ok(Response::new(GetProofByTransactionResponse {
+                proofs: found
+                    .proofs
+                    .into_iter()
+                    .map(|proof| TransactionProof {
+                        record: Some(proof.record),
+                        account_address: proof.account_address.unwrap_or_default(),
+                    })
+                    .collect(),
+                block_height: found.block_height,
+                block_time: found.block_time,
+            })),
+            // Malformed IDs are rejected as invalid addresses; say what was actually wrong
+            Err(BlockchainError::InvalidAddress(reason)) => Err(Status::invalid_argument(reason)),
+            Err(e) => {
+                error!(error = %e, "Proof lookup by transaction failed");
+                Err(status_from_error(&e, "Failed to look up transaction"))
+            }
+        }
+    }
+
+    #[instrument(skip_all)]
+    async fn list_proofs(&self, request: Request<ListProofsRequest>) -> Result<Response<ListProofsResponse>, Status> {
+        let request = request.get_ref();
+        let Some(index) = &self.index else {
+            return Err(Status::failed_precondition(
+                "Listing proofs needs a proof index; configure CHAIN_INDEX_PATH",
+            ));
+        };
+        let limit = match request.limit as usize {
+            0 => DEFAULT_LIST_LIMIT,
+            limit => limit.min(MAX_LIST_LIMIT),
+        };
+        let after = match request.page_token.as_str() {
+            "" => None,
+            token => Some(
+                token
+                    .parse::<i64>()
+                    .map_err(|_| Status::invalid_argument("page_token is not one returned by ListProofs"))?,
+            ),
+        };
+
+        let (proofs, next) = index.list(after, limit).map_err(|e| {
+            error!(error = %e, "Proof index listing failed");
+            Status::internal("Failed to list the proof index")
+        })?;
+        debug!(proofs = proofs.len(), more = next.is_some(), "Listed indexed proofs");
+        Ok(Response::new(ListProofsResponse {
+            proofs: proofs
+                .into_iter()
+                .map(|listed| ListedProof {
+                    record: Some(listed.record.unwrap_or_else(|| ContentRecord {
+                        uid: listed.uid,
+                        ..Default::default()
+                    })),
+                    account_address: listed.proof.account_address,
+                    transaction_id: listed.proof.transaction_id,
+                    block_height: listed.proof.block_height,
+                })
+                .collect(),
+            next_page_token: next.map(|rowid| rowid.to_string()).unwrap_or_default(),
+        }))
+    }
+
+    async fn get_network_info(
+        &self,
+        _request: Request<NetworkInfoRequest>,
+    ) -> Result<Response<NetworkInfoResponse>, Status> {
+        match self.provider.get_network_info().await {
+            Ok(info) => Ok(Response::new(NetworkInfoResponse {
+                chain_type: info.chain_type.to_string(),
+                network_url: info.network_url,
+                genesis_hash: info.genesis_hash,
+                latest_block: info.latest_block,
+                node_version: info.node_version,
+                chain_id: info.chain_id,
+            })),
+            Err(e) => {
+                error!(error = %e, "Network info query failed");
+                Err(status_from_error(&e, "Failed to query network info"))
+            }
+        }
+    }
+
+    async fn get_balance(&self, _request: Request<GetBalanceRequest>) -> Result<Response<GetBalanceResponse>, Status> {
+        match self.provider.get_balance().await {
+            Ok(info) => Ok(Response::new(GetBalanceResponse {
+                payers: info
+                    .accounts
+                    .iter()
+                    .map(|account| proto::PayerBalance {
+                        address: account.address.clone(),
+                        balance: account.balance.to_string(),
+                        amount: info.amount(account.balance),
+                    })
+                    .collect(),
+                total_balance: info.total().to_string(),
+                total_amount: info.amount(info.total()),
+                unit: info.unit.to_string(),
+                decimals: info.decimals,
+            })),
+            Err(e) => {
+                error!(error = %e, "Balance query failed");
+                Err(status_from_error(&e, "Failed to query payer balance"))
+            }
+        }
+    }
+
+    #[instrument(skip_all, fields(uid = %request.get_ref().uid, account_address))]
+    async fn retrieve(&self, request: Request<RetrieveRequest>) -> Result<Response<RetrieveResponse>, Status> {
+        let request = request.get_ref();
+        let account_address = self.resolve_account_address(&request.account_address, &request.uid)?;
+        Span::current().record("account_address", account_address.as_str());
+        debug!("Received RetrieveRequest");
+
+        match self.provider.retrieve_record(&account_address).await {
+            Ok(record) => {
+                let response = RetrieveResponse {
+                    record: Some(record),
+                    account_address,
+                };
+                Ok(Response::new(response))
+            }
+            Err(e) => {
+                error!(error = %e, "Blockchain retrieval failed");
+                Err(status_from_error(&e, "Failed to read record from blockchain"))
+            }
+        }
+    }
+
+    #[instrument(skip_all, fields(uid, account_address))]
+    async fn verify(&self, request: Request<VerifyRequest>) -> Result<Response<VerifyResponse>, Status> {
+        let request = request.get_ref();
+        let mut record = match &request.record {
+            Some(record) => record.clone(),
+            None => return Err(Status::invalid_argument("Record is missing")),
+        };
+        // Stored URLs are normalized, so compare in the same form; unparseable URLs are compared as given
+        if record.normalize_url(usize::MAX).is_err() {
+            debug!(url = %record.url, "Verifying with an unnormalized URL");
+        }
+        let account_address = self.resolve_account_address(&request.account_address, &record.uid)?;
+        Span::current().record("uid", record.uid.as_str());
+        Span::current().record("account_address", account_address.as_str());
+        debug!("Received VerifyRequest");
+
+        match self.provider.verify_record(&record, &account_address).await {
+            Ok(result) => {
+                if !result.matches {
+                    warn!(mismatched_fields = ?result.mismatched_fields, "Proof mismatch");
+                }
+                let response = VerifyResponse {
+                    matches: result.matches,
+                    mismatched_fields: result.mismatched_fields,
+                };
+                Ok(Response::new(response))
+            }
+            Err(e) => {
+                error!(error = %e, "Blockchain verification failed");
+                Err(status_from_error(&e, "Failed to verify record against blockchain"))
+            }
+        }
+    }
+
+    #[instrument(skip_all, fields(uid, root_transaction_id = %request.get_ref().root_transaction_id))]
+    async fn verify_inclusion(
+        &self,
+        request: Request<VerifyInclusionRequest>,
+    ) -> Result<Response<VerifyInclusionResponse>, Status> {
+        let request = request.get_ref();
+        let mut record = match &request.record {
+            Some(record) => record.clone(),
+            None => return Err(Status::invalid_argument("Record is missing")),
+        };
+        if request.root_transaction_id.is_empty() {
+            return Err(Status::invalid_argument("Root transaction ID is required"));
+        }
+        // Leaves were hashed with the normalized URL; unparseable URLs are hashed as given
+        if record.normalize_url(usize::MAX).is_err() {
+            debug!(url = %record.url, "Verifying inclusion with an unnormalized URL");
+        }
+        let path = request
+            .path
+            .iter()
+            .map(ProofStep::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| Status::invalid_argument(format!("Invalid path: {}", e)))?;
+        Span::current().record("uid", record.uid.as_str());
+
+        let stored = match self.provider.get_proof_by_transaction(&request.root_transaction_id).await {
+            Ok(stored) => stored,
+            Err(BlockchainError::InvalidAddress(reason)) => return Err(Status::invalid_argument(reason)),
+            Err(e) => {
+                error!(error = %e, "Merkle root lookup failed");
+                return Err(status_from_error(&e, "Failed to look up the root transaction"));
+            }
+        };
+        let Some(root) = stored
+            .proofs
+            .into_iter()
+            .find(|proof| self.is_own_merkle_root(proof))
+            .map(|proof| proof.record.content_hash)
+        else {
+            return Err(Status::invalid_argument(format!(
+                "Transaction {} stores no Merkle root of this gateway",
+                request.root_transaction_id
+            )));
+        };
+
+        let computed_root = merkle::to_hex(&merkle::root_from_path(merkle::leaf_hash(&record), &path));
+        let included = computed_root.eq_ignore_ascii_case(&root);
+        if !included {
+            warn!(%root, %computed_root, "Record is not under the Merkle root");
+        }
+        Ok(Response::new(VerifyInclusionResponse {
+            included,
+            root,
+            computed_root,
+        }))
+    }
+
+    #[instrument(skip_all, fields(account_address = %request.get_ref().account_address))]
+    async fn close_proof(&self, request: Request<CloseProofRequest>) -> Result<Response<CloseProofResponse>, Status> {
+        if !self.allow_close_proofs {
+            return Err(Status::failed_precondition("Closing proofs is disabled; set CHAIN_ALLOW_CLOSE_PROOFS to enable it"));
+        }
+        let request = request.get_ref();
+        if request.account_address.is_empty() {
+            return Err(Status::invalid_argument("Account address is required"));
+        }
+        debug!("Received CloseProofRequest");
+
+        // Closing is a transaction like any store, so it counts against the in-flight limit
+        let _permit = self.acquire_store_permit().await?;
+        match self.provider.close_proof(&request.account_address).await {
+            Ok(closed) => {
+                info!(
+                    transaction_id = %closed.transaction_id,
+                    reclaimed = closed.reclaimed,
+                    recipient = %closed.recipient,
+                    "Proof account closed"
+                );
+                Ok(Response::new(CloseProofResponse {
+                    transaction_id: closed.transaction_id,
+                    recipient: closed.recipient,
+                    reclaimed: closed.reclaimed,
+                    block_height: closed.block_height,
+                }))
+            }
+            Err(e) => {
+                error!(error = %e, "Closing proof account failed");
+                Err(status_from_error(&e, "Failed to close proof account"))
+            }
+        }
+    }
+
+    async fn subscribe_stored(
+        &self,
+        _request: Request<SubscribeStoredRequest>,
+    ) -> Result<Response<Self::SubscribeStoredStream>, Status> {
+        debug!("New stored event subscriber");
+        Ok(Response::new(Box::pin(self.events.subscribe())))
+    }
+}
+
+/// Outcome of each of `record_count` batch records, from the transactions that carried them
+fn record_statuses(record_count: usize, transactions: &[BatchTransaction]) -> Vec<BatchRecordStatus> {
+    let mut statuses: Vec<BatchRecordStatus> = (0..record_count as u64)
+        .map(|index| BatchRecordStatus {
+            index,
+            error: "Record was not submitted".to_string(),
+            ..Default::default()
+        })
+        .collect();
+    for transaction in transactions {
+        for (position, &index) in transaction.record_indices.iter().enumerate() {
+            let Some(status) = statuses.get_mut(index as usize) else {
+                continue;
+            };
+            status.success = transaction.success;
+            status.transaction_id = transaction.transaction_id.clone();
+            status.account_address = transaction.account_addresses.get(position).cloned().unwrap_or_default();
+            status.error = transaction.error.clone();
+        }
+    }
+    statuses
+}
+
+/// Audit entry for the outcome of a single store
+fn store_audit_entry(
+    rpc: &'static str,
+    caller: &Caller,
+    record: &ContentRecord,
+    result: &Result<StoreResponse, Status>,
+) -> AuditEntry {
+    match result {
+        Ok(response) => {
+            let outcome = if response.dry_run {
+                AuditOutcome::DryRun
+            } else if response.queued {
+                AuditOutcome::Queued
+            } else if response.already_exists {
+                AuditOutcome::Duplicate
+            } else if response.status == proto::TransactionStatus::Pending as i32 {
+                AuditOutcome::Submitted
+            } else {
+                AuditOutcome::Stored
+            };
+            AuditEntry::new(rpc, caller, record, outcome).with_transaction_id(response.transaction_id.as_str())
+        }
+        Err(status) => {
+            // Set when the transaction was sent but not confirmed in time
+            let transaction_id = status
+                .metadata()
+                .get(TRANSACTION_ID_METADATA)
+                .and_then(|value| value.to_str().ok())
+                .unwrap_or_default();
+            AuditEntry::new(rpc, caller, record, AuditOutcome::Failed)
+                .with_transaction_id(transaction_id)
+                .with_error(status)
+        }
+    }
+}
+
+/// Map a provider error to the status returned to clients. Messages that could carry
+/// RPC URLs or raw node errors are replaced; the full error stays in the logs.
+fn status_from_error(e: &BlockchainError, fallback: &str) -> Status {
+    match e {
+        BlockchainError::InsufficientFunds(_) => {
+            Status::failed_precondition("Payer has insufficient funds for this transaction")
+        }
+        BlockchainError::InvalidRecord(reason) => Status::invalid_argument(reason.clone()),
+        BlockchainError::InvalidAddress(reason) => Status::invalid_argument(format!("Invalid account address: {}", reason)),
+        BlockchainError::RpcUnavailable(_) | BlockchainError::ConnectionFailed { .. } => {
+            Status::unavailable("Blockchain RPC endpoint is unavailable")
+        }
+        BlockchainError::ConfirmationTimeout(_) => {
+            Status::deadline_exceeded("Transaction was not confirmed in time")
+        }
+        BlockchainError::RpcTimeout(_) => Status::deadline_exceeded("Blockchain RPC request timed out"),
+        BlockchainError::ConfirmationPending { transaction_id, timeout } => {
+            let mut status = Status::deadline_exceeded(format!(
+                "Transaction {} was not confirmed within {}s and may still land; check GetTransactionStatus before storing again",
+                transaction_id,
+                timeout.as_secs()
+            ));
+            if let Ok(value) = transaction_id.parse() {
+                status.metadata_mut().insert(TRANSACTION_ID_METADATA, value);
+            }
+            status
+        }
+        BlockchainError::AccountNotFound(address) => Status::not_found(format!("No proof account at {}", address)),
+        BlockchainError::TransactionNotFound(id) => Status::not_found(format!("Transaction {} is not known", id)),
+        BlockchainError::NotAProof(reason) => Status::failed_precondition(reason.clone()),
+        BlockchainError::AlreadyExists(reason) => Status::already_exists(reason.clone()),
+        // Program logs are what make these debuggable, and they carry no RPC details
+        BlockchainError::SimulationFailed { error, logs } => Status::failed_precondition(format!(
+            "Transaction simulation failed: {}\nProgram logs:\n{}",
+            error,
+            logs.join("\n")
+        )),
+        BlockchainError::ProgramError { instruction, code, message } => Status::failed_precondition(format!(
+            "Program rejected instruction {}: {}",
+            instruction,
+            blockchain::program_error_detail(code, message)
+        )),
+        BlockchainError::Unsupported(reason) => Status::unimplemented(reason.clone()),
+        BlockchainError::InvalidConfig(_) | BlockchainError::Serialization(_) | BlockchainError::Other(_) => {
+            Status::internal(fallback)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    #[cfg(any(feature = "index", feature = "queue"))]
+    use crate::test_util::TempDb;
+    use providers::mock::MockProvider;
+
+    fn gateway(provider: &MockProvider) -> MyChainGateway {
+        MyChainGateway::with_provider(Arc::new(provider.clone()), 4, Duration::from_secs(60), 512)
+    }
+
+    fn store_request(uid: &str) -> StoreRequest {
+        StoreRequest {
+            record: Some(ContentRecord {
+                uid: uid.to_string(),
+                url: "https://example.com/page".to_string(),
+                content_hash: "ab".repeat(32),
+                content_length: 1024,
+                version: 1,
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn store_returns_provider_result() {
+        let provider = MockProvider::new();
+        let response = gateway(&provider)
+            .store(Request::new(store_request("page-1")))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert!(response.success);
+        assert_eq!(response.transaction_id, "mock-tx-1");
+        assert_eq!(response.account_address, "mock-account-1");
+        assert_eq!(provider.stored_records().len(), 1);
+        assert_eq!(provider.stored_records()[0].uid, "page-1");
+    }
+
+    #[tokio::test]
+    async fn store_rejects_missing_record() {
+        let provider = MockProvider::new();
+        let status = gateway(&provider)
+            .store(Request::new(StoreRequest::default()))
+            .await
+            .unwrap_err();
+
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(provider.stored_records().is_empty());
+    }
+
+    #[tokio::test]
+    async fn store_rejects_malformed_content_hash() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider);
+
+        for content_hash in ["", "abc123", &"AB".repeat(32), &"zz".repeat(32)] {
+            let mut request = store_request("page-1");
+            request.record.as_mut().unwrap().content_hash = content_hash.to_string();
+            let status = gateway.store(Request::new(request)).await.unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument, "accepted {:?}", content_hash);
+        }
+        assert!(provider.stored_records().is_empty());
+    }
+
+    #[tokio::test]
+    async fn store_checks_content_type_and_storage_uri() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider);
+        let with_location = |content_type: &str, storage_uri: &str| {
+            let mut request = store_request("page-1");
+            let record = request.record.as_mut().unwrap();
+            record.content_type = content_type.to_string();
+            record.storage_uri = storage_uri.to_string();
+            request
+        };
+
+        for (content_type, storage_uri) in [("html", ""), ("text/\n", ""), ("", "bucket/key")] {
+            let status = gateway.store(Request::new(with_location(content_type, storage_uri))).await.unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument, "accepted {:?} {:?}", content_type, storage_uri);
+        }
+        gateway
+            .store(Request::new(with_location("text/html; charset=utf-8", "s3://proofs/page-1.html")))
+            .await
+            .unwrap();
+        let stored = provider.stored_records();
+        assert_eq!(stored.len(), 1);
+        assert_eq!(stored[0].content_type, "text/html; charset=utf-8");
+        assert_eq!(stored[0].storage_uri, "s3://proofs/page-1.html");
+    }
+
+    #[tokio::test]
+    async fn store_hashes_content_server_side() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider);
+        let sha256_hello = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+        let mut request = StoreRequest {
+            content: b"hello".to_vec(),
+            ..store_request("page-1")
+        };
+        let record = request.record.as_mut().unwrap();
+        record.content_hash.clear();
+        record.content_length = 0;
+        gateway.store(Request::new(request)).await.unwrap();
+        let stored = &provider.stored_records()[0];
+        assert_eq!(stored.content_hash, sha256_hello);
+        assert_eq!(stored.content_length, 5);
+
+        // A client hash that disagrees with the content is rejected
+        let mut request = StoreRequest {
+            content: b"hello!".to_vec(),
+            ..store_request("page-2")
+        };
+        request.record.as_mut().unwrap().content_length = 6;
+        let status = gateway.store(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        // So is a client length that disagrees with the content
+        let mut request = StoreRequest {
+            content: b"hello".to_vec(),
+            ..store_request("page-3")
+        };
+        let record = request.record.as_mut().unwrap();
+        record.content_hash.clear();
+        record.content_length = 6;
+        let status = gateway.store(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("content_length"), "{}", status.message());
+        assert_eq!(provider.stored_records().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn unknown_commitment_is_rejected_before_any_side_effect() {
+        let provider = MockProvider::new();
+        let gateway = MyChainGateway::with_provider(Arc::new(provider.clone()), 1, Duration::from_secs(60), 512);
+        let unknown_commitment = || {
+            let mut request = StoreRequest {
+                content: b"hello".to_vec(),
+                commitment: 42,
+                idempotency_key: "retry-1".to_string(),
+                ..store_request("page-1")
+            };
+            let record = request.record.as_mut().unwrap();
+            record.content_hash.clear();
+            record.content_length = 0;
+            request
+        };
+
+        // The only permit is taken, so a request that got as far as pinning would wait for it
+        let permit = gateway.acquire_store_permit().await.unwrap();
+        let status = tokio::time::timeout(Duration::from_secs(1), gateway.store(Request::new(unknown_commitment())))
+            .await
+            .expect("rejected only after waiting for a store permit")
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("Unknown commitment"), "{}", status.message());
+        drop(permit);
+        assert!(provider.stored_records().is_empty());
+
+        // Nor was the idempotency key left reserved
+        let request = StoreRequest {
+            commitment: proto::Commitment::Unspecified as i32,
+            ..unknown_commitment()
+        };
+        gateway.store(Request::new(request)).await.unwrap();
+        assert_eq!(provider.stored_records().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn store_hashes_content_with_the_record_algorithm() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider);
+        let keccak_hello = "1c8aff950685c2ed4bc3174f3472287b56d9517b9c948127319a09a7a36deac8";
+
+        let mut request = StoreRequest {
+            content: b"hello".to_vec(),
+            ..store_request("page-1")
+        };
+        let record = request.record.as_mut().unwrap();
+        record.content_hash.clear();
+        record.content_length = 0;
+        record.hash_algorithm = proto::HashAlgorithm::Keccak256 as i32;
+        gateway.store(Request::new(request)).await.unwrap();
+        let stored = provider.stored_records()[0].clone();
+        assert_eq!(stored.content_hash, keccak_hello);
+
+        // The algorithm travels with the hash on chain, and verification holds records to it
+        let on_chain = stored.stored_content_hash();
+        assert_eq!(on_chain, format!("keccak256:{}", keccak_hello));
+        let mut read_back = ContentRecord::default();
+        read_back.set_stored_content_hash(on_chain);
+        assert_eq!(read_back.content_hash, keccak_hello);
+        assert_eq!(read_back.hash_algorithm, proto::HashAlgorithm::Keccak256 as i32);
+        let sha256 = ContentRecord {
+            hash_algorithm: proto::HashAlgorithm::Sha256 as i32,
+            ..stored.clone()
+        };
+        assert!(!sha256.hash_algorithm_matches(&read_back));
+        assert!(stored.hash_algorithm_matches(&read_back));
+
+        // SHA-256 hashes are stored bare, as before algorithms were recorded
+        assert_eq!(sha256.stored_content_hash(), keccak_hello);
+    }
+
+    #[tokio::test]
+    async fn store_normalizes_and_bounds_url() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider);
+
+        let mut request = store_request("page-1");
+        request.record.as_mut().unwrap().url = "  HTTPS://Example.COM/Docs/../Page?q=1 ".to_string();
+        gateway.store(Request::new(request)).await.unwrap();
+        assert_eq!(provider.stored_records()[0].url, "https://example.com/Page?q=1");
+
+        for url in ["", "not a url", "mailto:someone", &format!("https://example.com/{}", "a".repeat(600))] {
+            let mut request = store_request("page-2");
+            request.record.as_mut().unwrap().url = url.to_string();
+            let status = gateway.store(Request::new(request)).await.unwrap_err();
+            assert_eq!(status.code(), tonic::Code::InvalidArgument, "accepted {:?}", url);
+        }
+        assert_eq!(provider.stored_records().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn store_rejects_oversized_record() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider).with_max_record_size(256);
+
+        gateway.store(Request::new(store_request("page-1"))).await.unwrap();
+        let status = gateway.store(Request::new(store_request(&"u".repeat(200)))).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("256 byte limit"), "{}", status.message());
+        assert_eq!(provider.stored_records().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn estimate_fee_totals_breakdown_without_storing() {
+        let provider = MockProvider::new();
+        let response = gateway(&provider)
+            .estimate_fee(Request::new(EstimateFeeRequest {
+                record: store_request("page-1").record,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+
+        assert_eq!(response.total_fee, response.storage_fee + response.transaction_fee);
+        assert_eq!(response.unit, "lamports");
+        assert!(provider.stored_records().is_empty());
+    }
+
+    #[tokio::test]
+    async fn store_maps_provider_errors_to_status() {
+        let cases = [
+            (BlockchainError::InsufficientFunds("short by 5000 lamports".to_string()), tonic::Code::FailedPrecondition),
+            (BlockchainError::RpcUnavailable("http://rpc.internal:8899".to_string()), tonic::Code::Unavailable),
+            (BlockchainError::ConfirmationTimeout("expired".to_string()), tonic::Code::DeadlineExceeded),
+            (BlockchainError::RpcTimeout("getLatestBlockhash".to_string()), tonic::Code::DeadlineExceeded),
+            (BlockchainError::AlreadyExists("proof exists".to_string()), tonic::Code::AlreadyExists),
+            (BlockchainError::Other("node said no".to_string()), tonic::Code::Internal),
+            (
+                BlockchainError::ProgramError { instruction: 1, code: 6000, message: None },
+                tonic::Code::FailedPrecondition,
+            ),
+        ];
+
+        for (error, code) in cases {
+            let provider = MockProvider::failing(error);
+            let status = gateway(&provider)
+                .store(Request::new(store_request("page-1")))
+                .await
+                .unwrap_err();
+
+            assert_eq!(status.code(), code);
+            assert!(!status.message().contains("rpc.internal"), "RPC details leaked: {}", status.message());
+        }
+    }
+
+    #[tokio::test]
+    async fn unconfirmed_store_returns_its_transaction_id() {
+        let provider = MockProvider::failing(BlockchainError::ConfirmationPending {
+            transaction_id: "5sig".to_string(),
+            timeout: Duration::from_secs(120),
+        });
+        let gateway = gateway(&provider);
+        let status = gateway
+            .store(Request::new(store_request("page-1")))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+        assert_eq!(status.metadata().get(TRANSACTION_ID_METADATA).unwrap(), "5sig");
+        assert!(status.message().contains("5sig"), "{}", status.message());
+        assert_eq!(gateway.confirmations.status("5sig"), Some(TrackedStatus::Pending));
+    }
+
+    #[derive(Default)]
+    struct RecordingSink(std::sync::Mutex<Vec<AuditEntry>>);
+
+    impl AuditSink for RecordingSink {
+        fn record(&self, entry: AuditEntry) {
+            self.0.lock().unwrap().push(entry);
+        }
+    }
+
+    #[tokio::test]
+    async fn store_attempts_are_audited_with_their_caller() {
+        let sink = Arc::new(RecordingSink::default());
+        let provider = MockProvider::new();
+        let mut request = Request::new(store_request("page-1"));
+        request.metadata_mut().insert(crate::auth::API_KEY_HEADER, "secret-key".parse().unwrap());
+        let response = gateway(&provider)
+            .with_audit_sink(sink.clone())
+            .store(request)
+            .await
+            .unwrap()
+            .into_inner();
+
+        let failing = MockProvider::failing(BlockchainError::ConfirmationPending {
+            transaction_id: "5sig".to_string(),
+            timeout: Duration::from_secs(120),
+        });
+        self::gateway(&failing)
+            .with_audit_sink(sink.clone())
+            .store(Request::new(store_request("page-2")))
+            .await
+            .unwrap_err();
+
+        let entries = sink.0.lock().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].uid, "page-1");
+        assert_eq!(entries[0].outcome, AuditOutcome::Stored);
+        assert_eq!(entries[0].transaction_id, response.transaction_id);
+        let api_key_id = entries[0].api_key_id.as_deref().unwrap();
+        assert_eq!(api_key_id.len(), 16);
+        assert!(!api_key_id.contains("secret"));
+        assert_eq!(entries[1].outcome, AuditOutcome::Failed);
+        assert_eq!(entries[1].transaction_id, "5sig");
+        assert_eq!(entries[1].error_code.as_deref(), Some("DeadlineExceeded"));
+        assert_eq!(entries[1].api_key_id, None);
+    }
+
+    #[tokio::test]
+    async fn store_replays_repeated_idempotency_key() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider);
+        let request = StoreRequest {
+            idempotency_key: "retry-1".to_string(),
+            ..store_request("page-1")
+        };
+
+        let first = gateway.store(Request::new(request.clone())).await.unwrap().into_inner();
+        let second = gateway.store(Request::new(request)).await.unwrap().into_inner();
+
+        assert_eq!(first, second);
+        assert_eq!(provider.stored_records().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn repeated_key_waits_for_the_store_in_flight() {
+        let provider = MockProvider::with_confirmation_delay(Duration::from_millis(50));
+        let service = gateway(&provider);
+        let request = StoreRequest {
+            idempotency_key: "retry-1".to_string(),
+            ..store_request("page-1")
+        };
+
+        let (first, second) = tokio::join!(service.store_request(&request), service.store_request(&request));
+        assert_eq!(first.unwrap(), second.unwrap());
+        assert_eq!(provider.stored_records().len(), 1);
+
+        // A failed store frees its key for the retry
+        let provider = MockProvider::failing(BlockchainError::Other("reverted".to_string()));
+        let service = gateway(&provider);
+        assert!(service.store_request(&request).await.is_err());
+        provider.recover();
+        service.store_request(&request).await.unwrap();
+        assert_eq!(provider.stored_records().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn idempotency_keys_are_scoped_to_their_chain() {
+        let solana = MockProvider::new();
+        let ethereum = MockProvider::on_chain(ChainType::Ethereum);
+        let gateway = gateway(&solana).with_additional_chain(Arc::new(ethereum.clone())).unwrap();
+        let request = StoreRequest {
+            idempotency_key: "retry-1".to_string(),
+            ..store_request("page-1")
+        };
+
+        gateway.store_request(&request).await.unwrap();
+        let on_ethereum = StoreRequest {
+            chain: proto::Chain::Ethereum as i32,
+            ..request.clone()
+        };
+        gateway.store_request(&on_ethereum).await.unwrap();
+        gateway.store_request(&on_ethereum).await.unwrap();
+        assert_eq!(solana.stored_records().len(), 1);
+        assert_eq!(ethereum.stored_records().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn dry_run_reports_simulation_without_storing() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider);
+        let request = StoreRequest {
+            dry_run: true,
+            idempotency_key: "retry-1".to_string(),
+            ..store_request("page-1")
+        };
+
+        let response = gateway.store(Request::new(request)).await.unwrap().into_inner();
+        assert!(response.dry_run);
+        assert!(response.transaction_id.is_empty());
+        assert!(!response.simulation_logs.is_empty());
+        assert!(provider.stored_records().is_empty());
+
+        // The dry run is not cached, so a real store under the same key still goes through
+        let request = StoreRequest {
+            idempotency_key: "retry-1".to_string(),
+            ..store_request("page-1")
+        };
+        let response = gateway.store(Request::new(request)).await.unwrap().into_inner();
+        assert!(!response.dry_run);
+        assert_eq!(provider.stored_records().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn subscribers_see_each_confirmed_store() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider);
+        let mut events = gateway
+            .subscribe_stored(Request::new(SubscribeStoredRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+
+        gateway.store(Request::new(store_request("page-1"))).await.unwrap();
+        let event = events.next().await.unwrap().unwrap();
+        assert_eq!(event.uid, "page-1");
+        assert_eq!(event.transaction_id, "mock-tx-1");
+        assert_eq!(event.account_address, "mock-account-1");
+        assert_eq!(event.block_height, Some(1));
+        assert!(event.timestamp > 0);
+        assert_eq!(event.chain, proto::Chain::Solana as i32);
+
+        // Fire-and-forget stores are announced once the tracker sees them confirm, not on submission
+        let request = StoreRequest {
+            fire_and_forget: true,
+            ..store_request("page-2")
+        };
+        gateway.store(Request::new(request)).await.unwrap();
+        gateway.confirmations.poll(&provider).await;
+        let event = events.next().await.unwrap().unwrap();
+        assert_eq!(event.uid, "page-2");
+        assert_eq!(event.transaction_id, "mock-tx-2");
+
+        gateway.close_subscriptions();
+        assert!(events.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn fire_and_forget_store_is_tracked_to_confirmation() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider);
+        let request = StoreRequest {
+            fire_and_forget: true,
+            ..store_request("page-1")
+        };
+
+        let stored = gateway.store(Request::new(request)).await.unwrap().into_inner();
+        assert_eq!(stored.status, proto::TransactionStatus::Pending as i32);
+        let status_request = || GetTransactionStatusRequest {
+            transaction_id: stored.transaction_id.clone(),
+        };
+        let pending = gateway.get_transaction_status(Request::new(status_request())).await.unwrap().into_inner();
+        assert_eq!(pending.status, proto::TransactionStatus::Pending as i32);
+
+        gateway.confirmations.poll(&provider).await;
+        let confirmed = gateway.get_transaction_status(Request::new(status_request())).await.unwrap().into_inner();
+        assert_eq!(confirmed.status, proto::TransactionStatus::Confirmed as i32);
+        assert_eq!(confirmed.block_height, Some(1));
+
+        let status = gateway
+            .get_transaction_status(Request::new(GetTransactionStatusRequest {
+                transaction_id: "unknown".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn get_proof_by_transaction_decodes_stored_record() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider);
+        let stored = gateway.store(Request::new(store_request("page-1"))).await.unwrap().into_inner();
+
+        let response = gateway
+            .get_proof_by_transaction(Request::new(GetProofByTransactionRequest {
+                transaction_id: stored.transaction_id,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.proofs.len(), 1);
+        assert_eq!(response.proofs[0].record.as_ref().unwrap().uid, "page-1");
+        assert_eq!(response.proofs[0].account_address, stored.account_address);
+        assert_eq!(response.block_height, Some(1));
+
+        let status = gateway
+            .get_proof_by_transaction(Request::new(GetProofByTransactionRequest {
+                transaction_id: "mock-tx-9".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn batch_reports_which_records_landed() {
+        let provider = MockProvider::new();
+        provider.fail_uid("page-2", BlockchainError::InsufficientFunds("payer is empty".to_string()));
+        let records: Vec<ContentRecord> = (1..=3)
+            .map(|page| store_request(&format!("page-{}", page)).record.unwrap())
+            .collect();
+
+        let response = gateway(&provider)
+            .store_batch(Request::new(StoreBatchRequest {
+                records,
+                merkle_root: false,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.transactions.len(), 3);
+
+        // The failed record leaves the records stored before and after it in place
+        let statuses = response.records;
+        assert_eq!(statuses.iter().map(|status| status.index).collect::<Vec<_>>(), vec![0, 1, 2]);
+        assert_eq!(statuses.iter().map(|status| status.success).collect::<Vec<_>>(), vec![true, false, true]);
+        assert_eq!(statuses[0].transaction_id, "mock-tx-1");
+        assert_eq!(statuses[0].account_address, "mock-account-1");
+        assert!(statuses[0].error.is_empty());
+        assert!(!statuses[1].error.is_empty());
+        assert!(statuses[1].account_address.is_empty());
+        assert_eq!(statuses[2].transaction_id, "mock-tx-3");
+    }
+
+    #[tokio::test]
+    async fn batch_checks_each_record_as_store_does() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider);
+        let mut records: Vec<ContentRecord> = (1..=2)
+            .map(|page| store_request(&format!("page-{}", page)).record.unwrap())
+            .collect();
+        records[1].url = "not a url".to_string();
+        let status = gateway
+            .store_batch(Request::new(StoreBatchRequest {
+                records: records.clone(),
+                merkle_root: false,
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().starts_with("Record 1: "), "{}", status.message());
+        assert!(provider.stored_records().is_empty());
+
+        // Client timestamps and submitters are dropped, as for a single store
+        records[1].url = "https://example.com/page-2".to_string();
+        records[1].timestamp = 1_700_000_000;
+        records[1].submitter = "someone-else".to_string();
+        gateway
+            .store_batch(Request::new(StoreBatchRequest {
+                records,
+                merkle_root: false,
+            }))
+            .await
+            .unwrap();
+        let stored = provider.stored_records();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[1].timestamp, 0);
+        assert!(stored[1].submitter.is_empty());
+    }
+
+    #[tokio::test]
+    async fn merkle_batch_stores_one_root_and_verifies_each_record() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider);
+        let records: Vec<ContentRecord> = (1..=5)
+            .map(|page| store_request(&format!("page-{}", page)).record.unwrap())
+            .collect();
+
+        let response = gateway
+            .store_batch(Request::new(StoreBatchRequest {
+                records: records.clone(),
+                merkle_root: true,
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        let stored = provider.stored_records();
+        assert_eq!(stored.len(), 1);
+        let merkle = response.merkle.unwrap();
+        assert_eq!(stored[0].content_hash, merkle.root);
+        assert_eq!(stored[0].content_length, 5);
+        assert_eq!(response.transactions.len(), 1);
+        let transaction = &response.transactions[0];
+        assert!(transaction.success);
+        assert_eq!(transaction.record_indices, vec![0, 1, 2, 3, 4]);
+
+        let verify = |record: &ContentRecord, inclusion: &MerkleInclusion| VerifyInclusionRequest {
+            record: Some(record.clone()),
+            path: inclusion.path.clone(),
+            root_transaction_id: transaction.transaction_id.clone(),
+        };
+        for (record, inclusion) in records.iter().zip(&merkle.inclusions) {
+            let result = gateway.verify_inclusion(Request::new(verify(record, inclusion))).await.unwrap().into_inner();
+            assert!(result.included, "{}", record.uid);
+            assert_eq!(result.root, merkle.root);
+        }
+
+        // A changed record, or a record with another's path, falls outside the root
+        let mut tampered = records[4].clone();
+        tampered.content_length += 1;
+        let result = gateway
+            .verify_inclusion(Request::new(verify(&tampered, &merkle.inclusions[4])))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!result.included);
+        let result = gateway
+            .verify_inclusion(Request::new(verify(&records[0], &merkle.inclusions[1])))
+            .await
+            .unwrap()
+            .into_inner();
+        assert!(!result.included);
+    }
+
+    #[tokio::test]
+    async fn merkle_roots_cannot_be_forged() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider);
+        let record = store_request("page-1").record.unwrap();
+        let forged = merkle::root_record(&merkle::leaf_hash(&record), 1);
+
+        // Clients may not store under the scheme roots are stored under, alone or in a batch
+        let status = gateway
+            .store(Request::new(StoreRequest {
+                record: Some(forged.clone()),
+                ..Default::default()
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("merkle:"), "{}", status.message());
+        let status = gateway
+            .store_batch(Request::new(StoreBatchRequest {
+                records: vec![record.clone(), forged.clone()],
+                merkle_root: false,
+            }))
+            .await
+            .unwrap_err();
+        assert!(status.message().starts_with("Record 1: "), "{}", status.message());
+        assert!(provider.stored_records().is_empty());
+
+        // A single leaf is its own root, so an empty path would prove the record if the root counted
+        let verify = |root_transaction_id: String| VerifyInclusionRequest {
+            record: Some(record.clone()),
+            path: Vec::new(),
+            root_transaction_id,
+        };
+        let foreign = provider.store_foreign(&forged).unwrap();
+        let status = gateway.verify_inclusion(Request::new(verify(foreign))).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        // Nor does a root the gateway stored under another UID, as it could before the scheme was reserved
+        let misnamed = ContentRecord {
+            uid: "page-0".to_string(),
+            ..forged
+        };
+        let stored = provider.store_record(&misnamed, &StoreOptions::default()).await.unwrap();
+        let status = gateway.verify_inclusion(Request::new(verify(stored.transaction_id))).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn self_test_stores_and_reads_back_only_on_development_networks() {
+        let provider = MockProvider::new();
+        gateway(&provider).self_test().await.unwrap();
+        let stored = provider.stored_records();
+        assert_eq!(stored.len(), 1);
+        assert!(stored[0].uid.starts_with("self-test-"));
+
+        // Solana mainnet-beta
+        let mainnet = MockProvider::on_network("5eykt4UsFv8P8NJdTREpY1vzqKqZKvdpKuc147dw2N9d");
+        let error = gateway(&mainnet).self_test().await.unwrap_err();
+        assert!(matches!(error, BlockchainError::InvalidConfig(_)), "unexpected error {:?}", error);
+        assert!(mainnet.stored_records().is_empty());
+    }
+
+    #[tokio::test]
+    async fn get_balance_reports_base_units_and_readable_amounts() {
+        let provider = MockProvider::with_payer_balance(1_500_000_000);
+        let response = gateway(&provider)
+            .get_balance(Request::new(GetBalanceRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.payers[0].balance, "1500000000");
+        assert_eq!(response.payers[0].amount, "1.5");
+        assert_eq!(response.total_amount, "1.5");
+        assert_eq!((response.unit.as_str(), response.decimals), ("lamports", 9));
+
+        let provider = MockProvider::with_payer_balance(42);
+        let response = gateway(&provider).get_balance(Request::new(GetBalanceRequest {})).await.unwrap().into_inner();
+        assert_eq!(response.total_amount, "0.000000042");
+    }
+
+    #[cfg(feature = "index")]
+    #[tokio::test]
+    async fn retrieve_finds_indexed_proof_by_uid() {
+        let db = TempDb::new("gateway-index");
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider).with_index(ProofIndex::open(db.path()).unwrap());
+        let stored = gateway.store(Request::new(store_request("page-1"))).await.unwrap().into_inner();
+
+        let response = gateway
+            .retrieve(Request::new(RetrieveRequest {
+                account_address: String::new(),
+                uid: "page-1".to_string(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.account_address, stored.account_address);
+
+        let status = gateway
+            .retrieve(Request::new(RetrieveRequest {
+                account_address: String::new(),
+                uid: "never-stored".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[cfg(feature = "queue")]
+    #[tokio::test]
+    async fn unreachable_store_is_queued_and_retried() {
+        let db = TempDb::new("gateway-queue");
+        let provider = MockProvider::failing(BlockchainError::RpcUnavailable("connection refused".to_string()));
+        let gateway = gateway(&provider).with_retry_queue(RetryQueue::open(db.path()).unwrap());
+
+        let response = gateway.store(Request::new(store_request("page-1"))).await.unwrap().into_inner();
+        assert!(response.queued);
+        assert!(response.transaction_id.is_empty());
+        assert_eq!(response.status, proto::TransactionStatus::Pending as i32);
+
+        // The retry fails too, so the entry stays queued but is no longer due
+        gateway.retry_queued_stores().await;
+        let queue = gateway.retry_queue.as_ref().unwrap();
+        assert!(queue.due(16).unwrap().is_empty());
+
+        provider.recover();
+        let conn = rusqlite::Connection::open(db.path()).unwrap();
+        conn.execute("UPDATE queued_stores SET next_attempt_at = 0", []).unwrap();
+        let due = queue.due(16).unwrap();
+        assert_eq!(due.iter().map(|entry| entry.attempts).collect::<Vec<_>>(), [1]);
+
+        gateway.retry_queued_stores().await;
+        assert!(queue.due(16).unwrap().is_empty());
+        assert_eq!(provider.stored_records().last().unwrap().uid, "page-1");
+        assert_eq!(metrics::metrics().retry_queue_depth.get(), 0);
+    }
+
+    #[cfg(feature = "index")]
+    #[tokio::test]
+    async fn duplicate_store_returns_existing_proof() {
+        let db = TempDb::new("gateway-dedupe");
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider)
+            .with_index(ProofIndex::open(db.path()).unwrap())
+            .with_skip_duplicates(true);
+        let stored = gateway.store(Request::new(store_request("page-1"))).await.unwrap().into_inner();
+        assert!(!stored.already_exists);
+
+        // Same URL and content under another UID is the same proof
+        let duplicate = gateway.store(Request::new(store_request("page-2"))).await.unwrap().into_inner();
+        assert!(duplicate.already_exists);
+        assert_eq!(duplicate.transaction_id, stored.transaction_id);
+        assert_eq!(duplicate.account_address, stored.account_address);
+        assert_eq!(provider.stored_records().len(), 1);
+
+        // The same hex digest under another algorithm is a different proof
+        let mut keccak = store_request("page-3");
+        keccak.record.as_mut().unwrap().hash_algorithm = proto::HashAlgorithm::Keccak256 as i32;
+        let keccak = gateway.store(Request::new(keccak)).await.unwrap().into_inner();
+        assert!(!keccak.already_exists);
+        assert_eq!(provider.stored_records().len(), 2);
+
+        let mut changed = store_request("page-1");
+        changed.record.as_mut().unwrap().content_hash = "cd".repeat(32);
+        let changed = gateway.store(Request::new(changed)).await.unwrap().into_inner();
+        assert!(!changed.already_exists);
+        assert_eq!(provider.stored_records().len(), 3);
+    }
+
+    #[cfg(feature = "queue")]
+    #[tokio::test]
+    async fn pending_stores_are_reconciled_after_restart() {
+        let db = TempDb::new("gateway-pending");
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider).with_retry_queue(RetryQueue::open(db.path()).unwrap());
+        let request = StoreRequest {
+            fire_and_forget: true,
+            ..store_request("page-1")
+        };
+        let stored = gateway.store(Request::new(request)).await.unwrap().into_inner();
+        let queue = gateway.retry_queue.as_ref().unwrap();
+        // Submitted long ago and never seen by the chain, as after a crash mid-submission
+        let dropped = store_request("page-2").record.unwrap();
+        let submitted_at = SystemTime::now() - UNSEEN_EXPIRY * 2;
+        queue.push_pending("mock-tx-99", "", &dropped, submitted_at).unwrap();
+        // Submitted as long ago, but landed and failed, so only a full-history lookup finds it
+        provider.fail_on_chain("mock-tx-98");
+        queue.push_pending("mock-tx-98", "", &store_request("page-3").record.unwrap(), submitted_at).unwrap();
+        drop(gateway);
+
+        // A restarted gateway settles all three from the file alone
+        let gateway = self::gateway(&provider).with_retry_queue(RetryQueue::open(db.path()).unwrap());
+        gateway.reconcile_pending_stores().await;
+        let queue = gateway.retry_queue.as_ref().unwrap();
+        assert!(queue.pending(16).unwrap().is_empty());
+        let due = queue.due(16).unwrap();
+        assert_eq!(due.iter().map(|entry| entry.record.uid.as_str()).collect::<Vec<_>>(), ["page-2"]);
+        assert_eq!(provider.stored_records().len(), 1, "{} was confirmed, not resubmitted", stored.transaction_id);
+
+        gateway.retry_queued_stores().await;
+        assert_eq!(provider.stored_records().last().unwrap().uid, "page-2");
+    }
+
+    #[cfg(feature = "index")]
+    #[tokio::test]
+    async fn list_proofs_pages_through_index_in_insertion_order() {
+        let db = TempDb::new("gateway-list");
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider).with_index(ProofIndex::open(db.path()).unwrap());
+        for uid in ["page-1", "page-2", "page-3"] {
+            gateway.store(Request::new(store_request(uid))).await.unwrap();
+        }
+        // Storing a UID again moves it to the end
+        gateway.store(Request::new(store_request("page-1"))).await.unwrap();
+
+        let list = |page_token: String| ListProofsRequest { limit: 2, page_token };
+        let first = gateway.list_proofs(Request::new(list(String::new()))).await.unwrap().into_inner();
+        let second = gateway
+            .list_proofs(Request::new(list(first.next_page_token.clone())))
+            .await
+            .unwrap()
+            .into_inner();
+        let uids: Vec<_> = first
+            .proofs
+            .iter()
+            .chain(&second.proofs)
+            .map(|proof| proof.record.as_ref().unwrap().uid.as_str())
+            .collect();
+        assert_eq!(uids, ["page-2", "page-3", "page-1"]);
+        assert_eq!(second.proofs[0].transaction_id, "mock-tx-4");
+        assert_eq!(second.proofs[0].record.as_ref().unwrap().url, "https://example.com/page");
+        assert!(second.next_page_token.is_empty());
+
+        let status = gateway.list_proofs(Request::new(list("bogus".to_string()))).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    }
+
+    #[tokio::test]
+    async fn retrieve_reads_back_stored_record() {
+        let provider = MockProvider::new();
+        let gateway = gateway(&provider);
+        let stored = gateway.store(Request::new(store_request("page-1"))).await.unwrap().into_inner();
+
+        let response = gateway
+            .retrieve(Request::new(RetrieveRequest {
+                account_address: stored.account_address,
+                uid: String::new(),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(response.record.unwrap().uid, "page-1");
+
+        let status = gateway
+            .retrieve(Request::new(RetrieveRequest {
+                account_address: "missing".to_string(),
+                uid: String::new(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn close_proof_is_disabled_unless_allowed() {
+        let provider = MockProvider::new();
+        let stored = gateway(&provider).store(Request::new(store_request("page-1"))).await.unwrap().into_inner();
+        let close = |account_address: &str| {
+            Request::new(CloseProofRequest {
+                account_address: account_address.to_string(),
+            })
+        };
+
+        let status = gateway(&provider).close_proof(close(&stored.account_address)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+
+        let gateway = gateway(&provider).with_allow_close_proofs(true);
+        let status = gateway.close_proof(close("")).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let closed = gateway.close_proof(close(&stored.account_address)).await.unwrap().into_inner();
+        assert!(closed.reclaimed > 0);
+        assert_eq!(closed.recipient, "mock-payer");
+        let status = gateway.close_proof(close(&stored.account_address)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::NotFound);
+    }
+
+    #[tokio::test]
+    async fn stores_go_to_the_chain_they_select() {
+        let solana = MockProvider::new();
+        let ethereum = MockProvider::on_chain(ChainType::Ethereum);
+        let gateway = gateway(&solana).with_additional_chain(Arc::new(ethereum.clone())).unwrap();
+        let mut events = gateway
+            .subscribe_stored(Request::new(SubscribeStoredRequest {}))
+            .await
+            .unwrap()
+            .into_inner();
+
+        let mut request = store_request("page-1");
+        request.chain = proto::Chain::Ethereum as i32;
+        gateway.store_request(&request).await.unwrap();
+        let event = events.next().await.unwrap().unwrap();
+        assert_eq!((event.uid.as_str(), event.chain), ("page-1", proto::Chain::Ethereum as i32));
+        gateway.store_request(&store_request("page-2")).await.unwrap();
+        request.chain = proto::Chain::Solana as i32;
+        request.record.as_mut().unwrap().uid = "page-3".to_string();
+        gateway.store_request(&request).await.unwrap();
+        assert_eq!(ethereum.stored_records().iter().map(|r| r.uid.as_str()).collect::<Vec<_>>(), ["page-1"]);
+        assert_eq!(solana.stored_records().iter().map(|r| r.uid.as_str()).collect::<Vec<_>>(), ["page-2", "page-3"]);
+
+        request.chain = proto::Chain::Evm as i32;
+        request.evm_chain_id = 137;
+        let status = gateway.store_request(&request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert!(status.message().contains("not configured"), "{}", status.message());
+
+        // Confirmations are only tracked on the primary chain
+        let mut request = store_request("page-4");
+        request.chain = proto::Chain::Ethereum as i32;
+        request.fire_and_forget = true;
+        let status = gateway.store_request(&request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(ethereum.stored_records().len(), 1);
+
+        assert!(gateway.with_additional_chain(Arc::new(MockProvider::new())).is_err());
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use tokio::sync::watch;
+
+use crate::blockchain::ChainType;
+use crate::proto::StoreResponse;
+
+/// Upper bound on remembered keys; the oldest entry is evicted beyond this
+const MAX_ENTRIES: usize = 10_000;
+
+/// Recently completed stores keyed by their chain and client-supplied idempotency key, so
+/// the same key sent to another chain is stored there rather than replayed.
+///
+/// A key is reserved while its first store is in flight, and repeats wait for that store to
+/// finish, so a retry sent while the original awaits confirmation is not stored twice.
+///
+/// This is best-effort: entries live only in this process, so a retry routed to
+/// another instance, or arriving after a restart, is submitted again.
+pub struct IdempotencyCache {
+    window: Duration,
+    entries: Mutex<HashMap<(ChainType, String), (Instant, StoreResponse)>>,
+    /// Keys whose store is in flight; the sender is dropped when that store finishes
+    pending: Mutex<HashMap<(ChainType, String), watch::Sender<()>>>,
+}
+
+/// Outcome of claiming an idempotency key
+pub enum Claim<'a> {
+    /// The key already completed; answer with its response
+    Replay(StoreResponse),
+    /// The key is this request's to store
+    Reserved(Reservation<'a>),
+}
+
+/// Hold on a key while its store is in flight. Dropped without `complete`, as when the
+/// store fails, it frees the key for the next request to try again.
+pub struct Reservation<'a> {
+    cache: &'a IdempotencyCache,
+    key: (ChainType, String),
+}
+
+impl Reservation<'_> {
+    /// Remember `response` for repeats of the key and wake those waiting on it
+    pub fn complete(self, response: StoreResponse) {
+        let (chain, key) = self.key.clone();
+        self.cache.insert(chain, key, response);
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        // Dropping the sender wakes every request waiting on the key
+        self.cache.pending.lock().unwrap_or_else(|e| e.into_inner()).remove(&self.key);
+    }
+}
+
+impl IdempotencyCache {
+    pub fn new(window: Duration) -> Self {
+        Self {
+            window,
+            entries: Mutex::new(HashMap::new()),
+            pending: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replay the response of `key` on `chain`, or reserve the key for this request to store.
+    /// While another request holds the key, waits for its store to finish first.
+    pub async fn claim(&self, chain: &ChainType, key: &str) -> Claim<'_> {
+        let key = (chain.clone(), key.to_string());
+        loop {
+            let mut done = {
+                let mut pending = self.pending.lock().unwrap_or_else(|e| e.into_inner());
+                // A completed store's response is inserted before its key leaves `pending`
+                if let Some(response) = self.get(&key.0, &key.1) {
+                    return Claim::Replay(response);
+                }
+                match pending.get(&key) {
+                    Some(in_flight) => in_flight.subscribe(),
+                    None => {
+                        pending.insert(key.clone(), watch::channel(()).0);
+                        return Claim::Reserved(Reservation { cache: self, key });
+                    }
+                }
+            };
+            // Nothing is ever sent, so this returns once the reservation is dropped
+            let _ = done.changed().await;
+        }
+    }
+
+    /// The response stored under `key` on `chain`, if it completed within the window
+    fn get(&self, chain: &ChainType, key: &str) -> Option<StoreResponse> {
+        self.get_at(chain, key, Instant::now())
+    }
+
+    fn get_at(&self, chain: &ChainType, key: &str, now: Instant) -> Option<StoreResponse> {
+        let entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        entries
+            .get(&(chain.clone(), key.to_string()))
+            .filter(|(completed_at, _)| now.saturating_duration_since(*completed_at) < self.window)
+            .map(|(_, response)| response.clone())
+    }
+
+    /// Remember a completed response, evicting expired entries and, when still
+    /// full, the oldest one
+    fn insert(&self, chain: ChainType, key: String, response: StoreResponse) {
+        self.insert_at(chain, key, response, Instant::now());
+    }
+
+    fn insert_at(&self, chain: ChainType, key: String, response: StoreResponse, now: Instant) {
+        let mut entries = self.entries.lock().unwrap_or_else(|e| e.into_inner());
+        if entries.len() >= MAX_ENTRIES {
+            entries.retain(|_, (completed_at, _)| now.saturating_duration_since(*completed_at) < self.window);
+        }
+        if entries.len() >= MAX_ENTRIES {
+            let oldest = entries
+                .iter()
+                .min_by_key(|(_, (completed_at, _))| *completed_at)
+                .map(|(key, _)| key.clone());
+            if let Some(oldest) = oldest {
+                entries.remove(&oldest);
+            }
+        }
+        entries.insert((chain, key), (now, response));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(transaction_id: &str) -> StoreResponse {
+        StoreResponse {
+            success: true,
+            transaction_id: transaction_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn responses_are_replayed_until_the_window_passes() {
+        let cache = IdempotencyCache::new(Duration::from_secs(60));
+        let start = Instant::now();
+        cache.insert_at(ChainType::Solana, "retry-1".to_string(), response("tx-1"), start);
+
+        let hit = cache.get_at(&ChainType::Solana, "retry-1", start + Duration::from_secs(59));
+        assert_eq!(hit.unwrap().transaction_id, "tx-1");
+        assert!(cache.get_at(&ChainType::Solana, "retry-2", start).is_none());
+        assert!(cache.get_at(&ChainType::Ethereum, "retry-1", start).is_none());
+        assert!(cache.get_at(&ChainType::Solana, "retry-1", start + Duration::from_secs(60)).is_none());
+    }
+
+    #[test]
+    fn oldest_entry_is_evicted_at_capacity() {
+        let cache = IdempotencyCache::new(Duration::from_secs(3600));
+        let start = Instant::now();
+        for index in 0..MAX_ENTRIES {
+            let at = start + Duration::from_millis(index as u64);
+            cache.insert_at(ChainType::Solana, format!("key-{}", index), response(&format!("tx-{}", index)), at);
+        }
+        let now = start + Duration::from_secs(60);
+        cache.insert_at(ChainType::Solana, "key-new".to_string(), response("tx-new"), now);
+
+        assert_eq!(cache.entries.lock().unwrap().len(), MAX_ENTRIES);
+        assert!(cache.get_at(&ChainType::Solana, "key-0", now).is_none());
+        assert!(cache.get_at(&ChainType::Solana, "key-1", now).is_some());
+        assert!(cache.get_at(&ChainType::Solana, "key-new", now).is_some());
+    }
+}
@@ -0,0 +1,291 @@
+use std::path::Path;
+
+use crate::proto::ContentRecord;
+
+/// Where a stored record ended up, as remembered by the index
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexedProof {
+    pub account_address: String,
+    pub transaction_id: String,
+    /// Slot or block the transaction landed in; unknown for fire-and-forget stores
+    pub block_height: Option<u64>,
+}
+
+/// An index entry as returned by `ProofIndex::list`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ListedProof {
+    pub uid: String,
+    pub proof: IndexedProof,
+    /// The record as stored; `None` for entries indexed before records were kept
+    pub record: Option<ContentRecord>,
+}
+
+/// Durable map from record UID to the proof account it was stored in, so proofs stored at
+/// random (non-PDA) addresses can still be found by UID. A later store of the same UID
+/// replaces the earlier entry.
+///
+/// Queries are short local SQLite calls and run inline on the async worker.
+#[cfg(feature = "index")]
+pub struct ProofIndex {
+    conn: std::sync::Mutex<rusqlite::Connection>,
+}
+
+/// Stand-in when the crate is built without the `index` feature; it can never be opened
+#[cfg(not(feature = "index"))]
+pub struct ProofIndex {
+    never: std::convert::Infallible,
+}
+
+#[cfg(feature = "index")]
+impl ProofIndex {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let conn = rusqlite::Connection::open(path)
+            .map_err(|e| format!("Failed to open proof index {}: {}", path.display(), e))?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE IF NOT EXISTS proofs (
+                 uid TEXT PRIMARY KEY,
+                 account_address TEXT NOT NULL,
+                 transaction_id TEXT NOT NULL,
+                 block_height INTEGER,
+                 record BLOB,
+                 content_hash TEXT
+             );",
+        )
+        .map_err(|e| format!("Failed to initialize proof index {}: {}", path.display(), e))?;
+        // Indexes created before records, and later content hashes, were kept lack the columns
+        for (column, definition) in [("record", "record BLOB"), ("content_hash", "content_hash TEXT")] {
+            let has_column = conn
+                .prepare("SELECT 1 FROM pragma_table_info('proofs') WHERE name = ?1")
+                .and_then(|mut statement| statement.exists([column]))
+                .map_err(|e| format!("Failed to inspect proof index {}: {}", path.display(), e))?;
+            if !has_column {
+                conn.execute_batch(&format!("ALTER TABLE proofs ADD COLUMN {};", definition))
+                    .map_err(|e| format!("Failed to migrate proof index {}: {}", path.display(), e))?;
+            }
+        }
+        conn.execute_batch("CREATE INDEX IF NOT EXISTS proofs_content_hash ON proofs (content_hash);")
+            .map_err(|e| format!("Failed to initialize proof index {}: {}", path.display(), e))?;
+        tracing::info!(path = %path.display(), "Opened proof index");
+        Ok(Self {
+            conn: std::sync::Mutex::new(conn),
+        })
+    }
+
+    /// Record where `record` was stored. Replacing an entry moves it to the end of `list`.
+    pub fn insert(&self, record: &ContentRecord, proof: &IndexedProof) -> Result<(), String> {
+        use prost::Message;
+
+        let uid = record.uid.as_str();
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        conn.execute(
+            "INSERT OR REPLACE INTO proofs (uid, account_address, transaction_id, block_height, record, content_hash)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            rusqlite::params![
+                uid,
+                proof.account_address,
+                proof.transaction_id,
+                proof.block_height.map(|height| height as i64),
+                record.encode_to_vec(),
+                record.content_hash
+            ],
+        )
+        .map(|_| ())
+        .map_err(|e| format!("Failed to index UID {:?}: {}", uid, e))
+    }
+
+    pub fn get(&self, uid: &str) -> Result<Option<IndexedProof>, String> {
+        use rusqlite::OptionalExtension;
+
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        conn.query_row(
+            "SELECT account_address, transaction_id, block_height FROM proofs WHERE uid = ?1",
+            [uid],
+            |row| {
+                Ok(IndexedProof {
+                    account_address: row.get(0)?,
+                    transaction_id: row.get(1)?,
+                    block_height: row.get::<_, Option<i64>>(2)?.map(|height| height as u64),
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| format!("Failed to look up UID {:?}: {}", uid, e))
+    }
+
+    /// Latest proof of a record with the URL, content hash and hash algorithm of `wanted`,
+    /// whatever its UID. Entries indexed before content hashes were kept are not found.
+    pub fn find_content(&self, wanted: &ContentRecord) -> Result<Option<IndexedProof>, String> {
+        use prost::Message;
+
+        let content_hash = &wanted.content_hash;
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let mut statement = conn
+            .prepare(
+                "SELECT account_address, transaction_id, block_height, record FROM proofs
+                 WHERE content_hash = ?1 AND record IS NOT NULL ORDER BY rowid DESC",
+            )
+            .map_err(|e| format!("Failed to look up content hash {}: {}", content_hash, e))?;
+        let rows = statement
+            .query_map([content_hash], |row| {
+                Ok((
+                    IndexedProof {
+                        account_address: row.get(0)?,
+                        transaction_id: row.get(1)?,
+                        block_height: row.get::<_, Option<i64>>(2)?.map(|height| height as u64),
+                    },
+                    row.get::<_, Vec<u8>>(3)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to look up content hash {}: {}", content_hash, e))?;
+
+        for row in rows {
+            let (proof, record) = row.map_err(|e| format!("Failed to look up content hash {}: {}", content_hash, e))?;
+            let record = ContentRecord::decode(record.as_slice())
+                .map_err(|e| format!("Failed to decode indexed record in {}: {}", proof.account_address, e))?;
+            // Indexed records are kept as sent, so their algorithm is known and must be the same
+            if record.url == wanted.url && record.digest_algorithm().ok() == wanted.digest_algorithm().ok() {
+                return Ok(Some(proof));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Up to `limit` entries in insertion order, starting after the `after` cursor, with the
+    /// cursor of the next page if there is one. Cursors are SQLite row IDs.
+    pub fn list(&self, after: Option<i64>, limit: usize) -> Result<(Vec<ListedProof>, Option<i64>), String> {
+        use prost::Message;
+
+        let conn = self.conn.lock().unwrap_or_else(|e| e.into_inner());
+        let mut statement = conn
+            .prepare(
+                "SELECT rowid, uid, account_address, transaction_id, block_height, record FROM proofs
+                 WHERE rowid > ?1 ORDER BY rowid LIMIT ?2",
+            )
+            .map_err(|e| format!("Failed to list proof index: {}", e))?;
+        // One extra row tells whether another page follows
+        let rows = statement
+            .query_map(rusqlite::params![after.unwrap_or(0), limit as i64 + 1], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    ListedProof {
+                        uid: row.get(1)?,
+                        proof: IndexedProof {
+                            account_address: row.get(2)?,
+                            transaction_id: row.get(3)?,
+                            block_height: row.get::<_, Option<i64>>(4)?.map(|height| height as u64),
+                        },
+                        record: None,
+                    },
+                    row.get::<_, Option<Vec<u8>>>(5)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to list proof index: {}", e))?;
+
+        let mut listed = Vec::new();
+        let mut next = None;
+        for row in rows {
+            let (rowid, mut proof, record) = row.map_err(|e| format!("Failed to list proof index: {}", e))?;
+            if listed.len() == limit {
+                next = listed.last().map(|(rowid, _)| *rowid);
+                break;
+            }
+            proof.record = match record {
+                Some(record) => Some(
+                    ContentRecord::decode(record.as_slice())
+                        .map_err(|e| format!("Failed to decode indexed record for UID {:?}: {}", proof.uid, e))?,
+                ),
+                None => None,
+            };
+            listed.push((rowid, proof));
+        }
+        Ok((listed.into_iter().map(|(_, proof)| proof).collect(), next))
+    }
+}
+
+#[cfg(not(feature = "index"))]
+impl ProofIndex {
+    pub fn open(_path: &Path) -> Result<Self, String> {
+        Err("CHAIN_INDEX_PATH is set but the gateway was built without the `index` feature".to_string())
+    }
+
+    pub fn insert(&self, _record: &ContentRecord, _proof: &IndexedProof) -> Result<(), String> {
+        match self.never {}
+    }
+
+    pub fn get(&self, _uid: &str) -> Result<Option<IndexedProof>, String> {
+        match self.never {}
+    }
+
+    pub fn find_content(&self, _wanted: &ContentRecord) -> Result<Option<IndexedProof>, String> {
+        match self.never {}
+    }
+
+    pub fn list(&self, _after: Option<i64>, _limit: usize) -> Result<(Vec<ListedProof>, Option<i64>), String> {
+        match self.never {}
+    }
+}
+
+#[cfg(all(test, feature = "index"))]
+mod tests {
+    use super::*;
+    use crate::proto::HashAlgorithm;
+    use crate::test_util::TempDb;
+
+    fn record(uid: &str) -> ContentRecord {
+        ContentRecord {
+            uid: uid.to_string(),
+            url: "https://example.com/page".to_string(),
+            content_hash: "ab".repeat(32),
+            ..Default::default()
+        }
+    }
+
+    fn proof(transaction_id: &str) -> IndexedProof {
+        IndexedProof {
+            account_address: format!("account-of-{}", transaction_id),
+            transaction_id: transaction_id.to_string(),
+            block_height: Some(7),
+        }
+    }
+
+    #[test]
+    fn content_is_found_by_url_hash_and_algorithm() {
+        let db = TempDb::new("index-content");
+        let index = ProofIndex::open(db.path()).unwrap();
+        index.insert(&record("page-1"), &proof("tx-1")).unwrap();
+        index.insert(&record("page-2"), &proof("tx-2")).unwrap();
+        assert_eq!(index.get("page-1").unwrap(), Some(proof("tx-1")));
+        assert_eq!(index.get("never-stored").unwrap(), None);
+
+        // The latest entry wins whatever its UID
+        assert_eq!(index.find_content(&record("page-3")).unwrap(), Some(proof("tx-2")));
+
+        let other_url = ContentRecord {
+            url: "https://example.com/other".to_string(),
+            ..record("page-3")
+        };
+        assert_eq!(index.find_content(&other_url).unwrap(), None);
+        let keccak = ContentRecord {
+            hash_algorithm: HashAlgorithm::Keccak256 as i32,
+            ..record("page-3")
+        };
+        assert_eq!(index.find_content(&keccak).unwrap(), None);
+    }
+
+    #[test]
+    fn list_pages_in_insertion_order() {
+        let db = TempDb::new("index-list");
+        let index = ProofIndex::open(db.path()).unwrap();
+        for (uid, transaction_id) in [("page-1", "tx-1"), ("page-2", "tx-2"), ("page-1", "tx-3")] {
+            index.insert(&record(uid), &proof(transaction_id)).unwrap();
+        }
+
+        let (first, next) = index.list(None, 1).unwrap();
+        assert_eq!(first[0].uid, "page-2");
+        assert_eq!(first[0].record, Some(record("page-2")));
+        let (second, next) = index.list(next, 1).unwrap();
+        assert_eq!((second[0].uid.as_str(), second[0].proof.transaction_id.as_str()), ("page-1", "tx-3"));
+        assert_eq!(next, None);
+    }
+}
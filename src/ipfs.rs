@@ -0,0 +1,128 @@
+/// Content pinned to IPFS ahead of its proof
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PinnedContent {
+    /// CIDv1 the node stored the content under, base32-encoded
+    pub cid: String,
+    /// Lowercase hex SHA-256 of the content when the CID is a single raw block,
+    /// i.e. the content fit one chunk; larger content is a DAG whose CID hashes its root node
+    pub sha256_digest: Option<String>,
+}
+
+/// Multicodec of a raw block, the CID codec of content small enough for one chunk
+#[cfg(feature = "ipfs")]
+const RAW_CODEC: u8 = 0x55;
+
+/// Multihash code and digest length of SHA-256
+#[cfg(feature = "ipfs")]
+const SHA256_MULTIHASH: [u8; 2] = [0x12, 0x20];
+
+/// Longest an upload may take before the store is abandoned
+#[cfg(feature = "ipfs")]
+const ADD_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Client for the HTTP RPC API of an IPFS node (Kubo), pinning content before its proof is stored
+#[cfg(feature = "ipfs")]
+pub struct IpfsClient {
+    http: reqwest::Client,
+    add_url: url::Url,
+}
+
+/// Stand-in when the crate is built without the `ipfs` feature; it can never be created
+#[cfg(not(feature = "ipfs"))]
+pub struct IpfsClient {
+    never: std::convert::Infallible,
+}
+
+#[cfg(feature = "ipfs")]
+impl IpfsClient {
+    /// Client for the node whose RPC API is at `api_url`, e.g. `http://127.0.0.1:5001`
+    pub fn new(api_url: &str) -> Result<Self, String> {
+        let mut add_url = url::Url::parse(api_url).map_err(|e| format!("Invalid IPFS API URL {:?}: {}", api_url, e))?;
+        add_url
+            .path_segments_mut()
+            .map_err(|()| format!("Invalid IPFS API URL {:?}", api_url))?
+            .pop_if_empty()
+            .extend(["api", "v0", "add"]);
+        // CIDv1 with raw leaves makes single-chunk content addressable by its plain SHA-256
+        add_url
+            .query_pairs_mut()
+            .append_pair("cid-version", "1")
+            .append_pair("raw-leaves", "true")
+            .append_pair("hash", "sha2-256")
+            .append_pair("pin", "true");
+        let http = reqwest::Client::builder()
+            .timeout(ADD_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to build IPFS HTTP client: {}", e))?;
+        tracing::info!(url = %crate::blockchain::redacted_url(api_url), "Pinning content to IPFS");
+        Ok(Self { http, add_url })
+    }
+
+    /// Add and pin `content`, returning the CID it is stored under
+    pub async fn add(&self, content: Vec<u8>) -> Result<PinnedContent, String> {
+        #[derive(serde::Deserialize)]
+        struct AddResponse {
+            #[serde(rename = "Hash")]
+            hash: String,
+        }
+
+        let form = reqwest::multipart::Form::new().part("file", reqwest::multipart::Part::bytes(content));
+        let response = self
+            .http
+            .post(self.add_url.clone())
+            .multipart(form)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("IPFS add failed: {}", e.without_url()))?;
+        let added: AddResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Unexpected IPFS add response: {}", e.without_url()))?;
+        let sha256_digest = raw_sha256_digest(&added.hash);
+        Ok(PinnedContent {
+            cid: added.hash,
+            sha256_digest,
+        })
+    }
+}
+
+/// SHA-256 digest inside a base32 CIDv1 of a raw block; `None` for any other kind of CID
+#[cfg(feature = "ipfs")]
+fn raw_sha256_digest(cid: &str) -> Option<String> {
+    let encoded = cid.strip_prefix('b')?.to_ascii_uppercase();
+    let bytes = data_encoding::BASE32_NOPAD.decode(encoded.as_bytes()).ok()?;
+    match bytes.as_slice() {
+        [0x01, RAW_CODEC, multihash @ ..] if multihash.len() == 34 && multihash[..2] == SHA256_MULTIHASH => {
+            Some(multihash[2..].iter().map(|byte| format!("{:02x}", byte)).collect())
+        }
+        _ => None,
+    }
+}
+
+#[cfg(not(feature = "ipfs"))]
+impl IpfsClient {
+    pub fn new(_api_url: &str) -> Result<Self, String> {
+        Err("CHAIN_IPFS_API_URL is set but the gateway was built without the `ipfs` feature".to_string())
+    }
+
+    pub async fn add(&self, _content: Vec<u8>) -> Result<PinnedContent, String> {
+        match self.never {}
+    }
+}
+
+#[cfg(all(test, feature = "ipfs"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn raw_cid_yields_content_sha256() {
+        // CIDs of the bytes "hello" as a raw block and as a dag-pb node
+        assert_eq!(
+            raw_sha256_digest("bafkreibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq").as_deref(),
+            Some("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824")
+        );
+        assert_eq!(raw_sha256_digest("bafybeibm6jg3ux5qumhcn2b3flc3tyu6dmlb4xa7u5bf44yegnrjhc4yeq"), None);
+        assert_eq!(raw_sha256_digest("QmYwAPJzv5CZsnA625s3Xf2nemtYgPpHdWEz79ojWnPbdG"), None);
+    }
+}
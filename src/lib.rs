@@ -0,0 +1,43 @@
+//! Content-proof storage on Solana and Ethereum, usable as a library or through the
+//! `chain-gateway` gRPC server binary.
+//!
+//! [`BlockchainProvider`] is the chain-agnostic storage interface; build one from a
+//! [`ChainConfig`] with [`providers::create_provider`], or wrap it in [`MyChainGateway`]
+//! to get the full gRPC service with validation, idempotency and in-flight limits.
+
+pub mod proto {
+    tonic::include_proto!("chain_gateway");
+
+    /// Encoded descriptors of the service and its messages, for gRPC reflection
+    pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("chain_gateway_descriptor");
+}
+
+pub mod alerts;
+pub mod audit;
+pub mod auth;
+pub mod blockchain;
+mod confirmation;
+mod events;
+pub mod gateway;
+mod idempotency;
+pub mod index;
+pub mod ipfs;
+pub mod merkle;
+pub mod metrics;
+pub mod probes;
+pub mod providers;
+pub mod rate_limit;
+pub mod request_id;
+pub mod rest;
+pub mod retry_queue;
+pub mod telemetry;
+#[cfg(all(test, any(feature = "index", feature = "queue")))]
+mod test_util;
+
+pub use blockchain::{
+    AccountBalance, BalanceInfo, BlockchainError, BlockchainProvider, BlockchainResult, ChainConfig, ChainConfigBuilder, ChainType, ClosedProof, RentStrategy, StoreOptions,
+    TransactionResult,
+};
+pub use gateway::MyChainGateway;
+#[cfg(feature = "solana")]
+pub use providers::SolanaProvider;
@@ -1,84 +1,665 @@
-use tonic::{transport::Server, Request, Response, Status};
-use proto::chain_gateway_server::{ChainGateway, ChainGatewayServer};
-use proto::{StoreRequest, StoreResponse};
+use clap::{Args, Parser, Subcommand};
 use std::env;
+use std::net::SocketAddr;
+use std::process::ExitCode;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tonic::service::interceptor::InterceptedService;
+use tonic::transport::{Identity, Server, ServerTlsConfig};
+use tonic_health::server::HealthReporter;
+use tonic_health::ServingStatus;
+use tracing::{error, info, warn};
+use tracing_subscriber::fmt::MakeWriter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
 
-pub mod proto {
-    tonic::include_proto!("chain_gateway");
+use chain_gateway::alerts::BalanceAlert;
+use chain_gateway::auth::ApiKeyInterceptor;
+use chain_gateway::proto::chain_gateway_server::ChainGatewayServer;
+use chain_gateway::proto::{ContentRecord, StoreRequest, FILE_DESCRIPTOR_SET};
+use chain_gateway::rate_limit::RateLimitInterceptor;
+use chain_gateway::request_id::RequestIdLayer;
+use chain_gateway::telemetry::{self, OtlpExporter};
+use chain_gateway::{
+    metrics, probes, providers, rest, BlockchainError, BlockchainProvider, BlockchainResult, ChainConfig, MyChainGateway,
+};
+
+/// Address the gRPC server binds to unless `CHAIN_GATEWAY_BIND_ADDR` is set
+const DEFAULT_BIND_ADDR: &str = "0.0.0.0:50051";
+
+/// Address the Prometheus endpoint binds to unless `CHAIN_GATEWAY_METRICS_ADDR` is set
+const DEFAULT_METRICS_ADDR: &str = "0.0.0.0:9090";
+
+/// Address the `/livez` and `/readyz` probes bind to unless `CHAIN_GATEWAY_PROBE_ADDR` is set
+const DEFAULT_PROBE_ADDR: &str = "0.0.0.0:8080";
+
+/// Payer balance below which `/readyz` fails unless `CHAIN_GATEWAY_READY_MIN_BALANCE` is set;
+/// an empty payer can never store
+const DEFAULT_READY_MIN_BALANCE: u64 = 1;
+
+/// How long shutdown waits for in-flight stores unless `CHAIN_GATEWAY_SHUTDOWN_GRACE_SECS` is set
+const DEFAULT_SHUTDOWN_GRACE: Duration = Duration::from_secs(25);
+
+/// Requests per second each client may sustain unless `CHAIN_GATEWAY_RATE_LIMIT_PER_SEC` is set
+const DEFAULT_RATE_LIMIT_PER_SEC: f64 = 10.0;
+
+/// Requests a client may make in a burst unless `CHAIN_GATEWAY_RATE_LIMIT_BURST` is set
+const DEFAULT_RATE_LIMIT_BURST: u32 = 20;
+
+/// Interval between background probes of the blockchain connection
+const HEALTH_PROBE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Largest gRPC request accepted, well below tonic's 4 MiB default; a full batch of
+/// maximum-size records still fits
+const MAX_REQUEST_SIZE: usize = 1024 * 1024;
+
+/// Report NOT_SERVING until the provider connects, then track its health on an interval.
+/// A provider already `initialized` before serving is reported SERVING right away, and one
+/// that failed on its configuration, such as an EVM node on the wrong chain, never is.
+async fn run_health_probe(provider: Arc<dyn BlockchainProvider>, reporter: HealthReporter, initialized: bool) {
+    set_health(&reporter, ServingStatus::NotServing).await;
+
+    let connected = if initialized { Ok(()) } else { provider.initialize().await };
+    match connected {
+        Ok(()) => set_health(&reporter, ServingStatus::Serving).await,
+        Err(e @ BlockchainError::InvalidConfig(_)) => {
+            error!(error = %e, "Blockchain provider is misconfigured; staying NOT_SERVING");
+            return;
+        }
+        Err(e) => error!(error = %e, "Blockchain provider failed to initialize"),
+    }
+
+    let mut interval = tokio::time::interval(HEALTH_PROBE_INTERVAL);
+    let mut healthy = None;
+    loop {
+        interval.tick().await;
+        let is_healthy = match provider.check_health().await {
+            Ok(()) => true,
+            Err(e) => {
+                warn!(error = %e, "Blockchain health probe failed");
+                false
+            }
+        };
+        if healthy != Some(is_healthy) {
+            info!(healthy = is_healthy, "Blockchain connection health changed");
+            let status = if is_healthy { ServingStatus::Serving } else { ServingStatus::NotServing };
+            set_health(&reporter, status).await;
+            healthy = Some(is_healthy);
+        }
+    }
+}
+
+/// Update both the ChainGateway service status and the overall server status
+async fn set_health(reporter: &HealthReporter, status: ServingStatus) {
+    reporter
+        .set_service_status(
+            <ChainGatewayServer<MyChainGateway> as tonic::server::NamedService>::NAME,
+            status,
+        )
+        .await;
+    reporter.set_service_status("", status).await;
 }
 
-pub mod providers;
-mod blockchain;
+/// TLS settings from `CHAIN_GATEWAY_TLS_CERT_PATH` and `CHAIN_GATEWAY_TLS_KEY_PATH`.
+/// Plaintext (`None`) unless both are set; setting only one is an error.
+fn load_tls_config() -> Result<Option<ServerTlsConfig>, String> {
+    let cert_path = env::var("CHAIN_GATEWAY_TLS_CERT_PATH").ok().filter(|path| !path.trim().is_empty());
+    let key_path = env::var("CHAIN_GATEWAY_TLS_KEY_PATH").ok().filter(|path| !path.trim().is_empty());
 
-use providers::SolanaProvider;
-use blockchain::{BlockchainProvider, SolanaConfig};
+    let (cert_path, key_path) = match (cert_path, key_path) {
+        (None, None) => return Ok(None),
+        (Some(cert_path), Some(key_path)) => (cert_path, key_path),
+        (Some(_), None) => return Err("CHAIN_GATEWAY_TLS_CERT_PATH is set but CHAIN_GATEWAY_TLS_KEY_PATH is not".to_string()),
+        (None, Some(_)) => return Err("CHAIN_GATEWAY_TLS_KEY_PATH is set but CHAIN_GATEWAY_TLS_CERT_PATH is not".to_string()),
+    };
 
-pub struct MyChainGateway {
-    provider: SolanaProvider,
+    let cert = std::fs::read(&cert_path).map_err(|e| format!("Failed to read TLS certificate {}: {}", cert_path, e))?;
+    let key = std::fs::read(&key_path).map_err(|e| format!("Failed to read TLS private key {}: {}", key_path, e))?;
+    if !String::from_utf8_lossy(&cert).contains("-----BEGIN CERTIFICATE-----") {
+        return Err(format!("TLS certificate {} is not a PEM certificate", cert_path));
+    }
+    if !String::from_utf8_lossy(&key).contains("PRIVATE KEY-----") {
+        return Err(format!("TLS private key {} is not a PEM private key", key_path));
+    }
+
+    info!(cert = %cert_path, key = %key_path, "Loaded TLS certificate");
+    Ok(Some(ServerTlsConfig::new().identity(Identity::from_pem(cert, key))))
 }
 
-impl Default for MyChainGateway {
-    fn default() -> Self {
-        let config = SolanaConfig {
-            rpc_url: env::var("SOLANA_RPC_URL")
-                .expect("SOLANA_RPC_URL must be set"),
-            program_id: env::var("SOLANA_PROGRAM_ID")
-                .expect("SOLANA_PROGRAM_ID must be set"),
-            keypair_path: env::var("SOLANA_KEYPAIR_PATH")
-                .expect("SOLANA_KEYPAIR_PATH must be set"),
-            proof_account_keypair_path: env::var("SOLANA_PROOF_ACCOUNT_KEYPAIR_PATH")
-                .expect("SOLANA_PROOF_ACCOUNT_KEYPAIR_PATH must be set"),
-        };
+/// API-key check from `CHAIN_GATEWAY_API_KEYS` (comma-separated). Authentication can only
+/// be turned off explicitly with `CHAIN_GATEWAY_AUTH_DISABLED=true`.
+fn load_auth() -> Result<ApiKeyInterceptor, String> {
+    let disabled = match env::var("CHAIN_GATEWAY_AUTH_DISABLED") {
+        Ok(value) => value
+            .trim()
+            .parse::<bool>()
+            .map_err(|e| format!("Invalid CHAIN_GATEWAY_AUTH_DISABLED {:?}: {}", value, e))?,
+        Err(_) => false,
+    };
+    if disabled {
+        warn!("API key authentication is DISABLED; anyone who can reach the server can store proofs");
+        return Ok(ApiKeyInterceptor::disabled());
+    }
+
+    let keys: Vec<String> = env::var("CHAIN_GATEWAY_API_KEYS")
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|key| !key.is_empty())
+        .map(str::to_string)
+        .collect();
+    if keys.is_empty() {
+        return Err(
+            "No API keys configured: set CHAIN_GATEWAY_API_KEYS, or CHAIN_GATEWAY_AUTH_DISABLED=true for local development"
+                .to_string(),
+        );
+    }
 
-        let provider = SolanaProvider::new(config)
-            .expect("Failed to initialize Solana provider");
+    info!(keys = keys.len(), "API key authentication enabled");
+    Ok(ApiKeyInterceptor::new(keys))
+}
+
+/// Per-client limit from `CHAIN_GATEWAY_RATE_LIMIT_PER_SEC` and `CHAIN_GATEWAY_RATE_LIMIT_BURST`;
+/// a rate of 0 disables it
+fn load_rate_limit(by_api_key: bool) -> Result<RateLimitInterceptor, String> {
+    let rate: f64 = match env::var("CHAIN_GATEWAY_RATE_LIMIT_PER_SEC") {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map_err(|e| format!("Invalid CHAIN_GATEWAY_RATE_LIMIT_PER_SEC {:?}: {}", value, e))?,
+        Err(_) => DEFAULT_RATE_LIMIT_PER_SEC,
+    };
+    let burst: u32 = match env::var("CHAIN_GATEWAY_RATE_LIMIT_BURST") {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map_err(|e| format!("Invalid CHAIN_GATEWAY_RATE_LIMIT_BURST {:?}: {}", value, e))?,
+        Err(_) => DEFAULT_RATE_LIMIT_BURST,
+    };
 
-        Self { provider }
+    if !rate.is_finite() || rate < 0.0 {
+        return Err(format!("Invalid CHAIN_GATEWAY_RATE_LIMIT_PER_SEC {}: must be 0 or positive", rate));
     }
+    if rate == 0.0 {
+        warn!("Per-client rate limiting is disabled");
+        return Ok(RateLimitInterceptor::disabled());
+    }
+
+    info!(rate_per_sec = rate, burst, by_api_key, "Per-client rate limiting enabled");
+    Ok(RateLimitInterceptor::new(rate, burst, by_api_key))
 }
 
-#[tonic::async_trait]
-impl ChainGateway for MyChainGateway {
-    async fn store(&self, request: Request<StoreRequest>) -> Result<Response<StoreResponse>, Status> {
-        println!("Received StoreRequest: {:?}", request);
+/// Listen address for an auxiliary HTTP endpoint from `var`, or `default` when unset;
+/// `off` disables the endpoint
+fn load_http_addr(var: &str, default: &str) -> Result<Option<SocketAddr>, String> {
+    let value = env::var(var).unwrap_or_else(|_| default.to_string());
+    if value.trim().eq_ignore_ascii_case("off") {
+        info!(var, "HTTP endpoint disabled");
+        return Ok(None);
+    }
+    value
+        .trim()
+        .parse()
+        .map(Some)
+        .map_err(|e| format!("Invalid {} {:?}: {}", var, value, e))
+}
 
-        let record = match &request.get_ref().record {
-            Some(record) => record,
-            None => return Err(Status::invalid_argument("Record is missing")),
-        };
+/// Bind an auxiliary HTTP endpoint early, so a taken port fails startup instead of a background task
+async fn bind_http(var: &str, default: &str) -> Result<Option<tokio::net::TcpListener>, String> {
+    match load_http_addr(var, default)? {
+        Some(addr) => tokio::net::TcpListener::bind(addr)
+            .await
+            .map(Some)
+            .map_err(|e| format!("Failed to bind {} {}: {}", var, addr, e)),
+        None => Ok(None),
+    }
+}
+
+/// How log lines are written, chosen with `LOG_FORMAT`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LogFormat {
+    /// Human-readable lines, the default
+    Pretty,
+    /// One JSON object per line for log pipelines, with event fields at the top level and the
+    /// fields of every enclosing span, such as `request_id` and `uid`, under `spans`
+    Json,
+}
+
+impl LogFormat {
+    fn from_env() -> Result<Self, String> {
+        let value = env::var("LOG_FORMAT").unwrap_or_default();
+        match value.trim().to_ascii_lowercase().as_str() {
+            "" | "pretty" => Ok(Self::Pretty),
+            "json" => Ok(Self::Json),
+            _ => Err(format!("LOG_FORMAT must be json or pretty, not {:?}", value)),
+        }
+    }
 
-        // Call provider to store the record
-        match self.provider.store_record(record).await {
-            Ok(result) => {
-                let response = StoreResponse {
-                    success: true,
-                    transaction_id: result.transaction_id.clone(),
-                    account_address: result.transaction_id, // Using transaction ID as account address for now
-                };
-                Ok(Response::new(response))
+    /// Formatting layer writing log lines to `writer`
+    fn layer<S, W>(self, writer: W) -> Box<dyn Layer<S> + Send + Sync>
+    where
+        S: tracing::Subscriber + for<'span> LookupSpan<'span>,
+        W: for<'writer> MakeWriter<'writer> + Send + Sync + 'static,
+    {
+        match self {
+            Self::Pretty => tracing_subscriber::fmt::layer().with_writer(writer).boxed(),
+            Self::Json => tracing_subscriber::fmt::layer()
+                .json()
+                .flatten_event(true)
+                .with_current_span(false)
+                .with_span_list(true)
+                .with_writer(writer)
+                .boxed(),
+        }
+    }
+}
+
+/// Resolve on SIGINT, or SIGTERM on Unix
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            error!(error = %e, "Failed to listen for SIGINT");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut signal) => {
+                signal.recv().await;
             }
             Err(e) => {
-                eprintln!("Blockchain transaction failed: {}", e);
-                Err(Status::internal("Failed to store on blockchain"))
+                error!(error = %e, "Failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
             }
         }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received SIGINT"),
+        _ = terminate => info!("Received SIGTERM"),
     }
 }
 
+#[derive(Parser)]
+#[command(version, about = "gRPC gateway that stores content proofs on chain")]
+struct Cli {
+    /// TOML file with chain profiles; overrides CHAIN_CONFIG_PATH
+    #[arg(long, global = true)]
+    config: Option<String>,
+    /// Profile to load from the config file; overrides CHAIN_PROFILE
+    #[arg(long, global = true)]
+    profile: Option<String>,
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Run the gRPC server (the default)
+    Serve(ServeArgs),
+    /// Store a single record and print its transaction ID
+    Store(StoreArgs),
+}
+
+#[derive(Args, Default)]
+struct ServeArgs {
+    /// Address the gRPC server listens on; overrides CHAIN_GATEWAY_BIND_ADDR
+    #[arg(long)]
+    bind: Option<SocketAddr>,
+    /// Further profile from the config file whose chain stores can select; repeatable,
+    /// overrides CHAIN_EXTRA_PROFILES
+    #[arg(long = "extra-profile")]
+    extra_profiles: Vec<String>,
+}
+
+#[derive(Args)]
+struct StoreArgs {
+    /// URL of the content
+    #[arg(long)]
+    url: String,
+    /// Lowercase hex SHA-256 digest of the content
+    #[arg(long)]
+    hash: String,
+    /// Content length in bytes
+    #[arg(long)]
+    length: u64,
+    /// Record UID; required when proof accounts are PDAs
+    #[arg(long)]
+    uid: Option<String>,
+    /// Simulate the store and print the program logs instead of submitting it
+    #[arg(long)]
+    dry_run: bool,
+}
+
+/// Providers for the profiles in `--extra-profile` or `CHAIN_EXTRA_PROFILES`, loaded from
+/// the same config file as the primary chain
+fn load_additional_chains(
+    config_path: Option<&str>,
+    profiles: Vec<String>,
+) -> BlockchainResult<Vec<Arc<dyn BlockchainProvider>>> {
+    let profiles = if profiles.is_empty() {
+        env::var("CHAIN_EXTRA_PROFILES")
+            .unwrap_or_default()
+            .split(',')
+            .map(str::trim)
+            .filter(|profile| !profile.is_empty())
+            .map(str::to_string)
+            .collect()
+    } else {
+        profiles
+    };
+    if profiles.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(path) = config_path.map(str::to_string).or_else(|| env::var("CHAIN_CONFIG_PATH").ok()) else {
+        return Err(BlockchainError::InvalidConfig(
+            "CHAIN_EXTRA_PROFILES needs a config file to load them from; set --config or CHAIN_CONFIG_PATH".to_string(),
+        ));
+    };
+    profiles
+        .iter()
+        .map(|profile| {
+            info!(path = %path, profile = %profile, "Loading additional chain configuration from file");
+            providers::create_provider(ChainConfig::from_toml(&path, Some(profile))?)
+        })
+        .collect()
+}
+
+/// Load the chain configuration from a TOML profile when a config file is given,
+/// otherwise from environment variables
+fn load_config(config_path: Option<String>, profile: Option<String>) -> BlockchainResult<ChainConfig> {
+    let config_path = config_path.or_else(|| env::var("CHAIN_CONFIG_PATH").ok());
+    let profile = profile.or_else(|| env::var("CHAIN_PROFILE").ok());
+
+    match config_path {
+        Some(path) => {
+            info!(path = %path, profile = ?profile, "Loading chain configuration from file");
+            ChainConfig::from_toml(&path, profile.as_deref())
+        }
+        None => ChainConfig::from_env(),
+    }
+}
+
+/// Store one record through the same validation as the Store RPC. Only the transaction ID
+/// (or the simulation logs of a dry run) goes to stdout, so scripts can capture it.
+async fn store_once(config: ChainConfig, args: StoreArgs) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let gateway = MyChainGateway::new(config)?;
+    gateway.provider().initialize().await?;
+
+    let request = StoreRequest {
+        record: Some(ContentRecord {
+            uid: args.uid.unwrap_or_default(),
+            url: args.url,
+            content_hash: args.hash,
+            content_length: args.length,
+            ..Default::default()
+        }),
+        dry_run: args.dry_run,
+        ..Default::default()
+    };
+    let response = gateway
+        .store_request(&request)
+        .await
+        .map_err(|status| format!("Store failed ({:?}): {}", status.code(), status.message()))?;
+
+    if response.dry_run {
+        for line in &response.simulation_logs {
+            println!("{}", line);
+        }
+    } else {
+        println!("{}", response.transaction_id);
+    }
+    Ok(())
+}
+
 #[tokio::main]
-async fn main() -> Result<(), Box<dyn std::error::Error>> {
+async fn main() -> ExitCode {
+    match run().await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            // Displayed, rather than the Debug form returning the error from main would print
+            eprintln!("chain-gateway: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+async fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     // Load environment variables from .env file
     dotenv::dotenv().ok();
+    let cli = Cli::parse();
+    let command = cli.command.unwrap_or(Command::Serve(ServeArgs::default()));
 
-    let addr = "0.0.0.0:50051".parse()?;
-    let service = MyChainGateway::default();
+    // Log level is controlled through RUST_LOG, e.g. RUST_LOG=chain_gateway=trace, and
+    // applies to exported spans too. A one-shot store keeps stdout for its result.
+    let log_format = LogFormat::from_env()?;
+    let otlp = match env::var("CHAIN_GATEWAY_OTLP_ENDPOINT") {
+        Ok(endpoint) if !endpoint.trim().is_empty() => Some(OtlpExporter::new(endpoint.trim())?),
+        _ => None,
+    };
+    let subscriber = tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")))
+        .with(otlp.as_ref().map(|otlp| otlp.layer()));
+    let result = match command {
+        Command::Serve(args) => {
+            subscriber.with(log_format.layer(std::io::stdout)).init();
+            serve(args, cli.config, cli.profile).await
+        }
+        Command::Store(args) => {
+            subscriber.with(log_format.layer(std::io::stderr)).init();
+            store_once(load_config(cli.config, cli.profile)?, args).await
+        }
+    };
+    if let Some(otlp) = &otlp {
+        otlp.shutdown();
+    }
+    result
+}
 
-    println!("ChainGateway gRPC server listening on {}", addr);
+/// Run the gRPC server until SIGTERM or SIGINT, then drain in-flight stores
+async fn serve(
+    args: ServeArgs,
+    config_path: Option<String>,
+    profile: Option<String>,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let addr = match args.bind {
+        Some(addr) => addr,
+        None => {
+            let bind_addr = env::var("CHAIN_GATEWAY_BIND_ADDR").unwrap_or_else(|_| DEFAULT_BIND_ADDR.to_string());
+            bind_addr
+                .parse()
+                .map_err(|e| format!("Invalid CHAIN_GATEWAY_BIND_ADDR {:?}: {}", bind_addr, e))?
+        }
+    };
+    let shutdown_grace = match env::var("CHAIN_GATEWAY_SHUTDOWN_GRACE_SECS") {
+        Ok(value) => Duration::from_secs(
+            value
+                .trim()
+                .parse()
+                .map_err(|e| format!("Invalid CHAIN_GATEWAY_SHUTDOWN_GRACE_SECS {:?}: {}", value, e))?,
+        ),
+        Err(_) => DEFAULT_SHUTDOWN_GRACE,
+    };
+    let ready_min_balance = match env::var("CHAIN_GATEWAY_READY_MIN_BALANCE") {
+        Ok(value) => value
+            .trim()
+            .parse()
+            .map_err(|e| format!("Invalid CHAIN_GATEWAY_READY_MIN_BALANCE {:?}: {}", value, e))?,
+        Err(_) => DEFAULT_READY_MIN_BALANCE,
+    };
+    let reflection = match env::var("CHAIN_GATEWAY_REFLECTION") {
+        Ok(value) => value
+            .trim()
+            .parse::<bool>()
+            .map_err(|e| format!("Invalid CHAIN_GATEWAY_REFLECTION {:?}: {}", value, e))?,
+        Err(_) => false,
+    };
+    let metrics_listener = bind_http("CHAIN_GATEWAY_METRICS_ADDR", DEFAULT_METRICS_ADDR).await?;
+    let probe_listener = bind_http("CHAIN_GATEWAY_PROBE_ADDR", DEFAULT_PROBE_ADDR).await?;
+    let rest_listener = bind_http("CHAIN_GATEWAY_REST_ADDR", "off").await?;
+    // Each request gets a span that continues the caller's trace when exporting is enabled
+    let mut builder = Server::builder().trace_fn(telemetry::request_span);
+    if let Some(tls_config) = load_tls_config()? {
+        // rustls parses the identity here, rejecting malformed keys and a key that does not match the certificate
+        builder = builder.tls_config(tls_config).map_err(|e| {
+            let cause = std::error::Error::source(&e).map(|cause| cause.to_string()).unwrap_or_else(|| e.to_string());
+            format!("Invalid TLS certificate or key: {}", cause)
+        })?;
+    }
+    let auth = load_auth()?;
+    let rate_limit = load_rate_limit(auth.is_enabled())?;
+    let additional_chains = load_additional_chains(config_path.as_deref(), args.extra_profiles)?;
+    let config = load_config(config_path, profile)?;
+    let self_test = config.startup_self_test;
+    let balance_alert = BalanceAlert::from_config(&config)?;
+    let mut service = MyChainGateway::new(config)?;
+    for provider in additional_chains {
+        service = service.with_additional_chain(provider)?;
+    }
+    let service = Arc::new(service);
+    // Stores may select these chains as soon as the server is up, so they connect first
+    for provider in service.additional_chains() {
+        provider
+            .initialize()
+            .await
+            .map_err(|e| format!("Chain {} failed to initialize: {}", provider.chain_type(), e))?;
+    }
+    if self_test {
+        // Before anything is served, so a gateway that cannot store never reports ready
+        service.provider().initialize().await?;
+        service
+            .self_test()
+            .await
+            .map_err(|e| format!("Startup self-test failed: {}", e))?;
+    }
+
+    let (health_reporter, health_service) = tonic_health::server::health_reporter();
+    // v1alpha is what most grpcurl and Postman versions still ask for
+    let (reflection_v1, reflection_v1alpha) = if reflection {
+        info!("Serving gRPC reflection");
+        let reflection = || {
+            tonic_reflection::server::Builder::configure()
+                .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+                .register_encoded_file_descriptor_set(tonic_health::pb::FILE_DESCRIPTOR_SET)
+        };
+        (Some(reflection().build_v1()?), Some(reflection().build_v1alpha()?))
+    } else {
+        (None, None)
+    };
+    tokio::spawn(run_health_probe(service.provider().clone(), health_reporter, self_test));
+    tokio::spawn(service.track_confirmations());
+    if let Some(balance_alert) = balance_alert {
+        tokio::spawn(balance_alert.run(service.provider().clone()));
+    }
+    if service.has_retry_queue() {
+        tokio::spawn(Arc::clone(&service).run_retry_queue());
+    }
+    if let Some(listener) = metrics_listener {
+        tokio::spawn(metrics::serve(listener));
+    }
+    if let Some(listener) = probe_listener {
+        tokio::spawn(probes::serve(listener, service.provider().clone(), ready_min_balance));
+    }
 
-    Server::builder()
-        .add_service(ChainGatewayServer::new(service))
-        .serve(addr)
-        .await?;
+    // Once signalled, both servers stop accepting requests and let open ones finish
+    let (stop, _) = watch::channel(false);
+    let stopped = |stop: &watch::Sender<bool>| {
+        let mut stopped = stop.subscribe();
+        async move {
+            let _ = stopped.wait_for(|stopped| *stopped).await;
+        }
+    };
+    let rest_server = rest_listener.map(|listener| {
+        tokio::spawn(rest::serve(listener, Arc::clone(&service), auth.clone(), rate_limit.clone(), stopped(&stop)))
+    });
+
+    info!(%addr, "ChainGateway gRPC server listening");
+
+    let mut server = tokio::spawn(
+        builder
+            .layer(RequestIdLayer)
+            .add_service(health_service)
+            // Health checks stay unauthenticated so orchestrator probes keep working, and
+            // reflection only describes the API, so tools can list it before holding a key
+            .add_optional_service(reflection_v1)
+            .add_optional_service(reflection_v1alpha)
+            // Authentication runs first, so only vetted keys get a bucket of their own
+            .add_service(InterceptedService::new(
+                InterceptedService::new(
+                    ChainGatewayServer::from_arc(Arc::clone(&service)).max_decoding_message_size(MAX_REQUEST_SIZE),
+                    rate_limit,
+                ),
+                auth,
+            ))
+            .serve_with_shutdown(addr, stopped(&stop)),
+    );
+
+    tokio::select! {
+        result = &mut server => return Ok(result??),
+        _ = shutdown_signal() => {}
+    }
+
+    let in_flight = service.in_flight_stores();
+    info!(in_flight, grace_secs = shutdown_grace.as_secs(), "Shutting down, draining in-flight stores");
+    stop.send_replace(true);
+    service.close_subscriptions();
+
+    let servers_stopped = async {
+        let result = (&mut server).await;
+        if let Some(rest_server) = rest_server {
+            let _ = rest_server.await;
+        }
+        result
+    };
+    match tokio::time::timeout(shutdown_grace, servers_stopped).await {
+        Ok(result) => {
+            result??;
+            let abandoned = service.in_flight_stores();
+            info!(drained = in_flight.saturating_sub(abandoned), abandoned, "Shutdown complete");
+        }
+        Err(_) => {
+            let abandoned = service.in_flight_stores();
+            warn!(
+                drained = in_flight.saturating_sub(abandoned),
+                abandoned,
+                "Grace period elapsed; abandoning stores that may still confirm on chain"
+            );
+        }
+    }
 
     Ok(())
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_defaults_to_serve_and_parses_store() {
+        let cli = Cli::try_parse_from(["chain-gateway", "--profile", "devnet"]).unwrap();
+        assert!(cli.command.is_none());
+        assert_eq!(cli.profile.as_deref(), Some("devnet"));
+
+        let cli = Cli::try_parse_from([
+            "chain-gateway", "store", "--url", "https://example.com", "--hash", "ab", "--length", "7", "--config", "chains.toml",
+        ])
+        .unwrap();
+        assert_eq!(cli.config.as_deref(), Some("chains.toml"));
+        match cli.command {
+            Some(Command::Store(args)) => {
+                assert_eq!(args.length, 7);
+                assert!(args.uid.is_none());
+            }
+            _ => panic!("expected the store subcommand"),
+        }
+
+        assert!(Cli::try_parse_from(["chain-gateway", "store", "--url", "https://example.com"]).is_err());
+    }
+}
@@ -0,0 +1,265 @@
+use sha2::{Digest, Sha256};
+
+use crate::proto::{self, ContentRecord};
+
+/// URL scheme reserved for the proofs Merkle roots are stored as; clients may not store under it
+const MERKLE_URL_SCHEME: &str = "merkle:";
+
+/// URL of the proof a Merkle root is stored as, followed by the number of leaves
+pub const MERKLE_ROOT_URL_PREFIX: &str = "merkle://batch/";
+
+/// UID of the proof a Merkle root is stored as, followed by the root in hex
+const MERKLE_ROOT_UID_PREFIX: &str = "merkle:";
+
+/// Domain separation, so a leaf can never be passed off as an inner node or the reverse
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+pub type Hash = [u8; 32];
+
+/// One level of a path from a leaf to the root
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofStep {
+    pub sibling: Hash,
+    /// The sibling is hashed before the running node rather than after it
+    pub sibling_on_left: bool,
+}
+
+/// Leaf committing to a record: SHA-256 over `0x00`, then the UID, URL and content hash, each
+/// prefixed with its byte length as a little-endian u32, then the content length as a
+/// little-endian u64. The content hash is taken as stored on chain, `<algorithm>:<hex>` for all
+/// but SHA-256, so equal digests under different algorithms are different leaves.
+pub fn leaf_hash(record: &ContentRecord) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([LEAF_PREFIX]);
+    for field in [&record.uid, &record.url, &record.stored_content_hash()] {
+        hasher.update((field.len() as u32).to_le_bytes());
+        hasher.update(field.as_bytes());
+    }
+    hasher.update(record.content_length.to_le_bytes());
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([NODE_PREFIX]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Binary Merkle tree over record leaves. A node without a sibling on its level is carried up
+/// unchanged, so no leaf is ever duplicated and an odd level adds no step to the paths through it.
+pub struct MerkleTree {
+    /// Leaves first, the root alone last
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    /// Tree over `leaves`, which must not be empty
+    pub fn new(leaves: Vec<Hash>) -> Self {
+        assert!(!leaves.is_empty(), "a Merkle tree needs at least one leaf");
+        let mut levels = vec![leaves];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let parents = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single] => *single,
+                    _ => unreachable!(),
+                })
+                .collect();
+            levels.push(parents);
+        }
+        Self { levels }
+    }
+
+    pub fn root(&self) -> Hash {
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    /// Path from leaf `index` to the root, nearest sibling first
+    pub fn path(&self, mut index: usize) -> Vec<ProofStep> {
+        let mut path = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            let sibling = index ^ 1;
+            if let Some(hash) = level.get(sibling) {
+                path.push(ProofStep {
+                    sibling: *hash,
+                    sibling_on_left: sibling < index,
+                });
+            }
+            index /= 2;
+        }
+        path
+    }
+}
+
+/// Root reached by folding `path` into `leaf`
+pub fn root_from_path(leaf: Hash, path: &[ProofStep]) -> Hash {
+    path.iter().fold(leaf, |node, step| {
+        if step.sibling_on_left {
+            node_hash(&step.sibling, &node)
+        } else {
+            node_hash(&node, &step.sibling)
+        }
+    })
+}
+
+/// Whether a normalized URL is in the scheme reserved for Merkle roots
+pub fn is_reserved_url(url: &str) -> bool {
+    url.starts_with(MERKLE_URL_SCHEME)
+}
+
+/// UID of the proof the root with hex `root` is stored as
+pub fn root_uid(root: &str) -> String {
+    format!("{}{}", MERKLE_ROOT_UID_PREFIX, root)
+}
+
+/// Proof stored on chain in place of the records under `root`: the root is its content hash
+/// and the leaf count its content length
+pub fn root_record(root: &Hash, leaf_count: usize) -> ContentRecord {
+    let root = to_hex(root);
+    ContentRecord {
+        uid: root_uid(&root),
+        url: format!("{}{}", MERKLE_ROOT_URL_PREFIX, leaf_count),
+        content_hash: root,
+        content_length: leaf_count as u64,
+        hash_algorithm: proto::HashAlgorithm::Sha256 as i32,
+        ..Default::default()
+    }
+}
+
+impl From<ProofStep> for proto::MerkleProofStep {
+    fn from(step: ProofStep) -> Self {
+        Self {
+            sibling: to_hex(&step.sibling),
+            sibling_on_left: step.sibling_on_left,
+        }
+    }
+}
+
+impl TryFrom<&proto::MerkleProofStep> for ProofStep {
+    type Error = String;
+
+    fn try_from(step: &proto::MerkleProofStep) -> Result<Self, Self::Error> {
+        Ok(Self {
+            sibling: from_hex(&step.sibling).ok_or_else(|| format!("sibling {:?} is not 64 hex characters", step.sibling))?,
+            sibling_on_left: step.sibling_on_left,
+        })
+    }
+}
+
+pub fn to_hex(hash: &Hash) -> String {
+    hash.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Parse 64 hex characters, in either case
+pub fn from_hex(hex: &str) -> Option<Hash> {
+    if hex.len() != 64 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut hash = [0; 32];
+    for (byte, pair) in hash.iter_mut().zip(hex.as_bytes().chunks(2)) {
+        *byte = u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()?;
+    }
+    Some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(uid: &str) -> ContentRecord {
+        ContentRecord {
+            uid: uid.to_string(),
+            url: "https://example.com/page".to_string(),
+            content_hash: "ab".repeat(32),
+            content_length: 1024,
+            ..Default::default()
+        }
+    }
+
+    fn leaves(count: usize) -> Vec<Hash> {
+        (1..=count).map(|index| leaf_hash(&record(&format!("page-{}", index)))).collect()
+    }
+
+    #[test]
+    fn leaves_commit_to_the_hash_algorithm() {
+        let sha256 = record("page-1");
+        let explicit = ContentRecord {
+            hash_algorithm: proto::HashAlgorithm::Sha256 as i32,
+            ..sha256.clone()
+        };
+        let keccak = ContentRecord {
+            hash_algorithm: proto::HashAlgorithm::Keccak256 as i32,
+            ..sha256.clone()
+        };
+        assert_eq!(leaf_hash(&sha256), leaf_hash(&explicit));
+        assert_ne!(leaf_hash(&sha256), leaf_hash(&keccak));
+    }
+
+    #[test]
+    fn single_leaf_is_its_own_root() {
+        let leaves = leaves(1);
+        let tree = MerkleTree::new(leaves.clone());
+        assert_eq!(tree.root(), leaves[0]);
+        assert!(tree.path(0).is_empty());
+    }
+
+    #[test]
+    fn unpaired_nodes_are_carried_up_without_a_step() {
+        let [a, b, c, d, e]: [Hash; 5] = leaves(5).try_into().unwrap();
+        let ab = node_hash(&a, &b);
+        assert_eq!(MerkleTree::new(vec![a, b]).root(), ab);
+        assert_eq!(MerkleTree::new(vec![a, b, c]).root(), node_hash(&ab, &c));
+        let abcd = node_hash(&ab, &node_hash(&c, &d));
+        let five = MerkleTree::new(vec![a, b, c, d, e]);
+        assert_eq!(five.root(), node_hash(&abcd, &e));
+        // e waits alone on two levels and meets abcd only at the top
+        assert_eq!(
+            five.path(4),
+            [ProofStep {
+                sibling: abcd,
+                sibling_on_left: true,
+            }]
+        );
+    }
+
+    #[test]
+    fn every_leaf_folds_back_to_the_root() {
+        for count in 1..=5 {
+            let leaves = leaves(count);
+            let tree = MerkleTree::new(leaves.clone());
+            for (index, leaf) in leaves.into_iter().enumerate() {
+                assert_eq!(root_from_path(leaf, &tree.path(index)), tree.root(), "leaf {} of {}", index, count);
+            }
+        }
+    }
+
+    #[test]
+    fn a_flipped_step_does_not_verify() {
+        let leaves = leaves(3);
+        let tree = MerkleTree::new(leaves.clone());
+        let mut path = tree.path(1);
+        path[0].sibling_on_left = !path[0].sibling_on_left;
+        assert_ne!(root_from_path(leaves[1], &path), tree.root());
+    }
+
+    #[test]
+    fn hex_must_be_64_hex_characters() {
+        let hash = leaves(1)[0];
+        assert_eq!(from_hex(&to_hex(&hash)), Some(hash));
+        assert_eq!(from_hex(&to_hex(&hash).to_uppercase()), Some(hash));
+        assert_eq!(from_hex(&"ab".repeat(31)), None);
+        assert_eq!(from_hex(&"ab".repeat(33)), None);
+        assert_eq!(from_hex(&"zz".repeat(32)), None);
+        // 64 bytes, but not 64 characters
+        assert_eq!(from_hex(&"é".repeat(32)), None);
+        let short = proto::MerkleProofStep {
+            sibling: "ab".to_string(),
+            sibling_on_left: false,
+        };
+        assert!(ProofStep::try_from(&short).is_err());
+    }
+}
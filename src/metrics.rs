@@ -0,0 +1,170 @@
+use axum::http::header::CONTENT_TYPE;
+use axum::routing::get;
+use axum::Router;
+use prometheus::{Encoder, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry, TextEncoder};
+use std::sync::LazyLock;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tracing::{debug, error, info};
+
+/// Confirmation latency buckets in seconds, from a fast Solana confirm to a slow Ethereum block
+const CONFIRMATION_BUCKETS: &[f64] = &[0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 15.0, 30.0, 60.0, 120.0];
+
+/// How a submitted transaction that was waited on ended, the `outcome` label of `confirmation_seconds`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmationOutcome {
+    Confirmed,
+    /// Executed with an error or reverted
+    Failed,
+    /// Dropped by the network, e.g. because its blockhash expired
+    Expired,
+    /// Still unconfirmed when the gateway stopped waiting
+    TimedOut,
+}
+
+impl ConfirmationOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Confirmed => "confirmed",
+            Self::Failed => "failed",
+            Self::Expired => "expired",
+            Self::TimedOut => "timed_out",
+        }
+    }
+}
+
+/// Process-wide metrics, registered once and served on the `/metrics` endpoint
+pub struct Metrics {
+    registry: Registry,
+    /// Store requests received, counting each record of a batch
+    pub store_requests: IntCounter,
+    pub stores_succeeded: IntCounter,
+    /// Stores answered with an existing proof of the same record instead of a new transaction
+    pub stores_deduplicated: IntCounter,
+    /// Failed stores by gRPC status code
+    pub stores_failed: IntCounterVec,
+    /// Time from submission until a transaction confirmed or was given up on, by commitment and outcome
+    pub confirmation_seconds: HistogramVec,
+    /// Store requests holding an in-flight slot
+    pub stores_in_flight: IntGauge,
+    /// Solana payer balance in lamports, as of the last balance check; the largest in a payer pool
+    pub payer_balance: IntGauge,
+    /// Stores waiting in the durable retry queue
+    pub retry_queue_depth: IntGauge,
+    /// Audit log entries lost because the writer fell behind or stopped
+    pub audit_entries_dropped: IntCounter,
+    /// RPC retries refused because the shared retry budget was spent
+    pub rpc_retries_rejected: IntCounter,
+    /// Transactions re-signed and resent after their blockhash expired before they landed
+    pub blockhash_resends: IntCounter,
+    /// Stored events skipped by `SubscribeStored` callers that fell behind
+    pub stored_events_missed: IntCounter,
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let registry = Registry::new_custom(Some("chain_gateway".to_string()), None)?;
+        let metrics = Self {
+            store_requests: IntCounter::new("store_requests_total", "Store requests received, per record")?,
+            stores_succeeded: IntCounter::new("stores_succeeded_total", "Records stored successfully")?,
+            stores_deduplicated: IntCounter::new(
+                "stores_deduplicated_total",
+                "Stores answered with an existing proof instead of a new transaction",
+            )?,
+            stores_failed: IntCounterVec::new(
+                Opts::new("stores_failed_total", "Records that failed to store, by gRPC status code"),
+                &["code"],
+            )?,
+            confirmation_seconds: HistogramVec::new(
+                HistogramOpts::new(
+                    "confirmation_seconds",
+                    "Time from submission until a transaction confirmed or was given up on",
+                )
+                .buckets(CONFIRMATION_BUCKETS.to_vec()),
+                &["commitment", "outcome"],
+            )?,
+            stores_in_flight: IntGauge::new("stores_in_flight", "Store requests submitting or awaiting confirmation")?,
+            payer_balance: IntGauge::new("payer_balance", "Solana payer balance in lamports at the last check")?,
+            retry_queue_depth: IntGauge::new("retry_queue_depth", "Stores queued for resubmission after the chain was unreachable")?,
+            audit_entries_dropped: IntCounter::new(
+                "audit_entries_dropped_total",
+                "Audit log entries dropped because the writer fell behind",
+            )?,
+            rpc_retries_rejected: IntCounter::new(
+                "rpc_retries_rejected_total",
+                "RPC retries refused because the retry budget was spent",
+            )?,
+            blockhash_resends: IntCounter::new(
+                "blockhash_resends_total",
+                "Transactions resent with a fresh blockhash after theirs expired",
+            )?,
+            stored_events_missed: IntCounter::new(
+                "stored_events_missed_total",
+                "Stored events skipped by subscribers that fell behind",
+            )?,
+            registry,
+        };
+        metrics.registry.register(Box::new(metrics.store_requests.clone()))?;
+        metrics.registry.register(Box::new(metrics.stores_succeeded.clone()))?;
+        metrics.registry.register(Box::new(metrics.stores_deduplicated.clone()))?;
+        metrics.registry.register(Box::new(metrics.stores_failed.clone()))?;
+        metrics.registry.register(Box::new(metrics.confirmation_seconds.clone()))?;
+        metrics.registry.register(Box::new(metrics.stores_in_flight.clone()))?;
+        metrics.registry.register(Box::new(metrics.payer_balance.clone()))?;
+        metrics.registry.register(Box::new(metrics.retry_queue_depth.clone()))?;
+        metrics.registry.register(Box::new(metrics.audit_entries_dropped.clone()))?;
+        metrics.registry.register(Box::new(metrics.rpc_retries_rejected.clone()))?;
+        metrics.registry.register(Box::new(metrics.blockhash_resends.clone()))?;
+        metrics.registry.register(Box::new(metrics.stored_events_missed.clone()))?;
+        Ok(metrics)
+    }
+
+    /// Count failed records under their status code, e.g. `Unavailable`
+    pub fn record_failures(&self, code: tonic::Code, records: u64) {
+        self.stores_failed.with_label_values(&[&format!("{:?}", code)]).inc_by(records);
+    }
+
+    /// Record how long a transaction took from submission to `outcome` at `commitment`, e.g.
+    /// `confirmed` on Solana, and log it at completion
+    pub fn record_confirmation(&self, commitment: &str, outcome: ConfirmationOutcome, elapsed: Duration) {
+        self.confirmation_seconds
+            .with_label_values(&[commitment, outcome.as_str()])
+            .observe(elapsed.as_secs_f64());
+        debug!(
+            commitment,
+            outcome = outcome.as_str(),
+            confirmation_ms = elapsed.as_millis() as u64,
+            "Transaction confirmation finished"
+        );
+    }
+
+    /// Text exposition of every registered metric
+    fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        if let Err(e) = TextEncoder::new().encode(&self.registry.gather(), &mut buffer) {
+            error!(error = %e, "Failed to encode metrics");
+        }
+        String::from_utf8_lossy(&buffer).into_owned()
+    }
+}
+
+static METRICS: LazyLock<Metrics> = LazyLock::new(|| Metrics::new().expect("metric definitions are valid"));
+
+/// The process-wide metrics
+pub fn metrics() -> &'static Metrics {
+    &METRICS
+}
+
+/// Serve `GET /metrics` on an already bound listener until the process exits
+pub async fn serve(listener: TcpListener) {
+    let app = Router::new().route(
+        "/metrics",
+        get(|| async { ([(CONTENT_TYPE, TextEncoder::new().format_type().to_string())], metrics().render()) }),
+    );
+    if let Ok(addr) = listener.local_addr() {
+        info!(%addr, "Prometheus metrics listening on /metrics");
+    }
+    if let Err(e) = axum::serve(listener, app).await {
+        error!(error = %e, "Metrics server failed");
+    }
+}
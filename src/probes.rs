@@ -0,0 +1,86 @@
+use axum::extract::State;
+use axum::http::StatusCode;
+use axum::routing::get;
+use axum::Router;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tracing::{error, info, warn};
+
+use crate::blockchain::BlockchainProvider;
+
+/// Longest a readiness check may take; probers treat a slow answer as a failure anyway
+const READINESS_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Clone)]
+struct ProbeState {
+    provider: Arc<dyn BlockchainProvider>,
+    min_payer_balance: u64,
+}
+
+/// Whether the gateway can store right now: the chain answers its health check and the
+/// payer holds at least `min_payer_balance`. Providers that do not report a balance are
+/// judged on health alone. Reasons are safe to return to the prober; raw errors, which may
+/// carry RPC URLs, are only logged.
+pub async fn readiness(provider: &dyn BlockchainProvider, min_payer_balance: u64) -> Result<(), String> {
+    if let Err(e) = provider.check_health().await {
+        warn!(error = %e, "Readiness health check failed");
+        return Err("blockchain RPC is unreachable".to_string());
+    }
+    match provider.payer_balance().await {
+        Ok(Some(balance)) if balance < min_payer_balance => Err(format!(
+            "payer balance {} is below the {} readiness threshold",
+            balance, min_payer_balance
+        )),
+        Ok(_) => Ok(()),
+        Err(e) => {
+            warn!(error = %e, "Readiness balance check failed");
+            Err("payer balance is unavailable".to_string())
+        }
+    }
+}
+
+async fn readyz(State(state): State<ProbeState>) -> (StatusCode, String) {
+    match tokio::time::timeout(READINESS_TIMEOUT, readiness(state.provider.as_ref(), state.min_payer_balance)).await {
+        Ok(Ok(())) => (StatusCode::OK, "ready\n".to_string()),
+        Ok(Err(reason)) => (StatusCode::SERVICE_UNAVAILABLE, format!("not ready: {}\n", reason)),
+        Err(_) => {
+            warn!("Readiness probe timed out");
+            (StatusCode::SERVICE_UNAVAILABLE, "not ready: readiness check timed out\n".to_string())
+        }
+    }
+}
+
+/// Serve `GET /livez` and `GET /readyz` on an already bound listener until the process exits.
+/// Liveness only shows the process is answering; readiness checks the chain and payer on every call.
+pub async fn serve(listener: TcpListener, provider: Arc<dyn BlockchainProvider>, min_payer_balance: u64) {
+    let app = Router::new()
+        .route("/livez", get(|| async { "ok\n" }))
+        .route("/readyz", get(readyz))
+        .with_state(ProbeState {
+            provider,
+            min_payer_balance,
+        });
+    if let Ok(addr) = listener.local_addr() {
+        info!(%addr, min_payer_balance, "HTTP probes listening on /livez and /readyz");
+    }
+    if let Err(e) = axum::serve(listener, app).await {
+        error!(error = %e, "Probe server failed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::mock::MockProvider;
+
+    #[tokio::test]
+    async fn readiness_requires_payer_balance_threshold() {
+        let provider = MockProvider::with_payer_balance(5_000);
+        assert_eq!(readiness(&provider, 5_000).await, Ok(()));
+        assert!(readiness(&provider, 5_001).await.is_err());
+
+        // Providers without a payer balance are judged on health alone
+        assert_eq!(readiness(&MockProvider::new(), u64::MAX).await, Ok(()));
+    }
+}
@@ -0,0 +1,860 @@
+use alloy::{
+    consensus::Transaction as _,
+    contract::Error as ContractError,
+    network::{EthereumWallet, ReceiptResponse, TransactionResponse as _},
+    primitives::{keccak256, Address, TxHash, B256},
+    eips::BlockNumberOrTag,
+    providers::{DynProvider, PendingTransactionError, Provider, ProviderBuilder, WatchTxError},
+    rpc::types::{Filter, Log},
+    signers::{local::PrivateKeySigner, Signer as _},
+    sol,
+    sol_types::{decode_revert_reason, SolCall, SolEvent},
+    transports::{http::reqwest, RpcError, TransportErrorKind},
+};
+use async_trait::async_trait;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+use tokio::sync::{Mutex, OnceCell};
+use tracing::{debug, info, warn};
+
+use crate::blockchain::{
+    redacted_url, AccountBalance, BalanceInfo, BlockchainError, BlockchainProvider, BlockchainResult, ChainConfig, ChainType, ConnectRetry, DecodedProof, Explorer,
+    FeeEstimate, NetworkInfo, StoreOptions, TransactionProofs, TransactionResult, TransactionStatus,
+};
+use crate::metrics::ConfirmationOutcome;
+use crate::proto::ContentRecord;
+
+sol! {
+    /// Proof-storage contract the gateway submits records to
+    #[sol(rpc)]
+    contract ProofStore {
+        function storeProof(string url, string contentHash, uint64 contentLength) external;
+
+        /// Emitted by `storeProof`; `key` is the Keccak-256 hash of the URL
+        event ProofStored(bytes32 indexed key, address indexed sender, string url, string contentHash, uint64 contentLength);
+    }
+}
+
+/// Blocks covered by one `eth_getLogs` query; halved while the node rejects the range, and
+/// doubled back up to this after each page it answers
+const LOG_BLOCK_RANGE: u64 = 10_000;
+
+/// Most `eth_getLogs` queries one lookup makes, refused ones included, before it gives up.
+/// At full width that reaches 5,000,000 blocks back from the latest.
+const MAX_LOG_QUERIES: u32 = 500;
+
+/// Node error messages meaning an `eth_getLogs` query spans too many blocks or results. Only
+/// range-specific wording: rate limits and quotas ("limit exceeded", "too many requests") are
+/// passed up as unavailable, since a narrower window would only slow the scan down.
+const LOG_RANGE_ERRORS: &[&str] = &[
+    "block range",
+    "range is too large",
+    "returned more than",
+    "response size exceeded",
+    "eth_getlogs is limited to",
+];
+
+/// Times a store is resubmitted with a freshly fetched nonce after the node rejects its nonce
+const NONCE_RETRIES: u32 = 3;
+
+/// Node error messages meaning the nonce we sent with is already taken
+const NONCE_ERRORS: &[&str] = &["nonce too low", "nonce has already been used", "replacement transaction underpriced"];
+
+/// Block windows an `eth_getLogs` scan pages back through, newest first, from the latest
+/// block down to `floor`. Windows are contiguous and never overlap.
+#[derive(Debug)]
+struct LogWindows {
+    to_block: u64,
+    floor: u64,
+    range: u64,
+}
+
+impl LogWindows {
+    /// `None` when `latest` is below `floor`, leaving nothing to scan
+    fn new(latest: u64, floor: u64) -> Option<Self> {
+        (latest >= floor).then_some(Self {
+            to_block: latest,
+            floor,
+            range: LOG_BLOCK_RANGE,
+        })
+    }
+
+    /// First and last block of the current window
+    fn current(&self) -> (u64, u64) {
+        let from_block = self.to_block.saturating_sub(self.range - 1).max(self.floor);
+        (from_block, self.to_block)
+    }
+
+    /// Halve the window after the node refused it; false once it is a single block
+    fn narrow(&mut self) -> bool {
+        if self.range == 1 {
+            return false;
+        }
+        self.range /= 2;
+        true
+    }
+
+    /// Double the window again, up to `LOG_BLOCK_RANGE`, after the node answered one
+    fn widen(&mut self) {
+        self.range = (self.range * 2).min(LOG_BLOCK_RANGE);
+    }
+
+    /// Move to the window just before the current one; false once `floor` is covered
+    fn advance(&mut self) -> bool {
+        let (from_block, _) = self.current();
+        if from_block <= self.floor {
+            return false;
+        }
+        self.to_block = from_block - 1;
+        true
+    }
+}
+
+/// EIP-1559 fees a transaction is sent with, in wei
+#[derive(Debug, Clone, Copy)]
+struct Eip1559Fees {
+    max_fee_per_gas: u128,
+    max_priority_fee_per_gas: u128,
+}
+
+pub struct EthereumProvider {
+    provider: DynProvider,
+    /// `Ethereum`, or the EVM network whose chain ID the node must report
+    chain_type: ChainType,
+    /// Set once the node has reported the configured chain ID; stores wait for it
+    chain_id_checked: OnceCell<()>,
+    contract_address: Address,
+    sender: Address,
+    network_url: String,
+    connect_retry: ConnectRetry,
+    dry_run: bool,
+    max_fee_per_gas: Option<u64>,
+    max_priority_fee_per_gas: Option<u64>,
+    confirmations: u64,
+    confirmation_timeout: Duration,
+    logs_from_block: u64,
+    explorer: Option<Explorer>,
+    /// Nonce for the next submission; `None` until fetched and after a failed send.
+    /// Held across each send so concurrent stores never share a nonce.
+    next_nonce: Mutex<Option<u64>>,
+}
+
+impl EthereumProvider {
+    pub fn new(config: ChainConfig) -> BlockchainResult<Self> {
+        config.validate()?;
+        let rpc_url = config
+            .network_url
+            .parse()
+            .map_err(|e| BlockchainError::InvalidConfig(format!("Invalid Ethereum RPC URL {:?}: {}", config.network_url, e)))?;
+        if !config.fallback_urls.is_empty() {
+            warn!("Fallback RPC URLs are only used on Solana; the Ethereum provider uses the primary URL alone");
+        }
+
+        let contract_address = Address::from_str(&config.program_id)
+            .map_err(|e| BlockchainError::InvalidConfig(format!("Invalid contract address {:?}: {}", config.program_id, e)))?;
+        info!(contract_address = %contract_address, "Using proof contract");
+
+        let key_path = config.private_key_path.as_deref().ok_or_else(|| {
+            BlockchainError::InvalidConfig("A private key file is required for the Ethereum provider".to_string())
+        })?;
+        debug!(path = %key_path, "Loading sender key");
+        let mut signer = Self::load_signer(key_path)?;
+        // Signing refuses any other chain, whatever the node reports
+        if let ChainType::Evm { chain_id } = config.chain_type {
+            signer.set_chain_id(Some(chain_id));
+        }
+        let sender = signer.address();
+        info!(sender = %sender, "Loaded sender key");
+
+        let http = reqwest::Client::builder()
+            .timeout(config.rpc_timeout)
+            .build()
+            .map_err(|e| BlockchainError::InvalidConfig(format!("Failed to build Ethereum HTTP client: {}", e)))?;
+        let provider = ProviderBuilder::new()
+            .wallet(EthereumWallet::from(signer))
+            .connect_reqwest(http, rpc_url)
+            .erased();
+
+        let explorer = Explorer::from_config(&config);
+        Ok(Self {
+            provider,
+            chain_type: config.chain_type.clone(),
+            chain_id_checked: OnceCell::new(),
+            contract_address,
+            sender,
+            network_url: config.network_url,
+            connect_retry: config.connect_retry,
+            dry_run: config.dry_run,
+            max_fee_per_gas: config.max_fee_per_gas,
+            max_priority_fee_per_gas: config.max_priority_fee_per_gas,
+            confirmations: config.confirmations,
+            confirmation_timeout: config.confirmation_timeout,
+            // Required on EVM chains by `validate`
+            logs_from_block: config.logs_from_block.unwrap_or_default(),
+            explorer,
+            next_nonce: Mutex::new(None),
+        })
+    }
+
+    fn load_signer(key_path: &str) -> BlockchainResult<PrivateKeySigner> {
+        let path = Path::new(key_path);
+        if !path.exists() {
+            return Err(BlockchainError::InvalidConfig(format!("Private key file not found at: {}", key_path)));
+        }
+
+        let key_hex = std::fs::read_to_string(path)
+            .map_err(|e| BlockchainError::InvalidConfig(format!("Failed to read private key file: {}", e)))?;
+
+        let signer = PrivateKeySigner::from_str(key_hex.trim())
+            .map_err(|e| BlockchainError::InvalidConfig(format!("Failed to parse private key: {}", e)))?;
+
+        Ok(signer)
+    }
+
+    /// Classify a contract call failure, preferring the decoded revert reason over the raw RPC error
+    fn classify_error(context: &str, e: ContractError) -> BlockchainError {
+        if let Some(reason) = e.as_revert_data().and_then(|data| decode_revert_reason(&data)) {
+            return BlockchainError::Other(format!("{}: execution reverted: {}", context, reason));
+        }
+
+        match &e {
+            ContractError::TransportError(RpcError::Transport(_)) => {
+                BlockchainError::RpcUnavailable(format!("{}: {}", context, e))
+            }
+            ContractError::TransportError(rpc_error)
+                if rpc_error
+                    .as_error_resp()
+                    .is_some_and(|resp| resp.message.contains("insufficient funds")) =>
+            {
+                BlockchainError::InsufficientFunds(format!("{}: {}", context, e))
+            }
+            _ => BlockchainError::Other(format!("{}: {}", context, e)),
+        }
+    }
+
+    /// Fail unless the node is on the configured EVM network, so a wrong RPC URL never
+    /// receives a store. Checked once it passes; plain `Ethereum` accepts any chain.
+    async fn check_chain_id(&self) -> BlockchainResult<()> {
+        let ChainType::Evm { chain_id } = self.chain_type else {
+            return Ok(());
+        };
+        self.chain_id_checked
+            .get_or_try_init(|| async {
+                let reported = self
+                    .provider
+                    .get_chain_id()
+                    .await
+                    .map_err(|e| BlockchainError::RpcUnavailable(format!("Failed to query chain ID: {}", e)))?;
+                if reported != chain_id {
+                    return Err(BlockchainError::InvalidConfig(format!(
+                        "CHAIN_TYPE {} expects chain ID {} but {} reports {}",
+                        self.chain_type,
+                        chain_id,
+                        redacted_url(&self.network_url),
+                        reported
+                    )));
+                }
+                Ok(())
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Whether the node rejected a transaction because its nonce is already used
+    fn is_nonce_error(e: &ContractError) -> bool {
+        let ContractError::TransportError(rpc_error) = e else {
+            return false;
+        };
+        rpc_error.as_error_resp().is_some_and(|resp| {
+            let message = resp.message.to_lowercase();
+            NONCE_ERRORS.iter().any(|pattern| message.contains(pattern))
+        })
+    }
+
+    /// Whether the node refused an `eth_getLogs` query for covering too much
+    fn is_log_range_error(e: &RpcError<TransportErrorKind>) -> bool {
+        e.as_error_resp().is_some_and(|resp| {
+            let message = resp.message.to_lowercase();
+            LOG_RANGE_ERRORS.iter().any(|pattern| message.contains(pattern))
+        })
+    }
+
+    /// Newest `ProofStored` event under `key`, paging back from the latest block to
+    /// `logs_from_block` and stopping at the first page that has one, or after `MAX_LOG_QUERIES`
+    async fn latest_proof_log(&self, key: B256) -> BlockchainResult<Log<ProofStore::ProofStored>> {
+        let rpc_error = |e| BlockchainError::RpcUnavailable(format!("Failed to fetch proof logs: {}", e));
+        let latest = self.provider.get_block_number().await.map_err(rpc_error)?;
+        let not_found = || BlockchainError::AccountNotFound(format!("No proof under key {} since block {}", key, self.logs_from_block));
+        let Some(mut windows) = LogWindows::new(latest, self.logs_from_block) else {
+            return Err(not_found());
+        };
+        for _ in 0..MAX_LOG_QUERIES {
+            let (from_block, to_block) = windows.current();
+            let filter = Filter::new()
+                .address(self.contract_address)
+                .event_signature(ProofStore::ProofStored::SIGNATURE_HASH)
+                .topic1(key)
+                .from_block(from_block)
+                .to_block(to_block);
+            let logs = match self.provider.get_logs(&filter).await {
+                Ok(logs) => logs,
+                Err(e) if Self::is_log_range_error(&e) && windows.narrow() => {
+                    debug!(range = windows.range, error = %e, "Node refused the log query, narrowing the block range");
+                    continue;
+                }
+                Err(e) => return Err(rpc_error(e)),
+            };
+            // A URL stored more than once reads back as its newest proof
+            let newest = logs
+                .into_iter()
+                .filter(|log| !log.removed)
+                .max_by_key(|log| (log.block_number, log.log_index));
+            if let Some(log) = newest {
+                return log.log_decode::<ProofStore::ProofStored>().map_err(|e| {
+                    BlockchainError::NotAProof(format!("ProofStored log under key {} does not decode: {}", key, e))
+                });
+            }
+            if !windows.advance() {
+                return Err(not_found());
+            }
+            windows.widen();
+        }
+        // Everything above the current window has been searched
+        let searched_to = windows.to_block + 1;
+        warn!(
+            %key,
+            queries = MAX_LOG_QUERIES,
+            searched_to,
+            logs_from_block = self.logs_from_block,
+            "Gave up searching proof logs before ETHEREUM_LOGS_FROM_BLOCK"
+        );
+        Err(BlockchainError::AccountNotFound(format!(
+            "No proof under key {} in blocks {} to {}; the search stopped after {} log queries",
+            key, searched_to, latest, MAX_LOG_QUERIES
+        )))
+    }
+
+    /// Fees to send with: configured values where set, the node's EIP-1559 estimate otherwise.
+    /// A configured priority fee keeps the estimated base fee headroom on top of it.
+    async fn fees(&self) -> BlockchainResult<Eip1559Fees> {
+        if let (Some(max_fee), Some(priority_fee)) = (self.max_fee_per_gas, self.max_priority_fee_per_gas) {
+            return Ok(Eip1559Fees {
+                max_fee_per_gas: max_fee.into(),
+                max_priority_fee_per_gas: priority_fee.into(),
+            });
+        }
+        let estimate = self
+            .provider
+            .estimate_eip1559_fees()
+            .await
+            .map_err(|e| BlockchainError::RpcUnavailable(format!("Failed to estimate EIP-1559 fees: {}", e)))?;
+        let fees = match (self.max_fee_per_gas, self.max_priority_fee_per_gas) {
+            (Some(max_fee), _) => Eip1559Fees {
+                max_fee_per_gas: max_fee.into(),
+                max_priority_fee_per_gas: estimate.max_priority_fee_per_gas.min(max_fee.into()),
+            },
+            (None, Some(priority_fee)) => Eip1559Fees {
+                max_fee_per_gas: estimate
+                    .max_fee_per_gas
+                    .saturating_sub(estimate.max_priority_fee_per_gas)
+                    .saturating_add(priority_fee.into()),
+                max_priority_fee_per_gas: priority_fee.into(),
+            },
+            (None, None) => Eip1559Fees {
+                max_fee_per_gas: estimate.max_fee_per_gas,
+                max_priority_fee_per_gas: estimate.max_priority_fee_per_gas,
+            },
+        };
+        debug!(
+            max_fee_per_gas = fees.max_fee_per_gas as u64,
+            max_priority_fee_per_gas = fees.max_priority_fee_per_gas as u64,
+            "Using EIP-1559 fees"
+        );
+        Ok(fees)
+    }
+
+    /// Nonce of the sender's next transaction, counting ones still in the mempool
+    async fn pending_nonce(&self) -> BlockchainResult<u64> {
+        self.provider
+            .get_transaction_count(self.sender)
+            .pending()
+            .await
+            .map_err(|e| BlockchainError::RpcUnavailable(format!("Failed to fetch sender nonce: {}", e)))
+    }
+
+    async fn store_record_impl(&self, record: &ContentRecord, options: &StoreOptions) -> BlockchainResult<TransactionResult> {
+        // Refused rather than dropped, so a client never believes they were proven
+        if !record.content_type.is_empty() || !record.storage_uri.is_empty() {
+            return Err(BlockchainError::InvalidRecord(
+                "content_type and storage_uri cannot be stored on Ethereum; the proof contract has no fields for them".to_string(),
+            ));
+        }
+        self.check_chain_id().await?;
+        let contract = ProofStore::new(self.contract_address, &self.provider);
+        let mut call = contract.storeProof(
+            record.url.clone(),
+            record.stored_content_hash(),
+            record.content_length,
+        );
+        if let ChainType::Evm { chain_id } = self.chain_type {
+            call = call.chain_id(chain_id);
+        }
+
+        // Estimation runs the call, so reverts surface here before we pay for them
+        let gas = call
+            .estimate_gas()
+            .await
+            .map_err(|e| Self::classify_error("Gas estimation failed", e))?;
+
+        if options.dry_run || self.dry_run {
+            info!(gas, uid = %record.uid, "Dry run estimated, nothing submitted");
+            return Ok(TransactionResult {
+                transaction_id: String::new(),
+                account_address: None,
+                block_height: None,
+                block_time: None,
+                confirmation_time: None,
+                finalization_time: None,
+                // eth_estimateGas executes the call but reports no logs
+                simulation_logs: Some(Vec::new()),
+                explorer_url: None,
+            });
+        }
+
+        let fees = self.fees().await?;
+        let call = call
+            .gas(gas)
+            .max_fee_per_gas(fees.max_fee_per_gas)
+            .max_priority_fee_per_gas(fees.max_priority_fee_per_gas);
+
+        let mut next_nonce = self.next_nonce.lock().await;
+        let mut nonce_retries = 0;
+        let pending = loop {
+            let nonce = match *next_nonce {
+                Some(nonce) => nonce,
+                None => self.pending_nonce().await?,
+            };
+            match call.clone().nonce(nonce).send().await {
+                Ok(pending) => {
+                    *next_nonce = Some(nonce + 1);
+                    break pending;
+                }
+                Err(e) if nonce_retries < NONCE_RETRIES && Self::is_nonce_error(&e) => {
+                    nonce_retries += 1;
+                    warn!(nonce, retry = nonce_retries, error = %e, "Nonce already used, refetching it");
+                    *next_nonce = None;
+                }
+                Err(e) => {
+                    // The transaction may or may not have reached the node; ask again next time
+                    *next_nonce = None;
+                    return Err(Self::classify_error("Failed to submit transaction", e));
+                }
+            }
+        };
+        drop(next_nonce);
+        let submitted_at = Instant::now();
+        let tx_hash = *pending.tx_hash();
+        debug!(tx_hash = %tx_hash, "Submitted Ethereum transaction");
+
+        if options.fire_and_forget {
+            info!(tx_hash = %tx_hash, uid = %record.uid, "Ethereum transaction submitted without waiting for a receipt");
+            return Ok(TransactionResult {
+                transaction_id: tx_hash.to_string(),
+                account_address: Some(proof_key(&record.url).to_string()),
+                block_height: None,
+                block_time: None,
+                confirmation_time: None,
+                finalization_time: None,
+                simulation_logs: None,
+                explorer_url: self.explorer.as_ref().map(|explorer| explorer.transaction_url(&tx_hash.to_string())),
+            });
+        }
+
+        let commitment = format!("{}_confirmations", self.confirmations);
+        let record_confirmation = |outcome| {
+            crate::metrics::metrics().record_confirmation(&commitment, outcome, submitted_at.elapsed());
+        };
+        let receipt = pending
+            .with_required_confirmations(self.confirmations)
+            .with_timeout(Some(self.confirmation_timeout))
+            .get_receipt()
+            .await
+            .map_err(|e| match e {
+                PendingTransactionError::TxWatcher(WatchTxError::Timeout) => {
+                    warn!(tx_hash = %tx_hash, timeout_secs = self.confirmation_timeout.as_secs(), "Gave up waiting for a receipt");
+                    record_confirmation(ConfirmationOutcome::TimedOut);
+                    BlockchainError::ConfirmationPending {
+                        transaction_id: tx_hash.to_string(),
+                        timeout: self.confirmation_timeout,
+                    }
+                }
+                e => BlockchainError::Other(format!("Failed to confirm transaction {}: {}", tx_hash, e)),
+            })?;
+        let confirmation_time = submitted_at.elapsed();
+
+        if !receipt.status() {
+            record_confirmation(ConfirmationOutcome::Failed);
+            return Err(BlockchainError::Other(format!(
+                "Transaction {} reverted in block {:?}",
+                tx_hash,
+                receipt.block_number()
+            )));
+        }
+
+        record_confirmation(ConfirmationOutcome::Confirmed);
+        info!(
+            tx_hash = %tx_hash,
+            block_number = ?receipt.block_number(),
+            confirmations = self.confirmations,
+            uid = %record.uid,
+            confirmation_ms = confirmation_time.as_millis() as u64,
+            "Ethereum transaction confirmed"
+        );
+
+        Ok(TransactionResult {
+            transaction_id: tx_hash.to_string(),
+            // Proofs have no account of their own; their key finds them in the contract's logs
+            account_address: Some(proof_key(&record.url).to_string()),
+            block_height: receipt.block_number(),
+            block_time: None,
+            confirmation_time: Some(confirmation_time),
+            finalization_time: None,
+            simulation_logs: None,
+            explorer_url: self.explorer.as_ref().map(|explorer| explorer.transaction_url(&tx_hash.to_string())),
+        })
+    }
+}
+
+#[async_trait]
+impl BlockchainProvider for EthereumProvider {
+    fn chain_type(&self) -> ChainType {
+        self.chain_type.clone()
+    }
+
+    async fn initialize(&self) -> BlockchainResult<()> {
+        info!(url = %redacted_url(&self.network_url), chain = %self.chain_type, "Connecting to Ethereum node");
+        self.connect_retry.connect(&self.network_url, || self.check_health()).await?;
+        self.check_chain_id().await?;
+        info!("Connected to Ethereum node");
+        Ok(())
+    }
+
+    async fn check_health(&self) -> BlockchainResult<()> {
+        self.provider
+            .get_block_number()
+            .await
+            .map_err(|e| BlockchainError::RpcUnavailable(e.to_string()))?;
+        Ok(())
+    }
+
+    async fn get_network_info(&self) -> BlockchainResult<NetworkInfo> {
+        let rpc_error = |e| BlockchainError::RpcUnavailable(format!("Failed to query network info: {}", e));
+        let chain_id = self.provider.get_chain_id().await.map_err(rpc_error)?;
+        let latest_block = self.provider.get_block_number().await.map_err(rpc_error)?;
+        let node_version = self.provider.get_client_version().await.map_err(rpc_error)?;
+        let genesis = self
+            .provider
+            .get_block_by_number(BlockNumberOrTag::Number(0))
+            .await
+            .map_err(rpc_error)?
+            .ok_or_else(|| BlockchainError::Other("Node did not return the genesis block".to_string()))?;
+
+        Ok(NetworkInfo {
+            chain_type: self.chain_type.clone(),
+            network_url: redacted_url(&self.network_url),
+            genesis_hash: genesis.header.hash.to_string(),
+            latest_block,
+            node_version,
+            chain_id: Some(chain_id),
+        })
+    }
+
+    async fn estimate_fee(&self, record: &ContentRecord) -> BlockchainResult<FeeEstimate> {
+        let contract = ProofStore::new(self.contract_address, &self.provider);
+        let gas_limit = contract
+            .storeProof(record.url.clone(), record.stored_content_hash(), record.content_length)
+            .estimate_gas()
+            .await
+            .map_err(|e| Self::classify_error("Gas estimation failed", e))?;
+        let gas_price = self
+            .provider
+            .get_gas_price()
+            .await
+            .map_err(|e| BlockchainError::RpcUnavailable(format!("Failed to fetch gas price: {}", e)))?;
+        let gas_price = u64::try_from(gas_price).unwrap_or(u64::MAX);
+
+        Ok(FeeEstimate {
+            // Contract storage is paid for in gas
+            storage_fee: 0,
+            transaction_fee: gas_limit.saturating_mul(gas_price),
+            unit: "wei",
+            gas_limit: Some(gas_limit),
+            gas_price: Some(gas_price),
+        })
+    }
+
+    async fn get_balance(&self) -> BlockchainResult<BalanceInfo> {
+        let balance = self
+            .provider
+            .get_balance(self.sender)
+            .await
+            .map_err(|e| BlockchainError::RpcUnavailable(format!("Failed to fetch sender balance: {}", e)))?;
+        Ok(BalanceInfo {
+            accounts: vec![AccountBalance {
+                address: self.sender.to_string(),
+                balance: u128::try_from(balance).unwrap_or(u128::MAX),
+            }],
+            unit: "wei",
+            decimals: 18,
+        })
+    }
+
+    /// Proofs carry no submitter here, so the sender alone decides
+    fn is_own_submission(&self, proof: &DecodedProof) -> bool {
+        proof.payer.as_deref() == Some(self.sender.to_string().as_str())
+    }
+
+    /// Sender balance in wei, saturating at `u64::MAX` (about 18.4 ETH)
+    async fn payer_balance(&self) -> BlockchainResult<Option<u64>> {
+        let balance = self.get_balance().await?;
+        let sender = balance.accounts.first().map(|account| account.balance).unwrap_or(0);
+        Ok(Some(u64::try_from(sender).unwrap_or(u64::MAX)))
+    }
+
+    /// Ethereum has no commitment levels; stores wait for the receipt at the configured confirmation depth
+    async fn store_record(&self, record: &ContentRecord, options: &StoreOptions) -> BlockchainResult<TransactionResult> {
+        self.store_record_impl(record, options).await
+    }
+
+    /// A receipt buried under the configured confirmation depth means the transaction is confirmed;
+    /// one known to the node that is not yet that deep is still pending
+    async fn transaction_statuses(&self, transaction_ids: &[String]) -> BlockchainResult<Vec<TransactionStatus>> {
+        let latest_block = self
+            .provider
+            .get_block_number()
+            .await
+            .map_err(|e| BlockchainError::RpcUnavailable(format!("Failed to fetch the latest block: {}", e)))?;
+        let mut statuses = Vec::with_capacity(transaction_ids.len());
+        for id in transaction_ids {
+            let tx_hash = TxHash::from_str(id)
+                .map_err(|e| BlockchainError::InvalidAddress(format!("Invalid transaction hash {:?}: {}", id, e)))?;
+            let receipt = self
+                .provider
+                .get_transaction_receipt(tx_hash)
+                .await
+                .map_err(|e| BlockchainError::RpcUnavailable(format!("Failed to fetch receipt for {}: {}", tx_hash, e)))?;
+
+            let status = match receipt {
+                Some(receipt)
+                    if receipt
+                        .block_number()
+                        .is_some_and(|block| latest_block.saturating_sub(block) + 1 < self.confirmations) =>
+                {
+                    TransactionStatus::Pending
+                }
+                Some(receipt) if receipt.status() => TransactionStatus::Confirmed {
+                    block_height: receipt.block_number(),
+                },
+                Some(receipt) => {
+                    TransactionStatus::Failed(format!("Transaction reverted in block {:?}", receipt.block_number()))
+                }
+                None => {
+                    let transaction = self.provider.get_transaction_by_hash(tx_hash).await.map_err(|e| {
+                        BlockchainError::RpcUnavailable(format!("Failed to fetch transaction {}: {}", tx_hash, e))
+                    })?;
+                    if transaction.is_some() {
+                        TransactionStatus::Pending
+                    } else {
+                        TransactionStatus::Unknown
+                    }
+                }
+            };
+            statuses.push(status);
+        }
+        Ok(statuses)
+    }
+
+    /// Decode the `storeProof` call of a mined transaction sent to the configured contract
+    async fn get_proof_by_transaction(&self, transaction_id: &str) -> BlockchainResult<TransactionProofs> {
+        let tx_hash = TxHash::from_str(transaction_id).map_err(|e| {
+            BlockchainError::InvalidAddress(format!("Invalid transaction hash {:?}: {}", transaction_id, e))
+        })?;
+        let rpc_error = |e| BlockchainError::RpcUnavailable(format!("Failed to fetch transaction {}: {}", tx_hash, e));
+
+        let transaction = self
+            .provider
+            .get_transaction_by_hash(tx_hash)
+            .await
+            .map_err(rpc_error)?
+            .ok_or_else(|| BlockchainError::TransactionNotFound(tx_hash.to_string()))?;
+        if transaction.to() != Some(self.contract_address) {
+            return Err(BlockchainError::NotAProof(format!(
+                "Transaction {} was not sent to contract {}",
+                tx_hash, self.contract_address
+            )));
+        }
+        let call = ProofStore::storeProofCall::abi_decode(transaction.input()).map_err(|e| {
+            BlockchainError::NotAProof(format!("Transaction {} is not a storeProof call: {}", tx_hash, e))
+        })?;
+
+        let receipt = self
+            .provider
+            .get_transaction_receipt(tx_hash)
+            .await
+            .map_err(rpc_error)?
+            .ok_or_else(|| BlockchainError::NotAProof(format!("Transaction {} is not mined yet", tx_hash)))?;
+        if !receipt.status() {
+            return Err(BlockchainError::NotAProof(format!("Transaction {} reverted", tx_hash)));
+        }
+
+        let block_height = receipt.block_number();
+        let block_time = match block_height {
+            Some(number) => match self.provider.get_block_by_number(BlockNumberOrTag::Number(number)).await {
+                Ok(block) => block.map(|block| block.header.timestamp as i64),
+                Err(e) => {
+                    warn!(block = number, error = %e, "Failed to fetch block time");
+                    None
+                }
+            },
+            None => None,
+        };
+
+        let mut record = ContentRecord {
+            url: call.url,
+            content_length: call.contentLength,
+            ..Default::default()
+        };
+        record.set_stored_content_hash(call.contentHash);
+        Ok(TransactionProofs {
+            proofs: vec![DecodedProof {
+                record,
+                account_address: None,
+                payer: Some(transaction.from().to_string()),
+            }],
+            block_height,
+            block_time,
+        })
+    }
+
+    /// Read back the newest proof under `account_address`, the proof key stores answer with,
+    /// from the contract's `ProofStored` logs
+    async fn retrieve_record(&self, account_address: &str) -> BlockchainResult<ContentRecord> {
+        let key = B256::from_str(account_address)
+            .map_err(|e| BlockchainError::InvalidAddress(format!("Invalid proof key {:?}: {}", account_address, e)))?;
+        let event = self.latest_proof_log(key).await?.inner.data;
+        let mut record = ContentRecord {
+            url: event.url,
+            content_length: event.contentLength,
+            ..Default::default()
+        };
+        record.set_stored_content_hash(event.contentHash);
+        Ok(record)
+    }
+}
+
+/// Key a proof is logged under: the Keccak-256 hash of its URL, as the contract computes it
+fn proof_key(url: &str) -> B256 {
+    keccak256(url.as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Error response as a node sends it
+    fn node_error(code: i64, message: &str) -> RpcError<TransportErrorKind> {
+        RpcError::ErrorResp(serde_json::from_value(serde_json::json!({ "code": code, "message": message })).unwrap())
+    }
+
+    #[test]
+    fn log_range_errors_of_common_providers_are_recognized() {
+        for (code, message) in [
+            // Alchemy
+            (-32602, "Log response size exceeded. You can make eth_getLogs requests with up to a 2K block range and no limit on the response size, or you can request any block range with a cap of 10K logs in the response."),
+            // Infura
+            (-32005, "query returned more than 10000 results"),
+            // QuickNode
+            (-32614, "eth_getLogs is limited to a 10,000 range"),
+            (-32600, "eth_getLogs range is too large, max is 1k blocks"),
+            (-32000, "exceed maximum block range: 50000"),
+        ] {
+            assert!(EthereumProvider::is_log_range_error(&node_error(code, message)), "missed {:?}", message);
+        }
+        for (code, message) in [
+            (3, "execution reverted"),
+            (-32000, "header not found"),
+            (-32000, "missing trie node"),
+            // Rate limits and quotas, which a narrower window does nothing for
+            (-32005, "limit exceeded"),
+            (-32005, "daily request count exceeded, request rate limited"),
+            (429, "Too many requests, please slow down"),
+            (429, "Your app has exceeded its compute units per second capacity. If you have retries enabled, you can safely ignore this message. If not, check out https://docs.alchemy.com/reference/throughput"),
+            (-32007, "100/second request limit reached - reduce calls per second or upgrade your account at quicknode.com"),
+        ] {
+            assert!(!EthereumProvider::is_log_range_error(&node_error(code, message)), "matched {:?}", message);
+        }
+        assert!(!EthereumProvider::is_log_range_error(&RpcError::Transport(TransportErrorKind::BackendGone)));
+    }
+
+    #[test]
+    fn log_windows_cover_every_block_down_to_the_floor_once() {
+        let mut windows = LogWindows::new(25_000, 0).unwrap();
+        let mut covered = vec![windows.current()];
+        while windows.advance() {
+            covered.push(windows.current());
+        }
+        assert_eq!(covered, [(15_001, 25_000), (5_001, 15_000), (0, 5_000)]);
+
+        // A floor above block 0 ends the scan there, however the range divides
+        let mut windows = LogWindows::new(25_000, 12_345).unwrap();
+        assert_eq!(windows.current(), (15_001, 25_000));
+        assert!(windows.advance());
+        assert_eq!(windows.current(), (12_345, 15_000));
+        assert!(!windows.advance());
+
+        assert!(LogWindows::new(99, 100).is_none());
+        let windows = LogWindows::new(0, 0).unwrap();
+        assert_eq!(windows.current(), (0, 0));
+    }
+
+    #[test]
+    fn narrowing_halves_the_window_down_to_one_block() {
+        let mut windows = LogWindows::new(1_000_000, 0).unwrap();
+        let mut narrowed = 0;
+        while windows.narrow() {
+            narrowed += 1;
+        }
+        assert_eq!(narrowed, 13);
+        assert_eq!(windows.current(), (1_000_000, 1_000_000));
+
+        // Single-block windows still step back without gaps
+        assert!(windows.advance());
+        assert_eq!(windows.current(), (999_999, 999_999));
+    }
+
+    #[test]
+    fn widening_recovers_the_full_window_without_gaps() {
+        let mut windows = LogWindows::new(1_000_000, 0).unwrap();
+        for _ in 0..3 {
+            windows.narrow();
+        }
+        assert_eq!(windows.current(), (998_751, 1_000_000));
+        let mut covered = vec![windows.current()];
+        for _ in 0..4 {
+            assert!(windows.advance());
+            windows.widen();
+            covered.push(windows.current());
+        }
+        assert_eq!(
+            covered,
+            [
+                (998_751, 1_000_000),
+                (996_251, 998_750),
+                (991_251, 996_250),
+                (981_251, 991_250),
+                (971_251, 981_250),
+            ]
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use async_trait::async_trait;
+use solana_client::client_error::{ClientError, ClientErrorKind, Result as ClientResult};
+use solana_client::rpc_custom_error::JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY;
+use solana_client::rpc_request::{RpcError, RpcRequest};
+use solana_client::rpc_sender::{RpcSender, RpcTransportStats};
+use reqwest::header::RETRY_AFTER;
+use reqwest::StatusCode;
+use reqwest_middleware::{Middleware, Next};
+use solana_rpc_client::http_sender::HttpSender;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+use tonic::codegen::http::Extensions;
+use tracing::{info, warn};
+
+use super::retry_budget::RetryBudget;
+use crate::blockchain::redacted_url;
+
+/// How long an endpoint that failed is passed over before it is tried again
+const UNHEALTHY_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// How long an idle connection is kept open for the next request. The SDK closes them after
+/// the request timeout, so a short timeout would have every burst reconnect and redo TLS.
+const POOL_IDLE_TIMEOUT: Duration = Duration::from_secs(90);
+
+/// Idle connections kept per endpoint on top of one per in-flight store, for the blockhash
+/// refresh, confirmation tracking and health checks running alongside stores
+const BACKGROUND_CONNECTIONS: usize = 4;
+
+/// Wait before retrying a rate-limited request that carries no `Retry-After`, as the SDK does
+const DEFAULT_RATE_LIMIT_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Longest `Retry-After` honored; a node asking for more is treated as unavailable for now
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(30);
+
+/// Retries of one request the node keeps rate limiting, before it moves on to the next endpoint
+const MAX_RATE_LIMIT_RETRIES: u32 = 5;
+
+/// A request that was rate limited and not retried
+#[derive(Debug, thiserror::Error)]
+#[error("RPC endpoint is rate limiting requests: {0}")]
+struct RateLimited(String);
+
+/// Retries HTTP 429 answers after the node's `Retry-After`, drawing every retry from the
+/// budget shared with the provider's send retries. Rate-limited answers never reach the SDK's
+/// `HttpSender`, which would otherwise retry them on its own, outside the budget.
+struct RateLimitRetry {
+    budget: Arc<RetryBudget>,
+}
+
+#[async_trait]
+impl Middleware for RateLimitRetry {
+    async fn handle(
+        &self,
+        request: reqwest::Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> reqwest_middleware::Result<reqwest::Response> {
+        let mut request = request;
+        let mut retries = 0;
+        loop {
+            // JSON-RPC bodies are in memory, so the request can always be cloned
+            let retry = request.try_clone();
+            let response = next.clone().run(request, extensions).await?;
+            if response.status() != StatusCode::TOO_MANY_REQUESTS {
+                return Ok(response);
+            }
+
+            let wait = retry_after(response.headers(), SystemTime::now()).unwrap_or(DEFAULT_RATE_LIMIT_BACKOFF);
+            let refusal = if wait > MAX_RETRY_AFTER {
+                Some(format!("asked to retry after {}s", wait.as_secs()))
+            } else if retries >= MAX_RATE_LIMIT_RETRIES {
+                Some(format!("still limited after {} retries", retries))
+            } else if !self.budget.try_acquire() {
+                Some("retry budget exhausted".to_string())
+            } else {
+                None
+            };
+            match (refusal, retry) {
+                (None, Some(next_request)) => {
+                    warn!(wait_ms = wait.as_millis() as u64, retries, "RPC endpoint is rate limiting, retrying");
+                    tokio::time::sleep(wait).await;
+                    request = next_request;
+                    retries += 1;
+                }
+                (refusal, _) => {
+                    let reason = refusal.unwrap_or_else(|| "request cannot be resent".to_string());
+                    warn!(url = %redacted_url(response.url().as_str()), %reason, "Rate-limited RPC request not retried");
+                    return Err(reqwest_middleware::Error::middleware(RateLimited(reason)));
+                }
+            }
+        }
+    }
+}
+
+/// Delay a `Retry-After` header asks for, given in seconds or as an HTTP date
+fn retry_after(headers: &reqwest::header::HeaderMap, now: SystemTime) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let at = httpdate::parse_http_date(value).ok()?;
+    Some(at.duration_since(now).unwrap_or(Duration::ZERO))
+}
+
+struct Endpoint {
+    url: String,
+    sender: HttpSender,
+    /// Set while the endpoint is cooling down after a failure
+    unhealthy_until: Mutex<Option<Instant>>,
+}
+
+impl Endpoint {
+    fn is_healthy(&self, now: Instant) -> bool {
+        self.unhealthy_until
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .is_none_or(|until| now >= until)
+    }
+
+    fn mark_healthy(&self) {
+        let mut unhealthy_until = self.unhealthy_until.lock().unwrap_or_else(|e| e.into_inner());
+        if unhealthy_until.take().is_some() {
+            info!(url = %redacted_url(&self.url), "RPC endpoint recovered");
+        }
+    }
+
+    fn mark_unhealthy(&self) {
+        *self.unhealthy_until.lock().unwrap_or_else(|e| e.into_inner()) = Some(Instant::now() + UNHEALTHY_COOLDOWN);
+    }
+}
+
+/// RPC transport over several endpoints. Each request goes to the first healthy endpoint
+/// in configured order, so the primary is preferred whenever it is up. Transport failures
+/// and unhealthy-node errors move on to the next one; other errors are the node's answer
+/// and are returned as is.
+///
+/// Every endpoint has its own async HTTP client whose pool opens a connection per concurrent
+/// request, so concurrent stores never queue behind one another for a connection.
+pub struct FailoverSender {
+    endpoints: Vec<Endpoint>,
+}
+
+impl FailoverSender {
+    /// `urls` must not be empty; the first one is the primary. Each request to an endpoint
+    /// fails after `timeout`, moving on to the next. Up to `max_in_flight` connections per
+    /// endpoint, plus a few for background work, are kept open between requests. Rate-limited
+    /// requests are retried from `budget`.
+    pub fn new(urls: &[String], timeout: Duration, max_in_flight: usize, budget: Arc<RetryBudget>) -> Result<Self, String> {
+        assert!(!urls.is_empty(), "FailoverSender needs at least one RPC URL");
+        let endpoints = urls
+            .iter()
+            .map(|url| {
+                let client = reqwest::Client::builder()
+                    .default_headers(HttpSender::default_headers())
+                    .timeout(timeout)
+                    .pool_idle_timeout(POOL_IDLE_TIMEOUT)
+                    .pool_max_idle_per_host(max_in_flight.saturating_add(BACKGROUND_CONNECTIONS))
+                    .build()
+                    .map_err(|e| format!("Failed to build RPC HTTP client: {}", e))?;
+                Ok(Endpoint {
+                    url: url.clone(),
+                    sender: HttpSender::new_with_client_with_middleware(
+                        url,
+                        reqwest_middleware::ClientBuilder::new(client)
+                            .with(RateLimitRetry { budget: budget.clone() })
+                            .build(),
+                    ),
+                    unhealthy_until: Mutex::new(None),
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self { endpoints })
+    }
+
+    /// The node could not serve the request, as opposed to rejecting it
+    fn should_fail_over(error: &ClientError) -> bool {
+        matches!(
+            error.kind(),
+            ClientErrorKind::Io(_)
+                | ClientErrorKind::Reqwest(_)
+                | ClientErrorKind::Middleware(_)
+                | ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                    code: JSON_RPC_SERVER_ERROR_NODE_UNHEALTHY,
+                    ..
+                })
+        )
+    }
+}
+
+#[async_trait]
+impl RpcSender for FailoverSender {
+    async fn send(&self, request: RpcRequest, params: serde_json::Value) -> ClientResult<serde_json::Value> {
+        let now = Instant::now();
+        // Endpoints still cooling down are a last resort rather than skipped outright
+        let (healthy, cooling): (Vec<&Endpoint>, Vec<&Endpoint>) =
+            self.endpoints.iter().partition(|endpoint| endpoint.is_healthy(now));
+
+        let mut last_error = None;
+        for endpoint in healthy.into_iter().chain(cooling) {
+            match endpoint.sender.send(request, params.clone()).await {
+                Ok(result) => {
+                    endpoint.mark_healthy();
+                    return Ok(result);
+                }
+                Err(e) if Self::should_fail_over(&e) => {
+                    warn!(url = %redacted_url(&endpoint.url), %request, error = %e, "RPC endpoint failed, trying the next one");
+                    endpoint.mark_unhealthy();
+                    last_error = Some(e);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Err(last_error.expect("at least one endpoint was tried"))
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        self.endpoints
+            .iter()
+            .map(|endpoint| endpoint.sender.get_transport_stats())
+            .fold(RpcTransportStats::default(), |total, stats| RpcTransportStats {
+                request_count: total.request_count + stats.request_count,
+                elapsed_time: total.elapsed_time + stats.elapsed_time,
+                rate_limited_time: total.rate_limited_time + stats.rate_limited_time,
+            })
+    }
+
+    /// The endpoint requests currently go to
+    fn url(&self) -> String {
+        let now = Instant::now();
+        self.endpoints
+            .iter()
+            .find(|endpoint| endpoint.is_healthy(now))
+            .unwrap_or(&self.endpoints[0])
+            .url
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_after_takes_seconds_or_an_http_date() {
+        let now = httpdate::parse_http_date("Wed, 21 Oct 2026 07:28:00 GMT").unwrap();
+        let header = |value: &str| {
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert(RETRY_AFTER, value.parse().unwrap());
+            retry_after(&headers, now)
+        };
+        assert_eq!(header("7"), Some(Duration::from_secs(7)));
+        assert_eq!(header("Wed, 21 Oct 2026 07:28:12 GMT"), Some(Duration::from_secs(12)));
+        // A date already past means retry now
+        assert_eq!(header("Wed, 21 Oct 2026 07:27:00 GMT"), Some(Duration::ZERO));
+        assert_eq!(header("soon"), None);
+        assert_eq!(retry_after(&reqwest::header::HeaderMap::new(), now), None);
+    }
+}
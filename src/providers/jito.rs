@@ -0,0 +1,127 @@
+use serde_json::{json, Value};
+use solana_sdk::{bs58, pubkey::Pubkey, transaction::VersionedTransaction};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tracing::info;
+use url::Url;
+
+use crate::blockchain::redacted_url;
+
+/// Block engine JSON-RPC endpoint for bundles, relative to the configured URL
+const BUNDLES_PATH: &str = "api/v1/bundles";
+
+/// Client of a Jito block engine, which hands bundles straight to Jito-running leaders ahead
+/// of the regular transaction queue in return for a tip paid inside the bundle
+pub struct JitoClient {
+    http: reqwest::Client,
+    bundles_url: Url,
+    /// Accounts tips may be paid to, fetched from the block engine on first use
+    tip_accounts: OnceCell<Vec<Pubkey>>,
+    /// Tips go to each account in turn, so bundles do not all contend for one write lock
+    next_tip_account: AtomicUsize,
+}
+
+impl JitoClient {
+    pub fn new(block_engine_url: &str, timeout: Duration) -> Result<Self, String> {
+        let mut base = Url::parse(block_engine_url)
+            .map_err(|e| format!("Invalid SOLANA_JITO_BLOCK_ENGINE_URL {:?}: {}", block_engine_url, e))?;
+        // Without a trailing slash `join` would replace the last path segment
+        if !base.path().ends_with('/') {
+            base.set_path(&format!("{}/", base.path()));
+        }
+        let bundles_url = base
+            .join(BUNDLES_PATH)
+            .map_err(|e| format!("Invalid SOLANA_JITO_BLOCK_ENGINE_URL {:?}: {}", block_engine_url, e))?;
+        let http = reqwest::Client::builder()
+            .timeout(timeout)
+            .build()
+            .map_err(|e| format!("Failed to build Jito HTTP client: {}", e))?;
+        info!(url = %redacted_url(block_engine_url), "Submitting stores as Jito bundles");
+        Ok(Self {
+            http,
+            bundles_url,
+            tip_accounts: OnceCell::new(),
+            next_tip_account: AtomicUsize::new(0),
+        })
+    }
+
+    /// Account the next bundle's tip is paid to
+    pub async fn tip_account(&self) -> Result<Pubkey, String> {
+        let accounts = self.tip_accounts.get_or_try_init(|| self.fetch_tip_accounts()).await?;
+        let index = self.next_tip_account.fetch_add(1, Ordering::Relaxed);
+        Ok(accounts[index % accounts.len()])
+    }
+
+    async fn fetch_tip_accounts(&self) -> Result<Vec<Pubkey>, String> {
+        let result = self.call("getTipAccounts", json!([])).await?;
+        let accounts = result
+            .as_array()
+            .map(|accounts| {
+                accounts
+                    .iter()
+                    .map(|account| account.as_str().and_then(|account| Pubkey::from_str(account).ok()))
+                    .collect::<Option<Vec<_>>>()
+            })
+            .unwrap_or_default()
+            .filter(|accounts| !accounts.is_empty())
+            .ok_or_else(|| format!("getTipAccounts returned no valid tip accounts: {}", result))?;
+        Ok(accounts)
+    }
+
+    /// Submit `transaction` as a bundle of its own, returning the bundle ID once the block
+    /// engine accepts it. Acceptance is no promise the bundle lands.
+    pub async fn send_bundle(&self, transaction: &VersionedTransaction) -> Result<String, String> {
+        let serialized = bincode::serialize(transaction).map_err(|e| format!("Failed to serialize transaction: {}", e))?;
+        let encoded = bs58::encode(serialized).into_string();
+        let result = self.call("sendBundle", json!([[encoded]])).await?;
+        result
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| format!("sendBundle returned no bundle ID: {}", result))
+    }
+
+    async fn call(&self, method: &str, params: Value) -> Result<Value, String> {
+        let request = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = self
+            .http
+            .post(self.bundles_url.clone())
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| format!("{} failed: {}", method, e.without_url()))?;
+        let status = response.status();
+        let body: Value = response
+            .json()
+            .await
+            .map_err(|e| format!("{} answered HTTP {} without a JSON-RPC body: {}", method, status, e.without_url()))?;
+        if let Some(error) = body.get("error") {
+            return Err(format!("{} was refused: {}", method, error));
+        }
+        body.get("result")
+            .cloned()
+            .ok_or_else(|| format!("{} answered HTTP {} without a result", method, status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blockchain::{BlockchainError, ChainConfig, MIN_JITO_TIP_LAMPORTS};
+
+    #[test]
+    fn bundle_endpoint_is_under_the_block_engine_url() {
+        let client = JitoClient::new("https://mainnet.block-engine.jito.wtf", Duration::from_secs(1)).unwrap();
+        assert_eq!(client.bundles_url.as_str(), "https://mainnet.block-engine.jito.wtf/api/v1/bundles");
+        let client = JitoClient::new("http://127.0.0.1:1234/jito", Duration::from_secs(1)).unwrap();
+        assert_eq!(client.bundles_url.as_str(), "http://127.0.0.1:1234/jito/api/v1/bundles");
+
+        // Jito drops bundles tipping less than its minimum
+        let error = ChainConfig::builder("http://127.0.0.1:8899", "B973pRh2NL9DkdvcT8VhSJywqqueknd8JXMkVVn18T3j")
+            .jito("https://mainnet.block-engine.jito.wtf", MIN_JITO_TIP_LAMPORTS - 1)
+            .build()
+            .unwrap_err();
+        assert!(matches!(error, BlockchainError::InvalidConfig(_)), "unexpected error {:?}", error);
+    }
+}
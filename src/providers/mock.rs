@@ -0,0 +1,290 @@
+use async_trait::async_trait;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use crate::blockchain::{
+    AccountBalance, BalanceInfo, BlockchainError, BlockchainProvider, BlockchainResult, ChainType, ClosedProof, DecodedProof, FeeEstimate, NetworkInfo,
+    StoreOptions, TransactionProofs, TransactionResult, TransactionStatus,
+};
+use crate::proto::ContentRecord;
+
+/// In-memory provider for tests: records every store and answers with a configurable result
+#[derive(Clone)]
+pub struct MockProvider {
+    inner: Arc<Mutex<MockState>>,
+}
+
+struct MockState {
+    /// `None` stores succeed with a generated transaction ID; `Some` answers every store with this error
+    store_error: Option<BlockchainError>,
+    /// Errors answering stores of particular UIDs, checked after `store_error`
+    uid_errors: HashMap<String, BlockchainError>,
+    stored: Vec<ContentRecord>,
+    /// Reported by `payer_balance`; `None` like providers without a payer balance
+    payer_balance: Option<u64>,
+    chain_type: ChainType,
+    /// Reported by `get_network_info`
+    genesis_hash: String,
+    /// How long a successful store waits after submitting, as if awaiting confirmation
+    confirmation_delay: Duration,
+    accounts: HashMap<String, ContentRecord>,
+    /// Transactions someone other than the gateway sent straight to the chain
+    foreign_transactions: HashSet<String>,
+    /// Transactions that landed but failed, known only to full-history lookups
+    failed_transactions: HashSet<String>,
+}
+
+impl MockProvider {
+    /// Provider whose stores all succeed
+    pub fn new() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(MockState {
+                store_error: None,
+                uid_errors: HashMap::new(),
+                stored: Vec::new(),
+                payer_balance: None,
+                chain_type: ChainType::Solana,
+                genesis_hash: "mock-genesis".to_string(),
+                confirmation_delay: Duration::ZERO,
+                accounts: HashMap::new(),
+                foreign_transactions: HashSet::new(),
+                failed_transactions: HashSet::new(),
+            })),
+        }
+    }
+
+    /// Provider whose stores all fail with `error`
+    pub fn failing(error: BlockchainError) -> Self {
+        let provider = Self::new();
+        provider.state().store_error = Some(error);
+        provider
+    }
+
+    /// Provider whose payer holds `balance`
+    pub fn with_payer_balance(balance: u64) -> Self {
+        let provider = Self::new();
+        provider.state().payer_balance = Some(balance);
+        provider
+    }
+
+    /// Provider reporting `chain_type` instead of Solana
+    pub fn on_chain(chain_type: ChainType) -> Self {
+        let provider = Self::new();
+        provider.state().chain_type = chain_type;
+        provider
+    }
+
+    /// Provider on the network with `genesis_hash`, such as a production cluster
+    pub fn on_network(genesis_hash: &str) -> Self {
+        let provider = Self::new();
+        provider.state().genesis_hash = genesis_hash.to_string();
+        provider
+    }
+
+    /// Provider whose stores take `delay` to confirm, leaving them in flight meanwhile
+    pub fn with_confirmation_delay(delay: Duration) -> Self {
+        let provider = Self::new();
+        provider.state().confirmation_delay = delay;
+        provider
+    }
+
+    /// Fail stores of the record with `uid` with `error`, while other records still succeed
+    pub fn fail_uid(&self, uid: &str, error: BlockchainError) {
+        self.state().uid_errors.insert(uid.to_string(), error);
+    }
+
+    /// Let later stores succeed again, as when an unreachable chain comes back
+    pub fn recover(&self) {
+        self.state().store_error = None;
+    }
+
+    /// Store `record` the way someone other than the gateway could, paying for it themselves,
+    /// and return the transaction ID
+    pub fn store_foreign(&self, record: &ContentRecord) -> BlockchainResult<String> {
+        let transaction_id = self.record_store(record, &StoreOptions::default())?.transaction_id;
+        self.state().foreign_transactions.insert(transaction_id.clone());
+        Ok(transaction_id)
+    }
+
+    /// Answer full-history lookups of `transaction_id` as failed on chain, as for a transaction
+    /// too old for status lookups to report
+    pub fn fail_on_chain(&self, transaction_id: &str) {
+        self.state().failed_transactions.insert(transaction_id.to_string());
+    }
+
+    /// Records passed to `store_record`, in call order
+    pub fn stored_records(&self) -> Vec<ContentRecord> {
+        self.state().stored.clone()
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Record a store and answer it the way `store_record` does, without the confirmation delay
+    fn record_store(&self, record: &ContentRecord, options: &StoreOptions) -> BlockchainResult<TransactionResult> {
+        let mut state = self.state();
+        if let Some(error) = state.store_error.clone().or_else(|| state.uid_errors.get(&record.uid).cloned()) {
+            state.stored.push(record.clone());
+            return Err(error);
+        }
+        if options.dry_run {
+            return Ok(TransactionResult {
+                transaction_id: String::new(),
+                account_address: Some(format!("mock-account-{}", state.stored.len() + 1)),
+                block_height: None,
+                block_time: None,
+                confirmation_time: None,
+                finalization_time: None,
+                simulation_logs: Some(vec!["Program log: mock dry run".to_string()]),
+                explorer_url: None,
+            });
+        }
+        state.stored.push(record.clone());
+
+        let index = state.stored.len();
+        let account_address = format!("mock-account-{}", index);
+        state.accounts.insert(account_address.clone(), record.clone());
+        Ok(TransactionResult {
+            transaction_id: format!("mock-tx-{}", index),
+            account_address: Some(account_address),
+            block_height: Some(index as u64),
+            block_time: None,
+            confirmation_time: None,
+            finalization_time: None,
+            simulation_logs: None,
+            explorer_url: None,
+        })
+    }
+}
+
+impl Default for MockProvider {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl BlockchainProvider for MockProvider {
+    fn chain_type(&self) -> ChainType {
+        self.state().chain_type.clone()
+    }
+
+    async fn check_health(&self) -> BlockchainResult<()> {
+        Ok(())
+    }
+
+    async fn get_network_info(&self) -> BlockchainResult<NetworkInfo> {
+        let state = self.state();
+        Ok(NetworkInfo {
+            chain_type: state.chain_type.clone(),
+            network_url: "mock://localhost".to_string(),
+            genesis_hash: state.genesis_hash.clone(),
+            latest_block: state.stored.len() as u64,
+            node_version: "mock".to_string(),
+            chain_id: None,
+        })
+    }
+
+    async fn estimate_fee(&self, _record: &ContentRecord) -> BlockchainResult<FeeEstimate> {
+        Ok(FeeEstimate {
+            storage_fee: 1_000,
+            transaction_fee: 5_000,
+            unit: "lamports",
+            gas_limit: None,
+            gas_price: None,
+        })
+    }
+
+    async fn store_record(&self, record: &ContentRecord, options: &StoreOptions) -> BlockchainResult<TransactionResult> {
+        let result = self.record_store(record, options);
+        if result.is_ok() {
+            let delay = self.state().confirmation_delay;
+            tokio::time::sleep(delay).await;
+        }
+        result
+    }
+
+    /// Transactions this mock issued are confirmed at their store index; anything else is unknown
+    async fn transaction_statuses(&self, transaction_ids: &[String]) -> BlockchainResult<Vec<TransactionStatus>> {
+        let stored = self.state().stored.len() as u64;
+        Ok(transaction_ids
+            .iter()
+            .map(|id| match id.strip_prefix("mock-tx-").and_then(|index| index.parse::<u64>().ok()) {
+                Some(index) if (1..=stored).contains(&index) => TransactionStatus::Confirmed {
+                    block_height: Some(index),
+                },
+                _ => TransactionStatus::Unknown,
+            })
+            .collect())
+    }
+
+    /// Transactions this mock issued carry the record stored with them; anything else is not found
+    async fn get_proof_by_transaction(&self, transaction_id: &str) -> BlockchainResult<TransactionProofs> {
+        let state = self.state();
+        if state.failed_transactions.contains(transaction_id) {
+            return Err(BlockchainError::NotAProof(format!("Transaction {} failed on chain", transaction_id)));
+        }
+        let index = transaction_id
+            .strip_prefix("mock-tx-")
+            .and_then(|index| index.parse::<usize>().ok())
+            .filter(|index| (1..=state.stored.len()).contains(index))
+            .ok_or_else(|| BlockchainError::TransactionNotFound(transaction_id.to_string()))?;
+        let payer = if state.foreign_transactions.contains(transaction_id) {
+            "mock-outsider"
+        } else {
+            "mock-payer"
+        };
+        Ok(TransactionProofs {
+            proofs: vec![DecodedProof {
+                record: state.stored[index - 1].clone(),
+                account_address: Some(format!("mock-account-{}", index)),
+                payer: Some(payer.to_string()),
+            }],
+            block_height: Some(index as u64),
+            block_time: None,
+        })
+    }
+
+    async fn get_balance(&self) -> BlockchainResult<BalanceInfo> {
+        Ok(BalanceInfo {
+            accounts: vec![AccountBalance {
+                address: "mock-payer".to_string(),
+                balance: self.state().payer_balance.unwrap_or(0).into(),
+            }],
+            unit: "lamports",
+            decimals: 9,
+        })
+    }
+
+    async fn payer_balance(&self) -> BlockchainResult<Option<u64>> {
+        Ok(self.state().payer_balance)
+    }
+
+    fn is_own_submission(&self, proof: &DecodedProof) -> bool {
+        proof.payer.as_deref() == Some("mock-payer")
+    }
+
+    async fn retrieve_record(&self, account_address: &str) -> BlockchainResult<ContentRecord> {
+        self.state()
+            .accounts
+            .get(account_address)
+            .cloned()
+            .ok_or_else(|| BlockchainError::AccountNotFound(account_address.to_string()))
+    }
+
+    /// Accounts this mock created are removed, each returning the same rent
+    async fn close_proof(&self, account_address: &str) -> BlockchainResult<ClosedProof> {
+        self.state()
+            .accounts
+            .remove(account_address)
+            .ok_or_else(|| BlockchainError::AccountNotFound(account_address.to_string()))?;
+        Ok(ClosedProof {
+            transaction_id: format!("mock-close-{}", account_address),
+            recipient: "mock-payer".to_string(),
+            reclaimed: 1_500_000,
+            block_height: None,
+        })
+    }
+}
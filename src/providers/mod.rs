@@ -1,4 +1,45 @@
+#[cfg(feature = "solana")]
 pub mod solana;
+#[cfg(feature = "ethereum")]
+pub mod ethereum;
+#[cfg(feature = "solana")]
+mod failover;
+#[cfg(feature = "solana")]
+mod jito;
+#[cfg(feature = "solana")]
+mod retry_budget;
+#[cfg(feature = "solana")]
+pub mod signer;
+#[cfg(test)]
+pub mod mock;
+
+use std::sync::Arc;
+
+use crate::blockchain::{BlockchainError, BlockchainProvider, BlockchainResult, ChainConfig};
+#[cfg(any(feature = "solana", feature = "ethereum"))]
+use crate::blockchain::ChainType;
 
 // Re-export providers for easier access
-pub use solana::SolanaProvider; 
\ No newline at end of file
+#[cfg(feature = "solana")]
+pub use signer::{LocalSigner, TransactionSigner};
+#[cfg(feature = "solana")]
+pub use solana::SolanaProvider;
+#[cfg(feature = "ethereum")]
+pub use ethereum::EthereumProvider;
+
+/// Instantiate the provider matching `config.chain_type`. Chains whose cargo feature is
+/// disabled are rejected as invalid configuration.
+pub fn create_provider(config: ChainConfig) -> BlockchainResult<Arc<dyn BlockchainProvider>> {
+    match config.chain_type {
+        #[cfg(feature = "solana")]
+        ChainType::Solana => Ok(Arc::new(SolanaProvider::new(config)?)),
+        #[cfg(feature = "ethereum")]
+        ChainType::Ethereum | ChainType::Evm { .. } => Ok(Arc::new(EthereumProvider::new(config)?)),
+        #[allow(unreachable_patterns)]
+        chain_type => Err(BlockchainError::InvalidConfig(format!(
+            "CHAIN_TYPE {} is not available in this build; rebuild with `--features {}`",
+            chain_type,
+            chain_type.cargo_feature()
+        ))),
+    }
+}
@@ -0,0 +1,75 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Window the budget's retry count is given over
+const BUDGET_WINDOW: Duration = Duration::from_secs(60);
+
+/// Retries shared by every request to the chain, as a token bucket holding up to the
+/// per-minute allowance and refilling evenly over the minute. A burst can spend the whole
+/// allowance at once; after that retries are refused until tokens trickle back, so a struggling
+/// or rate-limiting node is not hit harder the worse it gets.
+pub struct RetryBudget {
+    per_window: u32,
+    bucket: Mutex<Bucket>,
+}
+
+struct Bucket {
+    tokens: f64,
+    refilled_at: Instant,
+}
+
+impl RetryBudget {
+    /// Budget of `retries_per_minute`, starting full; 0 refuses every retry
+    pub fn new(retries_per_minute: u32) -> Self {
+        Self {
+            per_window: retries_per_minute,
+            bucket: Mutex::new(Bucket {
+                tokens: retries_per_minute as f64,
+                refilled_at: Instant::now(),
+            }),
+        }
+    }
+
+    /// Take one retry from the budget, or return false when it is spent
+    pub fn try_acquire(&self) -> bool {
+        self.try_acquire_at(Instant::now())
+    }
+
+    fn try_acquire_at(&self, now: Instant) -> bool {
+        let mut bucket = self.bucket.lock().unwrap_or_else(|e| e.into_inner());
+        let refill = now.saturating_duration_since(bucket.refilled_at).as_secs_f64() / BUDGET_WINDOW.as_secs_f64();
+        bucket.tokens = (bucket.tokens + refill * self.per_window as f64).min(self.per_window as f64);
+        bucket.refilled_at = now;
+        if bucket.tokens < 1.0 {
+            crate::metrics::metrics().rpc_retries_rejected.inc();
+            return false;
+        }
+        bucket.tokens -= 1.0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_is_spent_then_refills_over_the_window() {
+        let budget = RetryBudget::new(2);
+        let start = Instant::now();
+        assert!(budget.try_acquire_at(start));
+        assert!(budget.try_acquire_at(start));
+        assert!(!budget.try_acquire_at(start));
+
+        // One retry comes back every half minute, and never more than the allowance
+        assert!(!budget.try_acquire_at(start + Duration::from_secs(20)));
+        assert!(budget.try_acquire_at(start + Duration::from_secs(31)));
+        assert!(!budget.try_acquire_at(start + Duration::from_secs(31)));
+        let later = start + Duration::from_secs(600);
+        assert!(budget.try_acquire_at(later));
+        assert!(budget.try_acquire_at(later));
+        assert!(!budget.try_acquire_at(later));
+
+        assert!(!RetryBudget::new(0).try_acquire());
+    }
+}
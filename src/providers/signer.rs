@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use solana_sdk::{
+    pubkey::Pubkey,
+    signature::{Keypair, Signature, Signer},
+};
+
+use crate::blockchain::BlockchainResult;
+
+/// Signs Solana transaction messages for one account. The provider only ever hands over the
+/// serialized message, so an implementation may keep the private key elsewhere, e.g. behind
+/// an HTTP signing service or in an HSM.
+#[async_trait]
+pub trait TransactionSigner: Send + Sync {
+    /// Account the signatures are made for
+    fn pubkey(&self) -> Pubkey;
+
+    /// Sign the serialized transaction message. Signatures that do not verify against
+    /// `pubkey` fail the store rather than being sent.
+    async fn sign_message(&self, message: &[u8]) -> BlockchainResult<Signature>;
+}
+
+/// Keypair held in process memory
+pub struct LocalSigner {
+    keypair: Keypair,
+}
+
+impl LocalSigner {
+    pub fn new(keypair: Keypair) -> Self {
+        Self { keypair }
+    }
+}
+
+#[async_trait]
+impl TransactionSigner for LocalSigner {
+    fn pubkey(&self) -> Pubkey {
+        self.keypair.pubkey()
+    }
+
+    async fn sign_message(&self, message: &[u8]) -> BlockchainResult<Signature> {
+        Ok(self.keypair.sign_message(message))
+    }
+}
@@ -1,156 +1,996 @@
 use async_trait::async_trait;
 use borsh::{BorshSerialize, BorshDeserialize};
-use solana_client::rpc_client::RpcClient;
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use solana_client::client_error::{ClientError, ClientErrorKind};
+use solana_client::nonblocking::pubsub_client::{PubsubClient, PubsubClientError};
+use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::rpc_client::RpcClientConfig;
+use solana_client::rpc_config::{
+    RpcSendTransactionConfig, RpcSignatureSubscribeConfig, RpcSimulateTransactionConfig, RpcTransactionConfig,
+};
+use solana_client::rpc_request::{RpcError, RpcRequest};
+use solana_client::rpc_response::{Response as RpcResponse, RpcSignatureResult};
 use solana_sdk::{
-    commitment_config::CommitmentConfig,
-    instruction::{AccountMeta, Instruction},
-    pubkey::Pubkey,
-    signature::{Keypair, Signer},
-    system_program,
-    transaction::Transaction,
+    bs58,
+    commitment_config::{CommitmentConfig, CommitmentLevel},
+    hash::{hash, Hash},
+    instruction::{AccountMeta, Instruction, InstructionError},
+    message::{v0, AddressLookupTableAccount, Message, VersionedMessage},
+    native_token::LAMPORTS_PER_SOL,
+    pubkey::{Pubkey, MAX_SEED_LEN},
+    signature::{Keypair, Signature, Signer},
+    transaction::{TransactionError, VersionedTransaction},
 };
-use std::path::Path;
+use solana_address_lookup_table_interface::state::AddressLookupTable;
+use solana_compute_budget_interface::ComputeBudgetInstruction;
+use solana_system_interface::{instruction as system_instruction, program as system_program};
+use solana_transaction_status_client_types::option_serializer::OptionSerializer;
+use solana_transaction_status_client_types::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
+use std::env;
+use std::path::{Path, PathBuf};
 use std::str::FromStr;
-use std::time::Duration;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tracing::{debug, info, trace, warn};
 
+use super::signer::{LocalSigner, TransactionSigner};
+use crate::metrics::ConfirmationOutcome;
 use crate::blockchain::{
-    BlockchainProvider, BlockchainResult, SolanaConfig, TransactionResult,
+    redacted_url, AccountBalance, BalanceInfo, BatchChunkResult, BlockchainError, BlockchainProvider, BlockchainResult, ChainConfig, ChainType, ClosedProof,
+    Commitment, DecodedProof, Explorer, FeeEstimate, NetworkInfo, PriorityFee, RentStrategy, StoreOptions, TransactionProofs,
+    TransactionResult, TransactionStatus, SOLANA_NON_DEV_GENESIS_HASHES,
 };
 use crate::proto::ContentRecord;
+use super::failover::FailoverSender;
+use super::jito::JitoClient;
+use super::retry_budget::RetryBudget;
+
+/// Delay before the first resubmission; doubled on each further attempt
+const INITIAL_SEND_BACKOFF: Duration = Duration::from_millis(250);
+
+/// Percentile of recent prioritization fees paid when the priority fee is `auto`
+const AUTO_PRIORITY_FEE_PERCENTILE: usize = 75;
+
+/// Largest serialized transaction a validator accepts (`PACKET_DATA_SIZE`)
+const MAX_TRANSACTION_SIZE: usize = crate::blockchain::MAX_SOLANA_TRANSACTION_SIZE;
+
+/// Environment variable holding the payer's base58-encoded secret key
+const KEYPAIR_BASE58_VAR: &str = "CHAIN_KEYPAIR_BASE58";
+
+/// How long a payer found unable to cover a store is passed over before it is checked again
+const UNDERFUNDED_RECHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often the background task refreshes the cached blockhash
+const BLOCKHASH_REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Oldest cached blockhash we will sign with; blockhashes expire after ~150 slots (~60s),
+/// so this leaves room for confirmation
+const BLOCKHASH_MAX_AGE: Duration = Duration::from_secs(20);
+
+/// Delay between signature status checks while waiting for confirmation
+const CONFIRMATION_POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// While waiting on a signature subscription, how often to check that the blockhash is still valid
+const SUBSCRIPTION_EXPIRY_CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Start of the error raised for a transaction whose blockhash expired before it confirmed
+const BLOCKHASH_EXPIRED: &str = "blockhash expired";
+
+/// Signatures `getSignatureStatuses` accepts per call
+const MAX_SIGNATURE_STATUSES: usize = 256;
+
+/// SPL Memo program (v2)
+const MEMO_PROGRAM_ID: Pubkey = solana_sdk::pubkey!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// Tag in front of the UID in memos, marking the transaction as a gateway proof store
+const MEMO_PREFIX: &str = "proof:";
+
+/// Seed prefix for proof account PDAs
+pub const PROOF_SEED: &[u8] = b"proof";
+
+/// Instruction layout written since instructions gained a leading version byte. Version 1
+/// had no version byte and started with the variant tag, 0 or 1, so the two never collide.
+pub const PROOF_INSTRUCTION_V2: u8 = 2;
 
-#[derive(BorshSerialize, BorshDeserialize, Debug)]
+/// Instruction layout adding the optional content type and storage URI after the submitter.
+/// Only written for records that set one of them, so other stores still work with programs
+/// that predate it.
+pub const PROOF_INSTRUCTION_V3: u8 = 3;
+
+/// Proof account layout carrying the submission time and submitter. It is written as a
+/// version byte after the original fields, so accounts from before it decode as version 1.
+pub const PROOF_RECORD_V2: u8 = 2;
+
+/// Proof account layout that also carries the content type and storage URI, written by
+/// version 3 instructions
+pub const PROOF_RECORD_V3: u8 = 3;
+
+#[derive(BorshSerialize, BorshDeserialize, Debug, Clone)]
 pub enum ProofInstruction {
     StoreProof {
         url: String,
         content_hash: String,
         content_length: u64,
+        /// Unix seconds at submission, taken from the gateway clock rather than the client
+        timestamp: i64,
+        submitter: Option<String>,
+        /// MIME type of the content
+        content_type: Option<String>,
+        /// Where the content lives, e.g. `ipfs://<cid>`
+        storage_uri: Option<String>,
+    },
+    /// Store a proof in the PDA derived from `[PROOF_SEED, uid_seed]`; the program creates the account
+    StoreProofWithSeed {
+        uid_seed: Vec<u8>,
+        bump: u8,
+        url: String,
+        content_hash: String,
+        content_length: u64,
+        timestamp: i64,
+        submitter: Option<String>,
+        content_type: Option<String>,
+        storage_uri: Option<String>,
     },
+    /// Close a proof account and return its lamports to the payer, which must be the submitter
+    /// recorded in it or the program's close authority. Only exists in version 3.
+    CloseProof,
 }
 
 impl ProofInstruction {
-    pub fn try_to_vec(&self) -> Result<Vec<u8>, std::io::Error> {
-        borsh::to_vec(self)
-            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    /// Serialize as the oldest layout that holds the instruction: version 2 for stores without
+    /// a content type or storage URI, version 3 otherwise. Either is the version byte, then Borsh.
+    pub fn try_to_vec(&self) -> BlockchainResult<Vec<u8>> {
+        let encoded = match self.to_v2() {
+            Some(v2) => {
+                let mut data = vec![PROOF_INSTRUCTION_V2];
+                borsh::to_writer(&mut data, &v2).map(|()| data)
+            }
+            None => {
+                let mut data = vec![PROOF_INSTRUCTION_V3];
+                borsh::to_writer(&mut data, self).map(|()| data)
+            }
+        };
+        encoded.map_err(|e| BlockchainError::Serialization(format!("Failed to encode instruction: {}", e)))
+    }
+
+    /// The instruction in the version 2 layout, if it has no fields only version 3 holds
+    fn to_v2(&self) -> Option<ProofInstructionV2> {
+        match self.clone() {
+            Self::StoreProof {
+                url,
+                content_hash,
+                content_length,
+                timestamp,
+                submitter,
+                content_type: None,
+                storage_uri: None,
+            } => Some(ProofInstructionV2::StoreProof {
+                url,
+                content_hash,
+                content_length,
+                timestamp,
+                submitter,
+            }),
+            Self::StoreProofWithSeed {
+                uid_seed,
+                bump,
+                url,
+                content_hash,
+                content_length,
+                timestamp,
+                submitter,
+                content_type: None,
+                storage_uri: None,
+            } => Some(ProofInstructionV2::StoreProofWithSeed {
+                uid_seed,
+                bump,
+                url,
+                content_hash,
+                content_length,
+                timestamp,
+                submitter,
+            }),
+            _ => None,
+        }
+    }
+
+    /// Decode instruction data of any layout version, dispatching on the first byte.
+    /// Version 1 instructions predate timestamps and decode with a zero timestamp; versions
+    /// before 3 decode without a content type or storage URI.
+    pub fn decode(data: &[u8]) -> Option<Self> {
+        match data.split_first()? {
+            (&PROOF_INSTRUCTION_V3, rest) => Self::try_from_slice(rest).ok(),
+            (&PROOF_INSTRUCTION_V2, rest) => ProofInstructionV2::try_from_slice(rest).ok().map(Self::from),
+            (0 | 1, _) => ProofInstructionV1::try_from_slice(data).ok().map(Self::from),
+            (version, _) => {
+                debug!(version, "Skipping proof instruction of an unknown layout version");
+                None
+            }
+        }
+    }
+}
+
+/// Version 2 layout, without the content type and storage URI; still written for records without them
+#[derive(BorshSerialize, BorshDeserialize)]
+enum ProofInstructionV2 {
+    StoreProof {
+        url: String,
+        content_hash: String,
+        content_length: u64,
+        timestamp: i64,
+        submitter: Option<String>,
+    },
+    StoreProofWithSeed {
+        uid_seed: Vec<u8>,
+        bump: u8,
+        url: String,
+        content_hash: String,
+        content_length: u64,
+        timestamp: i64,
+        submitter: Option<String>,
+    },
+}
+
+impl From<ProofInstructionV2> for ProofInstruction {
+    fn from(instruction: ProofInstructionV2) -> Self {
+        match instruction {
+            ProofInstructionV2::StoreProof {
+                url,
+                content_hash,
+                content_length,
+                timestamp,
+                submitter,
+            } => Self::StoreProof {
+                url,
+                content_hash,
+                content_length,
+                timestamp,
+                submitter,
+                content_type: None,
+                storage_uri: None,
+            },
+            ProofInstructionV2::StoreProofWithSeed {
+                uid_seed,
+                bump,
+                url,
+                content_hash,
+                content_length,
+                timestamp,
+                submitter,
+            } => Self::StoreProofWithSeed {
+                uid_seed,
+                bump,
+                url,
+                content_hash,
+                content_length,
+                timestamp,
+                submitter,
+                content_type: None,
+                storage_uri: None,
+            },
+        }
+    }
+}
+
+/// Version 1 layout, without a version byte, timestamp or submitter; still found in old transactions
+#[derive(BorshDeserialize)]
+enum ProofInstructionV1 {
+    StoreProof {
+        url: String,
+        content_hash: String,
+        content_length: u64,
+    },
+    StoreProofWithSeed {
+        uid_seed: Vec<u8>,
+        bump: u8,
+        url: String,
+        content_hash: String,
+        content_length: u64,
+    },
+}
+
+impl From<ProofInstructionV1> for ProofInstruction {
+    fn from(instruction: ProofInstructionV1) -> Self {
+        match instruction {
+            ProofInstructionV1::StoreProof {
+                url,
+                content_hash,
+                content_length,
+            } => Self::StoreProof {
+                url,
+                content_hash,
+                content_length,
+                timestamp: 0,
+                submitter: None,
+                content_type: None,
+                storage_uri: None,
+            },
+            ProofInstructionV1::StoreProofWithSeed {
+                uid_seed,
+                bump,
+                url,
+                content_hash,
+                content_length,
+            } => Self::StoreProofWithSeed {
+                uid_seed,
+                bump,
+                url,
+                content_hash,
+                content_length,
+                timestamp: 0,
+                submitter: None,
+                content_type: None,
+                storage_uri: None,
+            },
+        }
+    }
+}
+
+/// On-chain layout of a proof account, as written by the `StoreProof` handler
+#[derive(Debug)]
+pub struct ProofRecord {
+    pub url: String,
+    pub content_hash: String,
+    pub content_length: u64,
+    /// Unix seconds at submission; `None` for version 1 accounts
+    pub timestamp: Option<i64>,
+    pub submitter: Option<String>,
+    /// `None` for accounts before version 3, and for records stored without one
+    pub content_type: Option<String>,
+    pub storage_uri: Option<String>,
+}
+
+impl ProofRecord {
+    /// Borsh-encoded size of the account the program writes for `record`: a u32 length prefix
+    /// per string, the u64 length, then the version byte, i64 timestamp and optional submitter.
+    /// Version 3 accounts, for records with a content type or storage URI, add both as options.
+    pub fn space(record: &ContentRecord, submitter: Option<&str>) -> usize {
+        let option = |value: Option<&str>| 1 + value.map_or(0, |value| 4 + value.len());
+        let v2 = 4 + record.url.len() + 4 + record.stored_content_hash().len() + 8 + 1 + 8 + option(submitter);
+        if record.content_type.is_empty() && record.storage_uri.is_empty() {
+            v2
+        } else {
+            v2 + option(non_empty(&record.content_type).as_deref()) + option(non_empty(&record.storage_uri).as_deref())
+        }
+    }
+
+    /// Decode an account of any layout version. Version 1 accounts end after `content_length`,
+    /// possibly followed by zero padding, so a missing or zero version byte means version 1.
+    pub fn decode(mut data: &[u8]) -> std::io::Result<Self> {
+        let url = String::deserialize(&mut data)?;
+        let content_hash = String::deserialize(&mut data)?;
+        let content_length = u64::deserialize(&mut data)?;
+        let mut record = Self {
+            url,
+            content_hash,
+            content_length,
+            timestamp: None,
+            submitter: None,
+            content_type: None,
+            storage_uri: None,
+        };
+        match data.split_first() {
+            None | Some((0, _)) => {}
+            Some((&version @ (PROOF_RECORD_V2 | PROOF_RECORD_V3), mut rest)) => {
+                record.timestamp = Some(i64::deserialize(&mut rest)?);
+                record.submitter = Option::<String>::deserialize(&mut rest)?;
+                if version == PROOF_RECORD_V3 {
+                    record.content_type = Option::<String>::deserialize(&mut rest)?;
+                    record.storage_uri = Option::<String>::deserialize(&mut rest)?;
+                }
+            }
+            Some((version, _)) => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("unknown proof account version {}", version),
+                ))
+            }
+        }
+        Ok(record)
+    }
+}
+
+/// Current time as the proof timestamp
+fn unix_now() -> i64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|since| since.as_secs() as i64).unwrap_or(0)
+}
+
+/// An optional record field as written on chain, where empty means unset
+fn non_empty(value: &str) -> Option<String> {
+    Some(value).filter(|value| !value.is_empty()).map(str::to_string)
+}
+
+/// A record's proof account and the instructions that create and fill it
+struct PreparedRecord {
+    proof_address: Pubkey,
+    instructions: Vec<Instruction>,
+    /// Signer for the proof account when it is a fresh keypair rather than a PDA
+    proof_keypair: Option<Keypair>,
+    /// Rent-exempt balance the payer funds the proof account with
+    rent_lamports: u64,
+}
+
+/// One signer of the payer pool, with what its last balance check found
+struct Payer {
+    signer: Arc<dyn TransactionSigner>,
+    balance: Mutex<PayerBalance>,
+    /// Held while topping the payer up, so concurrent stores request one airdrop between them
+    airdrop: tokio::sync::Mutex<()>,
+}
+
+#[derive(Default)]
+struct PayerBalance {
+    /// Lamports at the last check
+    lamports: Option<u64>,
+    /// When a check last found the payer unable to cover a store
+    underfunded_at: Option<Instant>,
+}
+
+impl Payer {
+    fn new(signer: Arc<dyn TransactionSigner>) -> Self {
+        Self {
+            signer,
+            balance: Mutex::new(PayerBalance::default()),
+            airdrop: tokio::sync::Mutex::new(()),
+        }
+    }
+
+    fn pubkey(&self) -> Pubkey {
+        self.signer.pubkey()
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, PayerBalance> {
+        self.balance.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Remember a fetched balance. With the lamports a store `required` it is judged funded or
+    /// not; without, a balance that grew since the last check clears an underfunded mark.
+    fn record_balance(&self, lamports: u64, required: Option<u64>) {
+        let mut balance = self.lock();
+        match required {
+            Some(required) => balance.underfunded_at = (lamports < required).then(Instant::now),
+            None if balance.lamports.is_some_and(|previous| lamports > previous) => balance.underfunded_at = None,
+            None => {}
+        }
+        balance.lamports = Some(lamports);
+    }
+
+    fn recently_underfunded(&self) -> bool {
+        self.lock()
+            .underfunded_at
+            .is_some_and(|at| at.elapsed() < UNDERFUNDED_RECHECK_INTERVAL)
     }
 }
 
+struct CachedBlockhash {
+    hash: Hash,
+    fetched_at: Instant,
+}
+
 pub struct SolanaProvider {
-    client: RpcClient,
+    client: Arc<RpcClient>,
+    /// Latest blockhash, kept fresh by a background task once initialized
+    blockhash_cache: Arc<RwLock<Option<CachedBlockhash>>>,
+    /// WebSocket client for signature subscriptions, connected on first use when `ws_url` is set
+    pubsub: tokio::sync::Mutex<Option<Arc<PubsubClient>>>,
     program_id: Pubkey,
-    payer: Keypair,
-    config: SolanaConfig,
+    /// Payers used in turn, so concurrent stores do not all contend for one account's write lock
+    payers: Vec<Payer>,
+    /// Where in `payers` the next store starts looking
+    next_payer: AtomicUsize,
+    /// Set once `initialize` has confirmed the cluster is devnet or a local validator
+    airdrop_allowed: AtomicBool,
+    /// `lookup_table` from the config, fetched by `initialize`; transactions stay legacy until then
+    lookup_table_address: Option<Pubkey>,
+    lookup_table: RwLock<Option<AddressLookupTableAccount>>,
+    explorer: Option<Explorer>,
+    /// Shared by send retries and the transport's retries of rate-limited requests
+    retry_budget: Arc<RetryBudget>,
+    /// Block engine stores are sent to as tipped bundles before falling back to the RPC node
+    jito: Option<JitoClient>,
+    /// Written into each proof: `CHAIN_SUBMITTER`, or the submitter keypair's public key
+    submitter: Option<String>,
+    /// Co-signs every store and close when the submitter is not the fee payer
+    submitter_keypair: Option<Keypair>,
+    config: ChainConfig,
 }
 
 impl SolanaProvider {
-    pub fn new(config: SolanaConfig) -> BlockchainResult<Self> {
-        let client = RpcClient::new_with_commitment(
-            config.rpc_url.clone(),
-            CommitmentConfig::confirmed(),
-        );
+    /// Provider paying with the keypairs `config` points at: the payer pool, or else the
+    /// single payer from `CHAIN_KEYPAIR_BASE58`, the keypair file or an ephemeral keypair
+    pub fn new(config: ChainConfig) -> BlockchainResult<Self> {
+        let payers = if config.payer_keypairs.is_empty() {
+            vec![Self::load_payer(config.private_key_path.as_deref())?]
+        } else {
+            Self::load_payer_pool(&config.payer_keypairs)?
+        };
+        Self::with_payers(config, payers)
+    }
 
-        let program_id = Pubkey::from_str(&config.program_id)?;
-        println!("🔗 Using program ID: {}", program_id);
+    /// Provider paying with `payers` in turn, ignoring the keypair settings in `config`
+    pub fn with_payers(config: ChainConfig, payers: Vec<Keypair>) -> BlockchainResult<Self> {
+        let signers = payers
+            .into_iter()
+            .map(|keypair| Arc::new(LocalSigner::new(keypair)) as Arc<dyn TransactionSigner>)
+            .collect();
+        Self::with_signers(config, signers)
+    }
+
+    /// Provider paying with `signers` in turn, ignoring the payer keypair settings in `config`.
+    /// The payer keys never have to be in this process; see [`TransactionSigner`]. A submitter
+    /// keypair in `config` is still loaded, since it is not a payer.
+    pub fn with_signers(config: ChainConfig, signers: Vec<Arc<dyn TransactionSigner>>) -> BlockchainResult<Self> {
+        config.validate()?;
+        if signers.is_empty() {
+            return Err(BlockchainError::InvalidConfig("At least one payer signer is required".to_string()));
+        }
+        let retry_budget = Arc::new(RetryBudget::new(config.retry_budget));
+        let client = Arc::new(RpcClient::new_sender(
+            FailoverSender::new(&config.rpc_urls(), config.rpc_timeout, config.max_in_flight, retry_budget.clone())
+                .map_err(BlockchainError::InvalidConfig)?,
+            RpcClientConfig::with_commitment(commitment_config(config.commitment)),
+        ));
+        if !config.fallback_urls.is_empty() {
+            info!(fallbacks = config.fallback_urls.len(), "RPC failover enabled");
+        }
+        if let Some(ws_url) = config.ws_url.as_deref() {
+            info!(url = %redacted_url(ws_url), "Confirming transactions through signature subscriptions");
+        }
+        if config.use_pda && config.rent_strategy != RentStrategy::Exact {
+            warn!(rent_strategy = %config.rent_strategy, "The rent strategy only applies to keypair mode; the program sizes PDA proof accounts");
+        }
 
-        println!("🔑 Loading payer keypair from: {}", config.keypair_path);
-        let payer = Self::load_keypair(&config.keypair_path)?;
-        println!("🔑 Payer public key: {}", payer.pubkey());
+        let program_id = Pubkey::from_str(&config.program_id)
+            .map_err(|e| BlockchainError::InvalidConfig(format!("Invalid program ID {:?}: {}", config.program_id, e)))?;
+        info!(program_id = %program_id, "Using proof program");
+        let lookup_table_address = config
+            .lookup_table
+            .as_deref()
+            .map(|address| {
+                Pubkey::from_str(address).map_err(|e| {
+                    BlockchainError::InvalidConfig(format!("Invalid SOLANA_LOOKUP_TABLE {:?}: {}", address, e))
+                })
+            })
+            .transpose()?;
+        let jito = config
+            .jito_block_engine_url
+            .as_deref()
+            .map(|url| JitoClient::new(url, config.rpc_timeout))
+            .transpose()
+            .map_err(BlockchainError::InvalidConfig)?;
+        let submitter_keypair = config.submitter_keypair.as_deref().map(Self::load_keypair).transpose()?;
+        if let Some(keypair) = &submitter_keypair {
+            info!(submitter = %keypair.pubkey(), "Recording a submitter separate from the fee payer");
+        }
+        let submitter = match &submitter_keypair {
+            Some(keypair) => Some(keypair.pubkey().to_string()),
+            None => config.submitter.clone(),
+        };
 
         Ok(Self {
             client,
+            blockhash_cache: Arc::new(RwLock::new(None)),
+            pubsub: tokio::sync::Mutex::new(None),
             program_id,
-            payer,
+            payers: signers.into_iter().map(Payer::new).collect(),
+            next_payer: AtomicUsize::new(0),
+            airdrop_allowed: AtomicBool::new(false),
+            lookup_table_address,
+            lookup_table: RwLock::new(None),
+            explorer: Explorer::from_config(&config),
+            retry_budget,
+            jito,
+            submitter,
+            submitter_keypair,
             config,
         })
     }
 
+    /// Load the payer from, in order: `CHAIN_KEYPAIR_BASE58`, the keypair file, or a
+    /// freshly generated ephemeral keypair. Only the source and public key are logged.
+    fn load_payer(keypair_path: Option<&str>) -> BlockchainResult<Keypair> {
+        if let Ok(secret) = env::var(KEYPAIR_BASE58_VAR) {
+            if !secret.trim().is_empty() {
+                let payer = Self::parse_base58_keypair(&secret)
+                    .map_err(|e| BlockchainError::InvalidConfig(format!("Invalid {}: {}", KEYPAIR_BASE58_VAR, e)))?;
+                info!(payer = %payer.pubkey(), source = KEYPAIR_BASE58_VAR, "Loaded payer keypair");
+                return Ok(payer);
+            }
+        }
+
+        if let Some(keypair_path) = keypair_path {
+            debug!(path = %keypair_path, "Loading payer keypair");
+            let payer = Self::load_keypair(keypair_path)?;
+            info!(payer = %payer.pubkey(), source = "file", path = %keypair_path, "Loaded payer keypair");
+            return Ok(payer);
+        }
+
+        let payer = Keypair::new();
+        warn!(
+            payer = %payer.pubkey(),
+            "No payer keypair configured (set {} or CHAIN_KEYPAIR_PATH); generated an EPHEMERAL keypair \
+             that is lost on restart and must be funded before it can store anything",
+            KEYPAIR_BASE58_VAR
+        );
+        Ok(payer)
+    }
+
+    /// Load every keypair file listed in `SOLANA_PAYER_KEYPAIRS`, expanding directories to the
+    /// `*.json` files directly inside them. A keypair listed twice is only used once.
+    fn load_payer_pool(paths: &[String]) -> BlockchainResult<Vec<Keypair>> {
+        let mut payers: Vec<Keypair> = Vec::new();
+        for path in paths {
+            let files = if Path::new(path).is_dir() {
+                let mut files: Vec<PathBuf> = std::fs::read_dir(path)
+                    .map_err(|e| BlockchainError::InvalidConfig(format!("Failed to read payer directory {}: {}", path, e)))?
+                    .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                    .filter(|file| file.is_file() && file.extension().is_some_and(|extension| extension == "json"))
+                    .collect();
+                if files.is_empty() {
+                    return Err(BlockchainError::InvalidConfig(format!(
+                        "Payer directory {} contains no *.json keypair files",
+                        path
+                    )));
+                }
+                files.sort();
+                files
+            } else {
+                vec![PathBuf::from(path)]
+            };
+
+            for file in files {
+                let keypair = match Self::load_keypair(&file.to_string_lossy()) {
+                    Ok(keypair) => keypair,
+                    Err(BlockchainError::InvalidConfig(reason)) => {
+                        return Err(BlockchainError::InvalidConfig(format!("{}: {}", file.display(), reason)))
+                    }
+                    Err(e) => return Err(e),
+                };
+                if payers.iter().any(|payer| payer.pubkey() == keypair.pubkey()) {
+                    warn!(payer = %keypair.pubkey(), path = %file.display(), "Skipping duplicate payer keypair");
+                    continue;
+                }
+                payers.push(keypair);
+            }
+        }
+
+        let pubkeys: Vec<String> = payers.iter().map(|payer| payer.pubkey().to_string()).collect();
+        info!(payers = ?pubkeys, "Loaded payer pool");
+        Ok(payers)
+    }
+
+    /// Read a keypair file in either the Solana CLI JSON byte-array format or as a base58 string
     fn load_keypair(keypair_path: &str) -> BlockchainResult<Keypair> {
         let path = Path::new(keypair_path);
         if !path.exists() {
-            return Err(format!("Keypair file not found at: {}", keypair_path).into());
+            return Err(BlockchainError::InvalidConfig(format!("Keypair file not found at: {}", keypair_path)));
         }
 
-        let keypair_json = std::fs::read_to_string(path)
-            .map_err(|e| format!("Failed to read keypair file: {}", e))?;
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| BlockchainError::InvalidConfig(format!("Failed to read keypair file: {}", e)))?;
+        let contents = contents.trim();
+
+        if !contents.starts_with('[') {
+            return Self::parse_base58_keypair(contents)
+                .map_err(|e| BlockchainError::InvalidConfig(format!("Failed to parse base58 keypair file: {}", e)));
+        }
 
-        let keypair_bytes = serde_json::from_str::<Vec<u8>>(&keypair_json)
-            .map_err(|e| format!("Failed to parse keypair JSON: {}", e))?;
+        let keypair_bytes = serde_json::from_str::<Vec<u8>>(contents)
+            .map_err(|e| BlockchainError::InvalidConfig(format!("Failed to parse keypair JSON: {}", e)))?;
 
-        let keypair = Keypair::from_bytes(&keypair_bytes)
-            .map_err(|e| format!("Failed to parse keypair bytes: {}", e))?;
+        let keypair = Keypair::try_from(keypair_bytes.as_slice())
+            .map_err(|e| BlockchainError::InvalidConfig(format!("Failed to parse keypair bytes: {}", e)))?;
 
-        println!("🔑 Loaded existing keypair: {}", keypair.pubkey());
         Ok(keypair)
     }
 
+    /// Decode a base58 secret key; errors never echo the input
+    fn parse_base58_keypair(encoded: &str) -> Result<Keypair, String> {
+        let bytes = bs58::decode(encoded.trim())
+            .into_vec()
+            .map_err(|_| "not valid base58".to_string())?;
+        Keypair::try_from(bytes.as_slice()).map_err(|_| format!("expected a 64-byte secret key, got {} bytes", bytes.len()))
+    }
+
+    /// Next payer in turn, passing over any recently found unable to cover a store. When every
+    /// payer is short it is the next in turn anyway, so the store fails with its exact shortfall.
+    fn next_payer(&self) -> &Payer {
+        let start = self.next_payer.fetch_add(1, Ordering::Relaxed);
+        let count = self.payers.len();
+        (0..count)
+            .map(|offset| &self.payers[start.wrapping_add(offset) % count])
+            .find(|payer| !payer.recently_underfunded())
+            .unwrap_or(&self.payers[start % count])
+    }
+
+    /// Report the largest balance last seen across the payers on the `payer_balance` gauge
+    fn report_payer_balance(&self) {
+        let largest = self.payers.iter().filter_map(|payer| payer.lock().lamports).max();
+        if let Some(largest) = largest {
+            crate::metrics::metrics().payer_balance.set(i64::try_from(largest).unwrap_or(i64::MAX));
+        }
+    }
+
+    /// Allow airdrops only once the genesis hash shows the cluster is neither mainnet nor testnet
+    async fn check_airdrop_cluster(&self) -> BlockchainResult<()> {
+        let Some(threshold) = self.config.airdrop_below else {
+            return Ok(());
+        };
+        let genesis_hash = self.client.get_genesis_hash().await?.to_string();
+        if SOLANA_NON_DEV_GENESIS_HASHES.contains(&genesis_hash.as_str()) {
+            return Err(BlockchainError::InvalidConfig(format!(
+                "SOLANA_AIRDROP_BELOW is set but {} is not devnet or a local validator (genesis hash {})",
+                redacted_url(&self.config.network_url),
+                genesis_hash
+            )));
+        }
+        self.airdrop_allowed.store(true, Ordering::Release);
+        info!(threshold, lamports = self.config.airdrop_lamports, "Airdropping to payers that run low");
+        Ok(())
+    }
+
+    /// Airdrop to a payer that has fallen below `SOLANA_AIRDROP_BELOW` and wait for it to
+    /// confirm. Failures are only logged; the balance check that follows reports any shortfall.
+    async fn top_up(&self, payer: &Payer) {
+        let Some(threshold) = self.config.airdrop_below else {
+            return;
+        };
+        if !self.airdrop_allowed.load(Ordering::Acquire) {
+            return;
+        }
+
+        // Stores queued behind an airdrop see the new balance and skip their own
+        let _airdrop = payer.airdrop.lock().await;
+        if let Err(e) = self.airdrop_if_below(payer, threshold).await {
+            warn!(payer = %payer.pubkey(), error = %e, "Airdrop failed");
+        }
+    }
+
+    async fn airdrop_if_below(&self, payer: &Payer, threshold: u64) -> BlockchainResult<()> {
+        let balance = self.client.get_balance(&payer.pubkey()).await?;
+        payer.record_balance(balance, None);
+        if balance >= threshold {
+            return Ok(());
+        }
+
+        let lamports = self.config.airdrop_lamports;
+        info!(payer = %payer.pubkey(), balance, threshold, lamports, "Payer is low, requesting an airdrop");
+        let signature = self.client.request_airdrop(&payer.pubkey(), lamports).await?;
+        self.client
+            .poll_for_signature_with_commitment(&signature, self.client.commitment())
+            .await?;
+        info!(payer = %payer.pubkey(), signature = %signature, "Airdrop confirmed");
+        Ok(())
+    }
+
     async fn wait_for_connection(&self) -> BlockchainResult<()> {
-        println!("🔌 Connecting to Solana validator...");
-        for attempt in 1..=10 {
-            match self.client.get_health() {
-                Ok(_) => {
-                    println!("✅ Connected to Solana validator");
-                    return Ok(());
-                }
-                Err(e) => {
-                    println!("❌ Connection attempt {}/10 failed: {}", attempt, e);
-                    if attempt < 10 {
-                        tokio::time::sleep(Duration::from_secs(3)).await;
-                    }
+        info!(url = %redacted_url(&self.config.network_url), "Connecting to Solana validator");
+        self.config
+            .connect_retry
+            .connect(&self.config.network_url, || async { Ok(self.client.get_health().await?) })
+            .await?;
+        info!("Connected to Solana validator");
+        Ok(())
+    }
+
+    /// Refresh the cached blockhash in the background so stores skip that round trip
+    fn spawn_blockhash_refresh(&self) {
+        let client = Arc::clone(&self.client);
+        let cache = Arc::clone(&self.blockhash_cache);
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(BLOCKHASH_REFRESH_INTERVAL);
+            loop {
+                interval.tick().await;
+                if let Err(e) = Self::fetch_blockhash(&client, &cache).await {
+                    warn!(error = %e, "Background blockhash refresh failed");
                 }
             }
+        });
+    }
+
+    async fn fetch_blockhash(
+        client: &RpcClient,
+        cache: &RwLock<Option<CachedBlockhash>>,
+    ) -> Result<Hash, ClientError> {
+        let hash = client.get_latest_blockhash().await?;
+        *cache.write().unwrap_or_else(|e| e.into_inner()) = Some(CachedBlockhash {
+            hash,
+            fetched_at: Instant::now(),
+        });
+        trace!(blockhash = %hash, "Refreshed blockhash");
+        Ok(hash)
+    }
+
+    /// Cached blockhash when it is young enough to still land, otherwise a fresh one
+    async fn recent_blockhash(&self) -> Result<Hash, ClientError> {
+        let cached = self
+            .blockhash_cache
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+            .filter(|cached| cached.fetched_at.elapsed() <= BLOCKHASH_MAX_AGE)
+            .map(|cached| cached.hash);
+
+        match cached {
+            Some(hash) => Ok(hash),
+            None => {
+                debug!("Cached blockhash missing or stale, fetching");
+                Self::fetch_blockhash(&self.client, &self.blockhash_cache).await
+            }
+        }
+    }
+
+    /// Fetch the configured lookup table, rejecting accounts that are not an active table
+    async fn load_lookup_table(&self) -> BlockchainResult<()> {
+        let Some(address) = self.lookup_table_address else {
+            return Ok(());
+        };
+        let account = self
+            .client
+            .get_account_with_commitment(&address, self.client.commitment())
+            .await?
+            .value
+            .ok_or_else(|| BlockchainError::InvalidConfig(format!("Address lookup table {} does not exist", address)))?;
+        if account.owner != solana_address_lookup_table_interface::program::ID {
+            return Err(BlockchainError::InvalidConfig(format!(
+                "Account {} is owned by {}, not the address lookup table program",
+                address, account.owner
+            )));
+        }
+        let table = AddressLookupTable::deserialize(&account.data).map_err(|e| {
+            BlockchainError::InvalidConfig(format!("Failed to decode address lookup table {}: {}", address, e))
+        })?;
+        if table.meta.deactivation_slot != u64::MAX {
+            return Err(BlockchainError::InvalidConfig(format!(
+                "Address lookup table {} was deactivated in slot {}",
+                address, table.meta.deactivation_slot
+            )));
         }
-        Err("Failed to connect to Solana validator after 10 attempts".into())
+
+        info!(lookup_table = %address, addresses = table.addresses.len(), "Sending v0 transactions with address lookup table");
+        *self.lookup_table.write().unwrap_or_else(|e| e.into_inner()) = Some(AddressLookupTableAccount {
+            key: address,
+            addresses: table.addresses.to_vec(),
+        });
+        Ok(())
     }
 
-    async fn store_record_impl(&self, record: &ContentRecord) -> BlockchainResult<TransactionResult> {
-        // Wait a bit to ensure airdrop is confirmed
-        tokio::time::sleep(Duration::from_secs(2)).await;
+    /// Message carrying these instructions: v0, with every account the lookup table holds
+    /// referenced through it, once the table is loaded, and legacy otherwise
+    fn compile_message(&self, payer: &Pubkey, instructions: &[Instruction], blockhash: Hash) -> BlockchainResult<VersionedMessage> {
+        let lookup_table = self.lookup_table.read().unwrap_or_else(|e| e.into_inner());
+        match lookup_table.as_ref() {
+            Some(table) => v0::Message::try_compile(payer, instructions, std::slice::from_ref(table), blockhash)
+                .map(VersionedMessage::V0)
+                .map_err(|e| BlockchainError::Other(format!("Failed to compile v0 transaction: {}", e))),
+            None => Ok(VersionedMessage::Legacy(Message::new_with_blockhash(instructions, Some(payer), &blockhash))),
+        }
+    }
 
-        // Generate a unique keypair for this proof record
-        let proof_account = Keypair::new();
-        
+    /// Sign `message` with the payer's signer and the proof keypairs, checking the payer's
+    /// signature before anything is sent
+    async fn sign_transaction(
+        message: VersionedMessage,
+        payer: &dyn TransactionSigner,
+        proof_signers: &[&Keypair],
+    ) -> BlockchainResult<VersionedTransaction> {
+        let bytes = message.serialize();
+        let mut transaction = Self::unsigned_transaction(message);
+        let signer_keys = &transaction.message.static_account_keys()[..transaction.signatures.len()];
+        let position = |pubkey: &Pubkey| {
+            signer_keys.iter().position(|key| key == pubkey).ok_or_else(|| {
+                BlockchainError::Other(format!("Failed to sign transaction: {} is not one of its signers", pubkey))
+            })
+        };
+
+        let payer_pubkey = payer.pubkey();
+        let payer_position = position(&payer_pubkey)?;
+        let mut signatures = vec![(payer_position, payer.sign_message(&bytes).await?)];
+        if !signatures[0].1.verify(payer_pubkey.as_ref(), &bytes) {
+            return Err(BlockchainError::Other(format!(
+                "Failed to sign transaction: signer returned an invalid signature for {}",
+                payer_pubkey
+            )));
+        }
+        for keypair in proof_signers {
+            signatures.push((position(&keypair.pubkey())?, keypair.sign_message(&bytes)));
+        }
+        for (position, signature) in signatures {
+            transaction.signatures[position] = signature;
+        }
+        Ok(transaction)
+    }
+
+    /// Transaction with placeholder signatures, which serializes to the size of the signed one
+    fn unsigned_transaction(message: VersionedMessage) -> VersionedTransaction {
+        VersionedTransaction {
+            signatures: vec![Signature::default(); message.header().num_required_signatures as usize],
+            message,
+        }
+    }
+
+    /// Seed bytes for a UID, hashed when it exceeds the per-seed length limit
+    fn uid_seed(uid: &str) -> Vec<u8> {
+        let bytes = uid.as_bytes();
+        if bytes.len() <= MAX_SEED_LEN {
+            bytes.to_vec()
+        } else {
+            hash(bytes).to_bytes().to_vec()
+        }
+    }
+
+    /// Derive the PDA a record with this UID is stored at, with its bump and UID seed
+    pub fn find_proof_address(&self, uid: &str) -> BlockchainResult<(Pubkey, u8, Vec<u8>)> {
+        if uid.is_empty() {
+            return Err(BlockchainError::InvalidRecord("UID is required to derive a proof account".to_string()));
+        }
+
+        let uid_seed = Self::uid_seed(uid);
+        let (address, bump) = Pubkey::find_program_address(&[PROOF_SEED, &uid_seed], &self.program_id);
+        Ok((address, bump, uid_seed))
+    }
+
+    /// Instructions for a proof account generated as a fresh keypair and allocated by us
+    async fn keypair_instructions(
+        &self,
+        payer: &Pubkey,
+        record: &ContentRecord,
+        proof_account: &Keypair,
+    ) -> BlockchainResult<(Vec<Instruction>, u64)> {
         // Verify they're different
         if proof_account.pubkey() == self.program_id {
-            return Err("ERROR: Generated proof account matches program ID!".into());
+            return Err(BlockchainError::Other("Generated proof account matches program ID".to_string()));
         }
-        if proof_account.pubkey() == self.payer.pubkey() {  
-            return Err("ERROR: Generated proof account matches payer!".into());
+        if proof_account.pubkey() == *payer {
+            return Err(BlockchainError::Other("Generated proof account matches payer".to_string()));
         }
 
         // Create the instruction data
         let instruction_data = ProofInstruction::StoreProof {
             url: record.url.clone(),
-            content_hash: record.content_hash.clone(),
+            content_hash: record.stored_content_hash(),
             content_length: record.content_length,
+            timestamp: unix_now(),
+            submitter: self.submitter.clone(),
+            content_type: non_empty(&record.content_type),
+            storage_uri: non_empty(&record.storage_uri),
         };
 
         // Serialize the instruction using Borsh
         let data = instruction_data.try_to_vec()?;
 
-        // Calculate space needed for the account
-        let space = data.len() as u64;
-        let rent = self.client.get_minimum_balance_for_rent_exemption(space as usize)?;
+        // Size the account to the record the program writes into it, plus any configured headroom
+        let record_space = ProofRecord::space(record, self.submitter.as_deref());
+        let space = self.config.rent_strategy.space(record_space) as u64;
+        let rent = self.client.get_minimum_balance_for_rent_exemption(space as usize).await?;
 
         // Create account instruction
-        let create_account_ix = solana_sdk::system_instruction::create_account(
-            &self.payer.pubkey(),
+        let create_account_ix = system_instruction::create_account(
+            payer,
             &proof_account.pubkey(),
             rent,
             space,
             &self.program_id,
         );
-        println!("🏗️  Created create_account instruction");
-        println!("   From: {} (payer)", self.payer.pubkey());
-        println!("   To: {} (new account)", proof_account.pubkey());
-        println!("   Owner: {} (program)", self.program_id);
-        println!("   Lamports: {}", rent);
-        println!("   Space: {}", space);
+        debug!(
+            payer = %payer,
+            proof_account = %proof_account.pubkey(),
+            owner = %self.program_id,
+            lamports = rent,
+            space,
+            "Created create_account instruction"
+        );
+        trace!(data = ?data, "StoreProof instruction data");
 
         // Store proof instruction
-        let account_metas = vec![
-            AccountMeta::new(self.payer.pubkey(), true),     // Payer (signer)
+        let mut account_metas = vec![
+            AccountMeta::new(*payer, true),                 // Payer (signer)
             AccountMeta::new(proof_account.pubkey(), true), // Proof account (writable, signer)
             AccountMeta::new_readonly(system_program::ID, false), // System program
         ];
+        account_metas.extend(self.submitter_account());
         
         let store_proof_ix = Instruction::new_with_bytes(
             self.program_id,
@@ -158,45 +998,1743 @@ impl SolanaProvider {
             account_metas,
         );
 
-        // Get recent blockhash
-        let recent_blockhash = self.client.get_latest_blockhash()?;
+        Ok((vec![create_account_ix, store_proof_ix], rent))
+    }
+
+    /// Instructions for a proof account derived from the UID; the program allocates it
+    async fn pda_instructions(
+        &self,
+        payer: &Pubkey,
+        record: &ContentRecord,
+    ) -> BlockchainResult<(Pubkey, Vec<Instruction>, u64)> {
+        let (proof_address, bump, uid_seed) = self.find_proof_address(&record.uid)?;
 
-        // Create transaction with both instructions
-        let instructions = [create_account_ix, store_proof_ix];
-        let signers = [&self.payer, &proof_account];
-        
-        let transaction = Transaction::new_signed_with_payer(
-            &instructions,
-            Some(&self.payer.pubkey()),
-            &signers,
-            recent_blockhash,
+        if self.client.get_account_with_commitment(&proof_address, self.client.commitment()).await?.value.is_some() {
+            return Err(BlockchainError::AlreadyExists(format!(
+                "Proof account {} for UID {} is already initialized",
+                proof_address, record.uid
+            )));
+        }
+
+        let instruction_data = ProofInstruction::StoreProofWithSeed {
+            uid_seed,
+            bump,
+            url: record.url.clone(),
+            content_hash: record.stored_content_hash(),
+            content_length: record.content_length,
+            timestamp: unix_now(),
+            submitter: self.submitter.clone(),
+            content_type: non_empty(&record.content_type),
+            storage_uri: non_empty(&record.storage_uri),
+        };
+        let data = instruction_data.try_to_vec()?;
+
+        // The program sizes the PDA to the account layout and funds it from the payer
+        let space = ProofRecord::space(record, self.submitter.as_deref());
+        let rent = self.client.get_minimum_balance_for_rent_exemption(space).await?;
+        debug!(proof_account = %proof_address, bump, lamports = rent, space, "Using PDA proof account");
+        trace!(data = ?data, "StoreProofWithSeed instruction data");
+
+        let mut account_metas = vec![
+            AccountMeta::new(*payer, true),                 // Payer (signer)
+            AccountMeta::new(proof_address, false),          // Proof PDA (writable)
+            AccountMeta::new_readonly(system_program::ID, false), // System program
+        ];
+        account_metas.extend(self.submitter_account());
+
+        let store_proof_ix = Instruction::new_with_bytes(
+            self.program_id,
+            &data,
+            account_metas,
         );
 
-        // Send transaction with confirmation
-        let signature = self
+        Ok((proof_address, vec![store_proof_ix], rent))
+    }
+
+    /// The separate submitter, last among a store's or close's accounts and signing it so the
+    /// program can tell the submitter vouched for the proof; `None` when the payer submits
+    fn submitter_account(&self) -> Option<AccountMeta> {
+        self.submitter_keypair.as_ref().map(|keypair| AccountMeta::new_readonly(keypair.pubkey(), true))
+    }
+
+    /// Compute budget instructions to prepend; empty unless a fee or limit is configured
+    async fn compute_budget_instructions(&self, writable_accounts: &[Pubkey]) -> BlockchainResult<Vec<Instruction>> {
+        let mut instructions = Vec::new();
+
+        if let Some(limit) = self.config.compute_unit_limit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(limit));
+        }
+
+        let micro_lamports = match self.config.priority_fee {
+            None => 0,
+            Some(PriorityFee::Fixed(micro_lamports)) => micro_lamports,
+            Some(PriorityFee::Auto) => self.recent_priority_fee(writable_accounts).await?,
+        };
+        if micro_lamports > 0 {
+            debug!(micro_lamports, "Adding priority fee");
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(micro_lamports));
+        }
+
+        Ok(instructions)
+    }
+
+    /// Pick a percentile of the fees recently paid to write-lock these accounts
+    async fn recent_priority_fee(&self, writable_accounts: &[Pubkey]) -> BlockchainResult<u64> {
+        let mut fees: Vec<u64> = self
             .client
-            .send_and_confirm_transaction_with_spinner(&transaction)?;
+            .get_recent_prioritization_fees(writable_accounts)
+            .await?
+            .into_iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect();
+        if fees.is_empty() {
+            return Ok(0);
+        }
 
-        println!("✅ Solana transaction successful!");
-        println!("📄 Transaction signature: {}", signature);
-        println!("📄 Proof account: {}", proof_account.pubkey());
-        println!("🔗 UID: {}", record.uid);
+        fees.sort_unstable();
+        Ok(fees[(fees.len() - 1) * AUTO_PRIORITY_FEE_PERCENTILE / 100])
+    }
 
-        Ok(TransactionResult {
-            transaction_id: signature.to_string(),
-        })
+    /// Proof account, instructions and (in keypair mode) extra signer for one record
+    async fn prepare_record(&self, payer: &Pubkey, record: &ContentRecord) -> BlockchainResult<PreparedRecord> {
+        let mut prepared = if self.config.use_pda {
+            let (proof_address, instructions, rent_lamports) = self.pda_instructions(payer, record).await?;
+            PreparedRecord {
+                proof_address,
+                instructions,
+                proof_keypair: None,
+                rent_lamports,
+            }
+        } else {
+            // Generate a unique keypair for this proof record
+            let proof_keypair = Keypair::new();
+            let (instructions, rent_lamports) = self.keypair_instructions(payer, record, &proof_keypair).await?;
+            PreparedRecord {
+                proof_address: proof_keypair.pubkey(),
+                instructions,
+                proof_keypair: Some(proof_keypair),
+                rent_lamports,
+            }
+        };
+
+        if self.config.memo && !record.uid.is_empty() {
+            prepared.instructions.push(Self::memo_instruction(&record.uid));
+        }
+        Ok(prepared)
     }
-}
 
-#[async_trait]
-impl BlockchainProvider for SolanaProvider {
-    async fn store_record(&self, record: &ContentRecord) -> BlockchainResult<TransactionResult> {
-        self.store_record_impl(record).await
+    /// Prepare a record to be paid for by `payer`, with the compute budget instructions to prepend
+    async fn prepare_store(&self, payer: &Payer, record: &ContentRecord) -> BlockchainResult<(PreparedRecord, Vec<Instruction>)> {
+        let prepared = self.prepare_record(&payer.pubkey(), record).await?;
+        let budget_instructions = self.compute_budget_instructions(&[payer.pubkey(), prepared.proof_address]).await?;
+        Ok((prepared, budget_instructions))
     }
-}
 
-impl SolanaProvider {
-    pub async fn initialize(&self) -> BlockchainResult<()> {
-        self.wait_for_connection().await
+    /// SPL Memo instruction naming the record, so explorers show the UID without decoding proof data.
+    /// It requires no signers, which the memo program accepts.
+    fn memo_instruction(uid: &str) -> Instruction {
+        Instruction::new_with_bytes(MEMO_PROGRAM_ID, format!("{}{}", MEMO_PREFIX, uid).as_bytes(), Vec::new())
+    }
+
+    /// Serialized size of a transaction carrying these instructions once signed
+    fn transaction_size(&self, payer: &Pubkey, instructions: &[Instruction]) -> usize {
+        self.compile_message(payer, instructions, Hash::default())
+            .ok()
+            .and_then(|message| bincode::serialized_size(&Self::unsigned_transaction(message)).ok())
+            .map_or(usize::MAX, |size| size as usize)
+    }
+
+    /// Rent for the proof accounts and the fee (including any priority fee) for one
+    /// transaction carrying these records
+    async fn required_lamports(
+        &self,
+        payer: &Pubkey,
+        budget_instructions: &[Instruction],
+        prepared: &[PreparedRecord],
+    ) -> BlockchainResult<(u64, u64)> {
+        let mut instructions = budget_instructions.to_vec();
+        for record in prepared {
+            instructions.extend(record.instructions.iter().cloned());
+        }
+        let mut message = Message::new(&instructions, Some(payer));
+        message.recent_blockhash = self.recent_blockhash().await?;
+
+        // A Jito tip is paid on top of the fee whenever the bundle lands
+        let tip = if self.jito.is_some() { self.config.jito_tip_lamports } else { 0 };
+        let fee = self.client.get_fee_for_message(&message).await?.saturating_add(tip);
+        let rent = prepared.iter().map(|record| record.rent_lamports).sum();
+        Ok((rent, fee))
+    }
+
+    /// Fail early with the exact shortfall when the payer cannot cover the rent and fee;
+    /// otherwise the runtime rejects the transaction with a much less helpful error
+    async fn ensure_sufficient_balance(
+        &self,
+        payer: &Payer,
+        budget_instructions: &[Instruction],
+        prepared: &[PreparedRecord],
+    ) -> BlockchainResult<()> {
+        self.top_up(payer).await;
+        let (rent, fee) = self.required_lamports(&payer.pubkey(), budget_instructions, prepared).await?;
+        let required = rent.saturating_add(fee);
+        let balance = self.client.get_balance(&payer.pubkey()).await?;
+        debug!(payer = %payer.pubkey(), balance, rent, fee, "Checked payer balance");
+        payer.record_balance(balance, Some(required));
+        self.report_payer_balance();
+
+        if balance < required {
+            let shortfall = required - balance;
+            return Err(BlockchainError::InsufficientFunds(format!(
+                "Payer {} has {} lamports but needs {} ({} rent + {} fee); short by {} lamports ({:.9} SOL)",
+                payer.pubkey(),
+                balance,
+                required,
+                rent,
+                fee,
+                shortfall,
+                shortfall as f64 / LAMPORTS_PER_SOL as f64
+            )));
+        }
+
+        Ok(())
     }
-} 
\ No newline at end of file
+
+    /// Dry-run the transaction so program rejections come back with their logs
+    /// instead of after a full confirmation attempt
+    async fn simulate(&self, payer: &Pubkey, instructions: &[Instruction]) -> BlockchainResult<Vec<String>> {
+        // Unsigned is fine: signatures are not verified and the node supplies the blockhash
+        let transaction = Self::unsigned_transaction(self.compile_message(payer, instructions, Hash::default())?);
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: Some(self.client.commitment()),
+            ..Default::default()
+        };
+
+        let result = self.client.simulate_transaction_with_config(&transaction, config).await?.value;
+        let logs = result.logs.unwrap_or_default();
+        if let Some(error) = result.err {
+            warn!(error = %error, logs = ?logs, "Transaction simulation failed");
+            let error = match error {
+                TransactionError::InstructionError(instruction, InstructionError::Custom(code)) => format!(
+                    "program rejected instruction {}: {}",
+                    instruction,
+                    crate::blockchain::program_error_detail(&code, &self.program_error_message(code))
+                ),
+                error => error.to_string(),
+            };
+            return Err(BlockchainError::SimulationFailed { error, logs });
+        }
+
+        debug!(units_consumed = ?result.units_consumed, "Transaction simulation succeeded");
+        trace!(logs = ?logs, "Simulation program logs");
+        Ok(logs)
+    }
+
+    /// Simulate the transaction these records would be sent in, without signing or sending it
+    async fn dry_run_prepared(
+        &self,
+        payer: &Pubkey,
+        budget_instructions: &[Instruction],
+        prepared: &[PreparedRecord],
+        account_address: Option<String>,
+    ) -> BlockchainResult<TransactionResult> {
+        let mut instructions = budget_instructions.to_vec();
+        for record in prepared {
+            instructions.extend(record.instructions.iter().cloned());
+        }
+        let logs = self.simulate(payer, &instructions).await?;
+        info!(records = prepared.len(), "Dry run simulated, nothing submitted");
+
+        Ok(TransactionResult {
+            transaction_id: String::new(),
+            account_address,
+            block_height: None,
+            block_time: None,
+            confirmation_time: None,
+            finalization_time: None,
+            simulation_logs: Some(logs),
+            explorer_url: None,
+        })
+    }
+
+    /// Prepend the compute budget instructions, sign with the payer and every proof keypair, and send,
+    /// waiting for `commitment` unless `wait_for_confirmation` is false
+    async fn submit_prepared(
+        &self,
+        payer: &Payer,
+        budget_instructions: &[Instruction],
+        prepared: &[PreparedRecord],
+        commitment: CommitmentConfig,
+        wait_for_confirmation: bool,
+    ) -> BlockchainResult<(Signature, Duration)> {
+        let mut instructions = budget_instructions.to_vec();
+        let mut signers = Vec::new();
+        for record in prepared {
+            instructions.extend(record.instructions.iter().cloned());
+            signers.extend(record.proof_keypair.as_ref());
+        }
+        signers.extend(self.submitter_keypair.as_ref());
+
+        if self.config.simulate_transactions {
+            self.simulate(&payer.pubkey(), &instructions).await?;
+        }
+
+        self.send_with_retry(payer, &instructions, &signers, commitment, wait_for_confirmation)
+            .await
+            .map_err(|e| self.with_program_error_message(e))
+    }
+
+    /// Attach the configured message to a program error
+    fn with_program_error_message(&self, error: BlockchainError) -> BlockchainError {
+        match error {
+            BlockchainError::ProgramError { instruction, code, .. } => BlockchainError::ProgramError {
+                instruction,
+                code,
+                message: self.program_error_message(code),
+            },
+            e => e,
+        }
+    }
+
+    /// Configured message for one of the program's custom error codes
+    fn program_error_message(&self, code: u32) -> Option<String> {
+        self.config.program_errors.message(code).map(str::to_string)
+    }
+
+    async fn store_record_impl(
+        &self,
+        record: &ContentRecord,
+        options: &StoreOptions,
+    ) -> BlockchainResult<TransactionResult> {
+        let commitment = options.commitment.map(commitment_config).unwrap_or_else(|| self.client.commitment());
+
+        let mut payer = self.next_payer();
+        let (mut prepared, mut budget_instructions) = self.prepare_store(payer, record).await?;
+
+        if options.dry_run || self.config.dry_run {
+            let proof_address = prepared.proof_address.to_string();
+            return self
+                .dry_run_prepared(&payer.pubkey(), &budget_instructions, &[prepared], Some(proof_address))
+                .await;
+        }
+
+        // Move on through the pool while payers turn out unable to cover the store
+        let mut candidates = self.payers.len();
+        loop {
+            match self.ensure_sufficient_balance(payer, &budget_instructions, std::slice::from_ref(&prepared)).await {
+                Ok(()) => break,
+                Err(BlockchainError::InsufficientFunds(reason)) if candidates > 1 => {
+                    candidates -= 1;
+                    payer = self.next_payer();
+                    debug!(reason = %reason, next_payer = %payer.pubkey(), "Payer is underfunded, trying the next one");
+                    (prepared, budget_instructions) = self.prepare_store(payer, record).await?;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        let proof_address = prepared.proof_address;
+
+        if options.fire_and_forget {
+            let (signature, _) = self
+                .submit_prepared(payer, &budget_instructions, &[prepared], commitment, false)
+                .await?;
+            info!(
+                signature = %signature,
+                proof_account = %proof_address,
+                uid = %record.uid,
+                "Solana transaction submitted without waiting for confirmation"
+            );
+            return Ok(TransactionResult {
+                transaction_id: signature.to_string(),
+                account_address: Some(proof_address.to_string()),
+                block_height: None,
+                block_time: None,
+                confirmation_time: None,
+                finalization_time: None,
+                simulation_logs: None,
+                explorer_url: self.explorer.as_ref().map(|explorer| explorer.transaction_url(&signature.to_string())),
+            });
+        }
+
+        // Send transaction with confirmation
+        let (signature, confirmation_time) = self
+            .submit_prepared(payer, &budget_instructions, &[prepared], commitment, true)
+            .await?;
+
+        info!(
+            signature = %signature,
+            commitment = ?commitment.commitment,
+            proof_account = %proof_address,
+            uid = %record.uid,
+            program_id = %self.program_id,
+            confirmation_ms = confirmation_time.as_millis() as u64,
+            "Solana transaction confirmed"
+        );
+
+        let finalization_time = self.await_finalized(&signature, commitment, confirmation_time).await?;
+        let (slot, block_time) = self.lookup_inclusion(&signature).await;
+
+        Ok(TransactionResult {
+            transaction_id: signature.to_string(),
+            account_address: Some(proof_address.to_string()),
+            block_height: slot,
+            block_time,
+            confirmation_time: Some(confirmation_time),
+            finalization_time,
+            simulation_logs: None,
+            explorer_url: self.explorer.as_ref().map(|explorer| explorer.transaction_url(&signature.to_string())),
+        })
+    }
+
+    async fn store_batch_impl(&self, records: &[ContentRecord]) -> Vec<BatchChunkResult> {
+        let mut chunks = Vec::new();
+
+        // One payer covers the whole batch, since its records are prepared before they are chunked
+        let payer = self.next_payer();
+        let budget_instructions = match self.compute_budget_instructions(&[payer.pubkey()]).await {
+            Ok(instructions) => instructions,
+            Err(e) => {
+                return vec![BatchChunkResult {
+                    record_indices: (0..records.len()).collect(),
+                    account_addresses: Vec::new(),
+                    result: Err(e),
+                }]
+            }
+        };
+
+        // Greedily fill each transaction up to the configured size and record limits
+        let mut pending: Vec<(usize, PreparedRecord)> = Vec::new();
+        for (index, record) in records.iter().enumerate() {
+            let prepared = match self.prepare_record(&payer.pubkey(), record).await {
+                Ok(prepared) => prepared,
+                Err(e) => {
+                    chunks.push(BatchChunkResult {
+                        record_indices: vec![index],
+                        account_addresses: Vec::new(),
+                        result: Err(e),
+                    });
+                    continue;
+                }
+            };
+
+            let mut candidate = budget_instructions.clone();
+            for (_, queued) in &pending {
+                candidate.extend(queued.instructions.iter().cloned());
+            }
+            candidate.extend(prepared.instructions.iter().cloned());
+            let fits = pending.len() < self.config.max_batch_records
+                && self.transaction_size(&payer.pubkey(), &candidate) <= self.config.max_batch_bytes;
+
+            if !fits && !pending.is_empty() {
+                chunks.push(self.submit_chunk(payer, &budget_instructions, std::mem::take(&mut pending)).await);
+            }
+            pending.push((index, prepared));
+        }
+        if !pending.is_empty() {
+            chunks.push(self.submit_chunk(payer, &budget_instructions, pending).await);
+        }
+
+        chunks.sort_by_key(|chunk| chunk.record_indices.first().copied());
+        chunks
+    }
+
+    async fn submit_chunk(
+        &self,
+        payer: &Payer,
+        budget_instructions: &[Instruction],
+        chunk: Vec<(usize, PreparedRecord)>,
+    ) -> BatchChunkResult {
+        let (record_indices, prepared): (Vec<usize>, Vec<PreparedRecord>) = chunk.into_iter().unzip();
+        let account_addresses = prepared.iter().map(|record| record.proof_address.to_string()).collect();
+
+        let mut instructions = budget_instructions.to_vec();
+        for record in &prepared {
+            instructions.extend(record.instructions.iter().cloned());
+        }
+        if self.transaction_size(&payer.pubkey(), &instructions) > MAX_TRANSACTION_SIZE {
+            return BatchChunkResult {
+                record_indices,
+                account_addresses,
+                result: Err(BlockchainError::InvalidRecord(format!(
+                    "Record does not fit in a single transaction ({} byte limit)",
+                    MAX_TRANSACTION_SIZE
+                ))),
+            };
+        }
+
+        if self.config.dry_run {
+            return BatchChunkResult {
+                result: self.dry_run_prepared(&payer.pubkey(), budget_instructions, &prepared, None).await,
+                record_indices,
+                account_addresses,
+            };
+        }
+
+        if let Err(e) = self.ensure_sufficient_balance(payer, budget_instructions, &prepared).await {
+            return BatchChunkResult {
+                record_indices,
+                account_addresses,
+                result: Err(e),
+            };
+        }
+
+        let commitment = self.client.commitment();
+        let submitted = match self.submit_prepared(payer, budget_instructions, &prepared, commitment, true).await {
+            Ok((signature, confirmation_time)) => self
+                .await_finalized(&signature, commitment, confirmation_time)
+                .await
+                .map(|finalization_time| (signature, confirmation_time, finalization_time)),
+            Err(e) => Err(e),
+        };
+        let result = match submitted {
+            Ok((signature, confirmation_time, finalization_time)) => {
+                info!(
+                    signature = %signature,
+                    records = prepared.len(),
+                    program_id = %self.program_id,
+                    confirmation_ms = confirmation_time.as_millis() as u64,
+                    "Solana batch transaction confirmed"
+                );
+                let (slot, block_time) = self.lookup_inclusion(&signature).await;
+                Ok(TransactionResult {
+                    transaction_id: signature.to_string(),
+                    account_address: None,
+                    block_height: slot,
+                    block_time,
+                    confirmation_time: Some(confirmation_time),
+                    finalization_time,
+                    simulation_logs: None,
+                    explorer_url: self.explorer.as_ref().map(|explorer| explorer.transaction_url(&signature.to_string())),
+                })
+            }
+            Err(e) => {
+                warn!(records = prepared.len(), error = %e, "Solana batch transaction failed");
+                Err(e)
+            }
+        };
+
+        BatchChunkResult {
+            record_indices,
+            account_addresses,
+            result,
+        }
+    }
+
+    /// Sign and send the transaction, backing off and re-signing against a fresh blockhash
+    /// after each retryable failure. A transaction whose blockhash expired is resent at once,
+    /// up to `max_blockhash_resends` times on top of the send attempts. Waiting for
+    /// confirmation, resends included, gives up after `confirmation_timeout` with the last
+    /// signature sent. Returns the time since the first send, which is recorded in
+    /// `confirmation_seconds` when waited on.
+    async fn send_with_retry(
+        &self,
+        payer: &Payer,
+        instructions: &[Instruction],
+        proof_signers: &[&Keypair],
+        commitment: CommitmentConfig,
+        wait_for_confirmation: bool,
+    ) -> BlockchainResult<(Signature, Duration)> {
+        let max_attempts = self.config.max_send_attempts.max(1);
+        let mut backoff = INITIAL_SEND_BACKOFF;
+        let mut attempt = 1;
+        let deadline = tokio::time::Instant::now() + self.config.confirmation_timeout;
+        // Set once the first transaction is signed, after its blockhash was fetched
+        let mut first_sent_at = None;
+        // Cleared once a bundle is refused or does not land; later attempts go through the RPC node
+        let mut bundles_allowed = self.jito.is_some();
+        // Resends after an expired blockhash, counted apart from `attempt`
+        let mut blockhash_resends = 0;
+        let mut resending = false;
+        let record_confirmation = |outcome, first_sent_at: Instant| {
+            if wait_for_confirmation {
+                crate::metrics::metrics().record_confirmation(commitment_label(commitment), outcome, first_sent_at.elapsed());
+            }
+        };
+
+        loop {
+            let mut via_bundle = false;
+            // The cache only holds blockhashes at the client's default commitment, so any other
+            // commitment fetches its own. A resend after expiry bypasses it too and refreshes it,
+            // since a stale blockhash will never confirm.
+            let blockhash = if commitment != self.client.commitment() {
+                self.client
+                    .get_latest_blockhash_with_commitment(commitment)
+                    .await
+                    .map(|(hash, _)| hash)
+            } else if !resending {
+                self.recent_blockhash().await
+            } else {
+                Self::fetch_blockhash(&self.client, &self.blockhash_cache).await
+            };
+            let result = match blockhash {
+                Ok(recent_blockhash) => {
+                    let bundled = match &self.jito {
+                        Some(jito) if bundles_allowed => {
+                            self.send_bundle(jito, payer, instructions, proof_signers, recent_blockhash).await?
+                        }
+                        _ => None,
+                    };
+                    via_bundle = bundled.is_some();
+                    bundles_allowed &= via_bundle;
+                    let transaction = match bundled {
+                        Some(transaction) => transaction,
+                        None => {
+                            let message = self.compile_message(&payer.pubkey(), instructions, recent_blockhash)?;
+                            Self::sign_transaction(message, payer.signer.as_ref(), proof_signers).await?
+                        }
+                    };
+                    let first_sent_at = *first_sent_at.get_or_insert_with(Instant::now);
+                    if wait_for_confirmation {
+                        let confirmation = async {
+                            if via_bundle {
+                                let signature = transaction.signatures[0];
+                                self.poll_confirmation(&signature, recent_blockhash, commitment).await.map(|()| signature)
+                            } else {
+                                self.send_and_confirm(&transaction, recent_blockhash, commitment).await
+                            }
+                        };
+                        match tokio::time::timeout_at(deadline, confirmation).await {
+                            Ok(result) => result,
+                            Err(_) => {
+                                let signature = transaction.signatures[0];
+                                warn!(signature = %signature, timeout_secs = self.config.confirmation_timeout.as_secs(), "Gave up waiting for confirmation");
+                                record_confirmation(ConfirmationOutcome::TimedOut, first_sent_at);
+                                return Err(BlockchainError::ConfirmationPending {
+                                    transaction_id: signature.to_string(),
+                                    timeout: self.config.confirmation_timeout,
+                                });
+                            }
+                        }
+                    } else if via_bundle {
+                        Ok(transaction.signatures[0])
+                    } else {
+                        self.send(&transaction, commitment).await
+                    }
+                }
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(signature) => {
+                    let first_sent_at = first_sent_at.unwrap_or_else(Instant::now);
+                    record_confirmation(ConfirmationOutcome::Confirmed, first_sent_at);
+                    return Ok((signature, first_sent_at.elapsed()));
+                }
+                // Expiry says nothing about the node's health, so the resend goes out at once,
+                // outside the send attempts and the retry budget
+                Err(e) if Self::is_blockhash_expired(&e)
+                    && blockhash_resends < self.config.max_blockhash_resends
+                    && !(wait_for_confirmation && tokio::time::Instant::now() >= deadline) =>
+                {
+                    if via_bundle {
+                        warn!(error = %e, "Jito bundle did not land, resending through the RPC node");
+                        bundles_allowed = false;
+                    }
+                    blockhash_resends += 1;
+                    crate::metrics::metrics().blockhash_resends.inc();
+                    warn!(
+                        resend = blockhash_resends,
+                        max_resends = self.config.max_blockhash_resends,
+                        error = %e,
+                        "Blockhash expired before the transaction landed, resending with a fresh one"
+                    );
+                    resending = true;
+                }
+                // A resend after the deadline could only time out at once
+                Err(e) if attempt < max_attempts
+                    && Self::is_retryable(&e)
+                    && !(wait_for_confirmation && tokio::time::Instant::now() + backoff >= deadline) =>
+                {
+                    // Resending into a failing node across every request at once would only deepen the outage
+                    if !self.retry_budget.try_acquire() {
+                        warn!(attempt, error = %e, "Send attempt failed and the retry budget is spent, giving up");
+                        return Err(BlockchainError::RpcUnavailable(format!("retry budget exhausted; last error: {}", e)));
+                    }
+                    if via_bundle {
+                        warn!(attempt, error = %e, "Jito bundle did not land, resending through the RPC node");
+                        bundles_allowed = false;
+                    }
+                    warn!(
+                        attempt,
+                        max_attempts,
+                        backoff_ms = backoff.as_millis() as u64,
+                        error = %e,
+                        "Send attempt failed, retrying"
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                    resending = true;
+                }
+                Err(e) => {
+                    // Only outcomes of a sent transaction; RPC failures say nothing about latency
+                    let outcome = if e.get_transaction_error().is_some() {
+                        Some(ConfirmationOutcome::Failed)
+                    } else if Self::is_blockhash_expired(&e) {
+                        Some(ConfirmationOutcome::Expired)
+                    } else {
+                        None
+                    };
+                    if let (Some(outcome), Some(first_sent_at)) = (outcome, first_sent_at) {
+                        record_confirmation(outcome, first_sent_at);
+                    }
+                    return Err(e.into());
+                }
+            }
+        }
+    }
+
+    /// Sign the instructions with a tip appended and submit them to the block engine as a
+    /// bundle. Returns the signed transaction once the bundle is accepted, or `None`, after
+    /// logging why, when it should go through the RPC node instead.
+    async fn send_bundle(
+        &self,
+        jito: &JitoClient,
+        payer: &Payer,
+        instructions: &[Instruction],
+        proof_signers: &[&Keypair],
+        recent_blockhash: Hash,
+    ) -> BlockchainResult<Option<VersionedTransaction>> {
+        let tip_account = match jito.tip_account().await {
+            Ok(tip_account) => tip_account,
+            Err(e) => {
+                warn!(error = %e, "Failed to fetch Jito tip accounts, sending through the RPC node");
+                return Ok(None);
+            }
+        };
+        let mut tipped = instructions.to_vec();
+        tipped.push(system_instruction::transfer(&payer.pubkey(), &tip_account, self.config.jito_tip_lamports));
+        if self.transaction_size(&payer.pubkey(), &tipped) > MAX_TRANSACTION_SIZE {
+            debug!("Transaction has no room for a Jito tip, sending through the RPC node");
+            return Ok(None);
+        }
+
+        let message = self.compile_message(&payer.pubkey(), &tipped, recent_blockhash)?;
+        let transaction = Self::sign_transaction(message, payer.signer.as_ref(), proof_signers).await?;
+        match jito.send_bundle(&transaction).await {
+            Ok(bundle_id) => {
+                debug!(bundle_id = %bundle_id, signature = %transaction.signatures[0], tip_lamports = self.config.jito_tip_lamports, "Jito bundle accepted");
+                Ok(Some(transaction))
+            }
+            Err(e) => {
+                warn!(error = %e, "Jito bundle was refused, sending through the RPC node");
+                Ok(None)
+            }
+        }
+    }
+
+    /// With `wait_for_finalized`, poll a transaction that reached `commitment` until it is
+    /// finalized too, returning the time since submission. Gives up with `ConfirmationPending`
+    /// after `finalization_timeout`, since the transaction may still finalize later.
+    async fn await_finalized(
+        &self,
+        signature: &Signature,
+        commitment: CommitmentConfig,
+        confirmation_time: Duration,
+    ) -> BlockchainResult<Option<Duration>> {
+        if !self.config.wait_for_finalized || commitment.is_finalized() {
+            return Ok(None);
+        }
+        let started = Instant::now();
+        let finalized = async {
+            loop {
+                match self.client.get_signature_status_with_commitment(signature, CommitmentConfig::finalized()).await? {
+                    Some(result) => return result.map_err(ClientError::from),
+                    None => tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await,
+                }
+            }
+        };
+        match tokio::time::timeout(self.config.finalization_timeout, finalized).await {
+            Ok(result) => result?,
+            Err(_) => {
+                warn!(signature = %signature, timeout_secs = self.config.finalization_timeout.as_secs(), "Gave up waiting for finalization");
+                return Err(BlockchainError::ConfirmationPending {
+                    transaction_id: signature.to_string(),
+                    timeout: self.config.finalization_timeout,
+                });
+            }
+        }
+        let finalization_time = confirmation_time + started.elapsed();
+        info!(signature = %signature, finalization_ms = finalization_time.as_millis() as u64, "Solana transaction finalized");
+        Ok(Some(finalization_time))
+    }
+
+    /// Send once, returning as soon as the node accepts the transaction.
+    /// With `skip_preflight` program errors only surface once the transaction executes.
+    async fn send(&self, transaction: &VersionedTransaction, commitment: CommitmentConfig) -> Result<Signature, ClientError> {
+        let config = RpcSendTransactionConfig {
+            skip_preflight: self.config.skip_preflight,
+            preflight_commitment: Some(commitment.commitment),
+            max_retries: self.config.max_rpc_retries,
+            ..Default::default()
+        };
+        self.client.send_transaction_with_config(transaction, config).await
+    }
+
+    /// Send once and wait until the transaction reaches `commitment` or its blockhash expires,
+    /// through a signature subscription when `ws_url` is set and by polling otherwise
+    async fn send_and_confirm(
+        &self,
+        transaction: &VersionedTransaction,
+        recent_blockhash: Hash,
+        commitment: CommitmentConfig,
+    ) -> Result<Signature, ClientError> {
+        if let Some(ws_url) = self.config.ws_url.as_deref() {
+            match self.pubsub_client(ws_url).await {
+                Ok(pubsub) => return self.send_and_await_notification(&pubsub, transaction, recent_blockhash, commitment).await,
+                Err(e) => warn!(error = %e, "Solana WebSocket unavailable, confirming by polling"),
+            }
+        }
+
+        let signature = self.send(transaction, commitment).await?;
+        self.poll_confirmation(&signature, recent_blockhash, commitment).await?;
+        Ok(signature)
+    }
+
+    /// The shared WebSocket client, connecting it if there is none
+    async fn pubsub_client(&self, ws_url: &str) -> Result<Arc<PubsubClient>, PubsubClientError> {
+        let mut pubsub = self.pubsub.lock().await;
+        if let Some(client) = pubsub.as_ref() {
+            return Ok(Arc::clone(client));
+        }
+        let client = Arc::new(PubsubClient::new(ws_url).await?);
+        info!(url = %redacted_url(ws_url), "Connected to Solana WebSocket");
+        *pubsub = Some(Arc::clone(&client));
+        Ok(client)
+    }
+
+    /// Subscribe to the signature before sending, so a fast confirmation cannot be missed, then
+    /// wait for the notification. Falls back to polling if the subscription fails or drops.
+    async fn send_and_await_notification(
+        &self,
+        pubsub: &PubsubClient,
+        transaction: &VersionedTransaction,
+        recent_blockhash: Hash,
+        commitment: CommitmentConfig,
+    ) -> Result<Signature, ClientError> {
+        let signature = transaction.signatures[0];
+        let config = RpcSignatureSubscribeConfig {
+            commitment: Some(commitment),
+            enable_received_notification: Some(false),
+        };
+        let subscription = pubsub.signature_subscribe(&signature, Some(config)).await;
+        let (mut notifications, unsubscribe) = match subscription {
+            Ok(subscription) => subscription,
+            Err(e) => {
+                warn!(error = %e, "Signature subscription failed, confirming by polling");
+                *self.pubsub.lock().await = None;
+                self.send(transaction, commitment).await?;
+                self.poll_confirmation(&signature, recent_blockhash, commitment).await?;
+                return Ok(signature);
+            }
+        };
+
+        let outcome = match self.send(transaction, commitment).await {
+            Ok(_) => {
+                self.next_signature_notification(&mut notifications, &signature, recent_blockhash, commitment)
+                    .await
+            }
+            Err(e) => Some(Err(e)),
+        };
+        drop(notifications);
+        unsubscribe().await;
+
+        match outcome {
+            Some(result) => result.map(|()| signature),
+            None => {
+                warn!(signature = %signature, "Solana WebSocket closed while confirming, falling back to polling");
+                *self.pubsub.lock().await = None;
+                self.poll_confirmation(&signature, recent_blockhash, commitment).await?;
+                Ok(signature)
+            }
+        }
+    }
+
+    /// Wait for the subscription to report the transaction, checking between waits that its
+    /// blockhash has not expired. `None` means the WebSocket closed first.
+    async fn next_signature_notification(
+        &self,
+        notifications: &mut BoxStream<'_, RpcResponse<RpcSignatureResult>>,
+        signature: &Signature,
+        recent_blockhash: Hash,
+        commitment: CommitmentConfig,
+    ) -> Option<Result<(), ClientError>> {
+        loop {
+            match tokio::time::timeout(SUBSCRIPTION_EXPIRY_CHECK_INTERVAL, notifications.next()).await {
+                Ok(Some(notification)) => match notification.value {
+                    RpcSignatureResult::ProcessedSignature(result) => {
+                        return Some(result.err.map_or(Ok(()), |e| Err(e.into())));
+                    }
+                    RpcSignatureResult::ReceivedSignature(_) => continue,
+                },
+                Ok(None) => return None,
+                Err(_) => match self
+                    .client
+                    .is_blockhash_valid(&recent_blockhash, CommitmentConfig::processed())
+                    .await
+                {
+                    Ok(true) => continue,
+                    Ok(false) => return Some(Err(Self::blockhash_expired(signature, commitment))),
+                    Err(e) => return Some(Err(e)),
+                },
+            }
+        }
+    }
+
+    /// Poll the signature status until it reaches `commitment` or the blockhash expires
+    async fn poll_confirmation(
+        &self,
+        signature: &Signature,
+        recent_blockhash: Hash,
+        commitment: CommitmentConfig,
+    ) -> Result<(), ClientError> {
+        loop {
+            match self.client.get_signature_status_with_commitment(signature, commitment).await? {
+                Some(Ok(())) => return Ok(()),
+                Some(Err(e)) => return Err(e.into()),
+                None => {
+                    if !self
+                        .client
+                        .is_blockhash_valid(&recent_blockhash, CommitmentConfig::processed())
+                        .await?
+                    {
+                        return Err(Self::blockhash_expired(signature, commitment));
+                    }
+                    tokio::time::sleep(CONFIRMATION_POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    /// Error for a transaction that can no longer land, told apart from the SDK's own
+    /// client-side errors by `BLOCKHASH_EXPIRED`
+    fn blockhash_expired(signature: &Signature, commitment: CommitmentConfig) -> ClientError {
+        ClientErrorKind::Custom(format!(
+            "{}: transaction {} was not {:?} in time",
+            BLOCKHASH_EXPIRED, signature, commitment.commitment
+        ))
+        .into()
+    }
+
+    /// The transaction can no longer land on its blockhash: the node no longer knows it, or
+    /// it expired while confirmation was awaited. Re-signing against a fresh one fixes both.
+    fn is_blockhash_expired(error: &ClientError) -> bool {
+        matches!(error.get_transaction_error(), Some(TransactionError::BlockhashNotFound)) || is_expiry_sentinel(error)
+    }
+
+    /// Transient network failures are worth retrying; anything the runtime rejected
+    /// (insufficient funds, invalid instruction, ...) is not. Expired blockhashes are
+    /// resent separately, see `is_blockhash_expired`.
+    fn is_retryable(error: &ClientError) -> bool {
+        matches!(
+            error.kind(),
+            ClientErrorKind::Io(_) | ClientErrorKind::Reqwest(_) | ClientErrorKind::RpcError(RpcError::RpcRequestError(_))
+        )
+    }
+
+    /// Best-effort lookup of the slot and block time a confirmed transaction landed in
+    async fn lookup_inclusion(&self, signature: &Signature) -> (Option<u64>, Option<i64>) {
+        let slot = match self.client.get_signature_statuses(&[*signature]).await {
+            Ok(response) => response.value.into_iter().flatten().next().map(|status| status.slot),
+            Err(e) => {
+                warn!(signature = %signature, error = %e, "Failed to fetch signature status");
+                None
+            }
+        };
+
+        let block_time = match slot {
+            Some(slot) => match self.client.get_block_time(slot).await {
+                Ok(block_time) => Some(block_time),
+                Err(e) => {
+                    warn!(slot, error = %e, "Failed to fetch block time");
+                    None
+                }
+            },
+            None => None,
+        };
+
+        (slot, block_time)
+    }
+
+    /// Look up signatures in the node's recent status cache, judged against the client's commitment.
+    /// Transactions older than the cache (a few minutes) come back as `Unknown`.
+    async fn transaction_statuses_impl(&self, transaction_ids: &[String]) -> BlockchainResult<Vec<TransactionStatus>> {
+        let signatures = transaction_ids
+            .iter()
+            .map(|id| {
+                Signature::from_str(id)
+                    .map_err(|e| BlockchainError::InvalidAddress(format!("Invalid transaction signature {:?}: {}", id, e)))
+            })
+            .collect::<BlockchainResult<Vec<_>>>()?;
+
+        let commitment = self.client.commitment();
+        let mut statuses = Vec::with_capacity(signatures.len());
+        for chunk in signatures.chunks(MAX_SIGNATURE_STATUSES) {
+            let response = self.client.get_signature_statuses(chunk).await?;
+            statuses.extend(response.value.into_iter().map(|status| match status {
+                None => TransactionStatus::Unknown,
+                Some(status) => match &status.err {
+                    Some(e) => TransactionStatus::Failed(e.to_string()),
+                    None if status.satisfies_commitment(commitment) => TransactionStatus::Confirmed {
+                        block_height: Some(status.slot),
+                    },
+                    None => TransactionStatus::Pending,
+                },
+            }));
+        }
+        Ok(statuses)
+    }
+
+    /// Fetch a transaction and decode every proof instruction of this program in it
+    async fn get_proof_by_transaction_impl(&self, transaction_id: &str) -> BlockchainResult<TransactionProofs> {
+        let signature = Signature::from_str(transaction_id).map_err(|e| {
+            BlockchainError::InvalidAddress(format!("Invalid transaction signature {:?}: {}", transaction_id, e))
+        })?;
+
+        // Sent raw because `getTransaction` answers null for unknown signatures, which `get_transaction` cannot decode
+        let config = RpcTransactionConfig {
+            encoding: Some(UiTransactionEncoding::Base64),
+            commitment: Some(self.client.commitment()),
+            max_supported_transaction_version: Some(0),
+        };
+        let confirmed = self
+            .client
+            .send::<Option<EncodedConfirmedTransactionWithStatusMeta>>(
+                RpcRequest::GetTransaction,
+                serde_json::json!([signature.to_string(), config]),
+            )
+            .await?
+            .ok_or_else(|| BlockchainError::TransactionNotFound(signature.to_string()))?;
+
+        if let Some(e) = confirmed.transaction.meta.as_ref().and_then(|meta| meta.err.as_ref()) {
+            return Err(BlockchainError::NotAProof(format!("Transaction {} failed on chain: {}", signature, e)));
+        }
+
+        let transaction = confirmed.transaction.transaction.decode().ok_or_else(|| {
+            BlockchainError::Serialization(format!("Failed to decode transaction {}", signature))
+        })?;
+        // v0 transactions reference some accounts through lookup tables, resolved in the metadata
+        let mut account_keys = transaction.message.static_account_keys().to_vec();
+        if let Some(OptionSerializer::Some(loaded)) = confirmed.transaction.meta.as_ref().map(|meta| &meta.loaded_addresses) {
+            for address in loaded.writable.iter().chain(&loaded.readonly) {
+                let address = Pubkey::from_str(address).map_err(|e| {
+                    BlockchainError::Serialization(format!("Invalid loaded address in transaction {}: {}", signature, e))
+                })?;
+                account_keys.push(address);
+            }
+        }
+
+        let mut proofs = Vec::new();
+        for instruction in transaction.message.instructions() {
+            if account_keys.get(instruction.program_id_index as usize) != Some(&self.program_id) {
+                continue;
+            }
+            // Instructions of this program that are not proof stores are skipped
+            let Some(decoded) = ProofInstruction::decode(&instruction.data) else {
+                continue;
+            };
+            let mut record = match decoded {
+                ProofInstruction::StoreProof {
+                    url,
+                    content_hash,
+                    content_length,
+                    timestamp,
+                    submitter,
+                    content_type,
+                    storage_uri,
+                } => ContentRecord {
+                    url,
+                    content_hash,
+                    content_length,
+                    timestamp,
+                    submitter: submitter.unwrap_or_default(),
+                    content_type: content_type.unwrap_or_default(),
+                    storage_uri: storage_uri.unwrap_or_default(),
+                    ..Default::default()
+                },
+                // Seeds of UIDs longer than MAX_SEED_LEN are hashes and cannot be turned back into the UID
+                ProofInstruction::StoreProofWithSeed {
+                    uid_seed,
+                    url,
+                    content_hash,
+                    content_length,
+                    timestamp,
+                    submitter,
+                    content_type,
+                    storage_uri,
+                    ..
+                } => ContentRecord {
+                    uid: String::from_utf8(uid_seed).unwrap_or_default(),
+                    url,
+                    content_hash,
+                    content_length,
+                    timestamp,
+                    submitter: submitter.unwrap_or_default(),
+                    content_type: content_type.unwrap_or_default(),
+                    storage_uri: storage_uri.unwrap_or_default(),
+                    ..Default::default()
+                },
+                ProofInstruction::CloseProof => continue,
+            };
+            let stored_hash = std::mem::take(&mut record.content_hash);
+            record.set_stored_content_hash(stored_hash);
+            // Both instructions take the proof account second, after the payer
+            let account_address = instruction
+                .accounts
+                .get(1)
+                .and_then(|index| account_keys.get(*index as usize))
+                .map(|address| address.to_string());
+            proofs.push(DecodedProof {
+                record,
+                account_address,
+                payer: account_keys.first().map(|payer| payer.to_string()),
+            });
+        }
+
+        if proofs.is_empty() {
+            return Err(BlockchainError::NotAProof(format!(
+                "Transaction {} has no proof instructions for program {}",
+                signature, self.program_id
+            )));
+        }
+
+        debug!(signature = %signature, proofs = proofs.len(), slot = confirmed.slot, "Decoded proof transaction");
+        Ok(TransactionProofs {
+            proofs,
+            block_height: Some(confirmed.slot),
+            block_time: confirmed.block_time,
+        })
+    }
+
+    async fn retrieve_record_impl(&self, account_address: &str) -> BlockchainResult<ContentRecord> {
+        let address = Pubkey::from_str(account_address)
+            .map_err(|e| BlockchainError::InvalidAddress(format!("{:?}: {}", account_address, e)))?;
+
+        let account = self
+            .client
+            .get_account_with_commitment(&address, self.client.commitment())
+            .await?
+            .value
+            .ok_or_else(|| BlockchainError::AccountNotFound(address.to_string()))?;
+
+        if account.owner != self.program_id {
+            return Err(BlockchainError::InvalidAddress(format!(
+                "Account {} is owned by {}, not by program {}",
+                address, account.owner, self.program_id
+            )));
+        }
+
+        // Accounts created before sizing matched the layout are larger, so trailing bytes are tolerated
+        let proof = ProofRecord::decode(&account.data)
+            .map_err(|e| BlockchainError::Serialization(format!("Failed to decode proof account {}: {}", address, e)))?;
+
+        debug!(proof_account = %address, "Retrieved proof account");
+
+        // UID and version are not part of the on-chain layout
+        let mut record = ContentRecord {
+            url: proof.url,
+            content_length: proof.content_length,
+            timestamp: proof.timestamp.unwrap_or_default(),
+            submitter: proof.submitter.unwrap_or_default(),
+            content_type: proof.content_type.unwrap_or_default(),
+            storage_uri: proof.storage_uri.unwrap_or_default(),
+            ..Default::default()
+        };
+        record.set_stored_content_hash(proof.content_hash);
+        Ok(record)
+    }
+
+    /// Close a proof account through the program, returning its lamports to the primary payer
+    async fn close_proof_impl(&self, account_address: &str) -> BlockchainResult<ClosedProof> {
+        let address = Pubkey::from_str(account_address)
+            .map_err(|e| BlockchainError::InvalidAddress(format!("{:?}: {}", account_address, e)))?;
+
+        let account = self
+            .client
+            .get_account_with_commitment(&address, self.client.commitment())
+            .await?
+            .value
+            .ok_or_else(|| BlockchainError::AccountNotFound(address.to_string()))?;
+
+        // The program only closes its own accounts, but never send a transaction for anything else
+        if account.owner != self.program_id {
+            return Err(BlockchainError::InvalidAddress(format!(
+                "Account {} is owned by {}, not by program {}",
+                address, account.owner, self.program_id
+            )));
+        }
+        if let Err(e) = ProofRecord::decode(&account.data) {
+            return Err(BlockchainError::NotAProof(format!("Account {} does not hold a proof: {}", address, e)));
+        }
+
+        // The primary payer is the one recorded as submitter by single-payer setups
+        let payer = &self.payers[0];
+        let mut account_metas = vec![
+            AccountMeta::new(payer.pubkey(), true), // Payer (signer, receives the lamports)
+            AccountMeta::new(address, false),       // Proof account (writable)
+        ];
+        account_metas.extend(self.submitter_account());
+        let close_ix = Instruction::new_with_bytes(self.program_id, &ProofInstruction::CloseProof.try_to_vec()?, account_metas);
+        let mut instructions = self.compute_budget_instructions(&[address]).await?;
+        instructions.push(close_ix);
+
+        if self.config.simulate_transactions {
+            self.simulate(&payer.pubkey(), &instructions).await?;
+        }
+        let signers: Vec<&Keypair> = self.submitter_keypair.iter().collect();
+        let (signature, _) = self
+            .send_with_retry(payer, &instructions, &signers, self.client.commitment(), true)
+            .await
+            .map_err(|e| self.with_program_error_message(e))?;
+        let (block_height, _) = self.lookup_inclusion(&signature).await;
+
+        info!(
+            signature = %signature,
+            proof_account = %address,
+            lamports = account.lamports,
+            "Closed proof account"
+        );
+        Ok(ClosedProof {
+            transaction_id: signature.to_string(),
+            recipient: payer.pubkey().to_string(),
+            reclaimed: account.lamports,
+            block_height,
+        })
+    }
+}
+
+#[async_trait]
+impl BlockchainProvider for SolanaProvider {
+    fn chain_type(&self) -> ChainType {
+        ChainType::Solana
+    }
+
+    async fn initialize(&self) -> BlockchainResult<()> {
+        self.wait_for_connection().await?;
+        self.check_airdrop_cluster().await?;
+        self.load_lookup_table().await?;
+        self.spawn_blockhash_refresh();
+        // Report the balance right away instead of waiting for the first store
+        if let Err(e) = self.payer_balance().await {
+            warn!(error = %e, "Failed to fetch payer balance");
+        }
+        Ok(())
+    }
+
+    async fn check_health(&self) -> BlockchainResult<()> {
+        self.client.get_health().await?;
+        Ok(())
+    }
+
+    async fn get_network_info(&self) -> BlockchainResult<NetworkInfo> {
+        let genesis_hash = self.client.get_genesis_hash().await?;
+        let latest_block = self.client.get_slot().await?;
+        let version = self.client.get_version().await?;
+
+        Ok(NetworkInfo {
+            chain_type: ChainType::Solana,
+            // The endpoint in use, which differs from the primary while it is failed over
+            network_url: redacted_url(&self.client.url()),
+            genesis_hash: genesis_hash.to_string(),
+            latest_block,
+            node_version: version.solana_core,
+            chain_id: None,
+        })
+    }
+
+    async fn estimate_fee(&self, record: &ContentRecord) -> BlockchainResult<FeeEstimate> {
+        // Every payer is charged the same, so the first stands in for whichever would pay
+        let payer = &self.payers[0];
+        let (prepared, budget_instructions) = self.prepare_store(payer, record).await?;
+        let (rent, fee) = self
+            .required_lamports(&payer.pubkey(), &budget_instructions, std::slice::from_ref(&prepared))
+            .await?;
+
+        Ok(FeeEstimate {
+            storage_fee: rent,
+            transaction_fee: fee,
+            unit: "lamports",
+            gas_limit: None,
+            gas_price: None,
+        })
+    }
+
+    async fn store_record(&self, record: &ContentRecord, options: &StoreOptions) -> BlockchainResult<TransactionResult> {
+        self.store_record_impl(record, options).await
+    }
+
+    async fn store_batch(&self, records: &[ContentRecord]) -> Vec<BatchChunkResult> {
+        self.store_batch_impl(records).await
+    }
+
+    async fn retrieve_record(&self, account_address: &str) -> BlockchainResult<ContentRecord> {
+        self.retrieve_record_impl(account_address).await
+    }
+
+    async fn transaction_statuses(&self, transaction_ids: &[String]) -> BlockchainResult<Vec<TransactionStatus>> {
+        self.transaction_statuses_impl(transaction_ids).await
+    }
+
+    async fn get_proof_by_transaction(&self, transaction_id: &str) -> BlockchainResult<TransactionProofs> {
+        self.get_proof_by_transaction_impl(transaction_id).await
+    }
+
+    /// Balance of every payer in lamports; also refreshes the `payer_balance` gauge and
+    /// clears underfunded marks on payers since topped up
+    async fn get_balance(&self) -> BlockchainResult<BalanceInfo> {
+        let mut accounts = Vec::with_capacity(self.payers.len());
+        for payer in &self.payers {
+            let balance = self.client.get_balance(&payer.pubkey()).await?;
+            payer.record_balance(balance, None);
+            accounts.push(AccountBalance {
+                address: payer.pubkey().to_string(),
+                balance: balance.into(),
+            });
+        }
+        self.report_payer_balance();
+        Ok(BalanceInfo {
+            accounts,
+            unit: "lamports",
+            decimals: 9,
+        })
+    }
+
+    /// Largest payer balance in lamports, the most one store can draw on
+    async fn payer_balance(&self) -> BlockchainResult<Option<u64>> {
+        let balance = self.get_balance().await?;
+        let largest = balance.accounts.iter().map(|account| account.balance).max().unwrap_or(0);
+        Ok(Some(u64::try_from(largest).unwrap_or(u64::MAX)))
+    }
+
+    async fn close_proof(&self, account_address: &str) -> BlockchainResult<ClosedProof> {
+        self.close_proof_impl(account_address).await
+    }
+
+    fn is_own_submission(&self, proof: &DecodedProof) -> bool {
+        let paid_by_own = proof
+            .payer
+            .as_deref()
+            .is_some_and(|payer| self.payers.iter().any(|own| own.pubkey().to_string() == payer));
+        paid_by_own && proof.record.submitter == self.submitter.as_deref().unwrap_or_default()
+    }
+
+    fn derive_account_address(&self, uid: &str) -> BlockchainResult<Option<String>> {
+        if !self.config.use_pda {
+            return Ok(None);
+        }
+        let (address, _, _) = self.find_proof_address(uid)?;
+        Ok(Some(address.to_string()))
+    }
+}
+
+/// Turn the RPC failures callers can act on into specific errors
+impl From<ClientError> for BlockchainError {
+    fn from(error: ClientError) -> Self {
+        match error.get_transaction_error() {
+            // A payer that was never funded is reported as AccountNotFound
+            Some(TransactionError::InsufficientFundsForFee)
+            | Some(TransactionError::InsufficientFundsForRent { .. })
+            | Some(TransactionError::AccountNotFound) => {
+                return BlockchainError::InsufficientFunds(error.to_string());
+            }
+            // The message for the code is attached by the provider, which holds the table
+            Some(TransactionError::InstructionError(instruction, InstructionError::Custom(code))) => {
+                return BlockchainError::ProgramError {
+                    instruction,
+                    code,
+                    message: None,
+                };
+            }
+            Some(_) => return BlockchainError::Other(error.to_string()),
+            None => {}
+        }
+
+        match error.kind() {
+            ClientErrorKind::Reqwest(e) if e.is_timeout() => BlockchainError::RpcTimeout(error.to_string()),
+            ClientErrorKind::Io(_)
+            | ClientErrorKind::Reqwest(_)
+            // Raised by the transport for rate-limited requests it would not retry
+            | ClientErrorKind::Middleware(_)
+            | ClientErrorKind::RpcError(RpcError::RpcRequestError(_)) => {
+                BlockchainError::RpcUnavailable(error.to_string())
+            }
+            _ if is_expiry_sentinel(&error) => BlockchainError::ConfirmationTimeout(error.to_string()),
+            _ => BlockchainError::Other(error.to_string()),
+        }
+    }
+}
+
+/// Whether `error` is the one `SolanaProvider::blockhash_expired` raises
+fn is_expiry_sentinel(error: &ClientError) -> bool {
+    matches!(error.kind(), ClientErrorKind::Custom(message) if message.starts_with(BLOCKHASH_EXPIRED))
+}
+
+/// `commitment` label of `confirmation_seconds`
+fn commitment_label(commitment: CommitmentConfig) -> &'static str {
+    match commitment.commitment {
+        CommitmentLevel::Processed => "processed",
+        CommitmentLevel::Confirmed => "confirmed",
+        CommitmentLevel::Finalized => "finalized",
+    }
+}
+
+fn commitment_config(commitment: Commitment) -> CommitmentConfig {
+    match commitment {
+        Commitment::Processed => CommitmentConfig::processed(),
+        Commitment::Confirmed => CommitmentConfig::confirmed(),
+        Commitment::Finalized => CommitmentConfig::finalized(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn instruction_layouts_decode_by_version() {
+        let instruction = ProofInstruction::StoreProof {
+            url: "https://example.com/page".to_string(),
+            content_hash: "ab".repeat(32),
+            content_length: 1024,
+            timestamp: 1_700_000_000,
+            submitter: Some("gateway-1".to_string()),
+            content_type: None,
+            storage_uri: None,
+        };
+        let data = instruction.try_to_vec().unwrap();
+        assert_eq!(data[0], PROOF_INSTRUCTION_V2);
+        match ProofInstruction::decode(&data) {
+            Some(ProofInstruction::StoreProof { timestamp, submitter, content_type, .. }) => {
+                assert_eq!(timestamp, 1_700_000_000);
+                assert_eq!(submitter.as_deref(), Some("gateway-1"));
+                assert_eq!(content_type, None);
+            }
+            other => panic!("unexpected decode {:?}", other),
+        }
+
+        // Only records with a content type or storage URI need the version 3 layout
+        let instruction = ProofInstruction::StoreProof {
+            url: "https://example.com/page".to_string(),
+            content_hash: "ab".repeat(32),
+            content_length: 1024,
+            timestamp: 1_700_000_000,
+            submitter: Some("gateway-1".to_string()),
+            content_type: None,
+            storage_uri: Some("ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi".to_string()),
+        };
+        let data = instruction.try_to_vec().unwrap();
+        assert_eq!(data[0], PROOF_INSTRUCTION_V3);
+        match ProofInstruction::decode(&data) {
+            Some(ProofInstruction::StoreProof { submitter, storage_uri, .. }) => {
+                assert_eq!(submitter.as_deref(), Some("gateway-1"));
+                assert!(storage_uri.unwrap().starts_with("ipfs://"));
+            }
+            other => panic!("unexpected decode {:?}", other),
+        }
+
+        // Version 1 data is the bare Borsh enum, led by its variant tag
+        let mut v1 = vec![0u8];
+        borsh::to_writer(&mut v1, &("https://example.com/page".to_string(), "ab".repeat(32), 1024u64)).unwrap();
+        match ProofInstruction::decode(&v1) {
+            Some(ProofInstruction::StoreProof { content_length, timestamp, submitter, .. }) => {
+                assert_eq!(content_length, 1024);
+                assert_eq!(timestamp, 0);
+                assert_eq!(submitter, None);
+            }
+            other => panic!("unexpected decode {:?}", other),
+        }
+        assert!(ProofInstruction::decode(&[9, 0, 0]).is_none());
+
+        // Closing only exists in version 3
+        let close = ProofInstruction::CloseProof.try_to_vec().unwrap();
+        assert_eq!(close, [PROOF_INSTRUCTION_V3, 2]);
+        assert!(matches!(ProofInstruction::decode(&close), Some(ProofInstruction::CloseProof)));
+    }
+
+    #[test]
+    fn proof_accounts_decode_with_and_without_version_byte() {
+        let mut v1 = borsh::to_vec(&("https://example.com/page".to_string(), "ab".repeat(32), 1024u64)).unwrap();
+        v1.extend([0; 16]);
+        let record = ProofRecord::decode(&v1).unwrap();
+        assert_eq!(record.content_length, 1024);
+        assert_eq!(record.timestamp, None);
+
+        let mut v2 = borsh::to_vec(&("https://example.com/page".to_string(), "ab".repeat(32), 1024u64)).unwrap();
+        v2.push(PROOF_RECORD_V2);
+        borsh::to_writer(&mut v2, &(1_700_000_000i64, Some("gateway-1".to_string()))).unwrap();
+        let mut stored = ContentRecord {
+            url: "https://example.com/page".to_string(),
+            content_hash: "ab".repeat(32),
+            content_length: 1024,
+            ..Default::default()
+        };
+        assert_eq!(v2.len(), ProofRecord::space(&stored, Some("gateway-1")));
+        let record = ProofRecord::decode(&v2).unwrap();
+        assert_eq!(record.timestamp, Some(1_700_000_000));
+        assert_eq!(record.submitter.as_deref(), Some("gateway-1"));
+        assert_eq!(record.content_type, None);
+
+        let mut v3 = borsh::to_vec(&("https://example.com/page".to_string(), "ab".repeat(32), 1024u64)).unwrap();
+        v3.push(PROOF_RECORD_V3);
+        borsh::to_writer(&mut v3, &(1_700_000_000i64, Some("gateway-1".to_string()), Some("text/html".to_string()), None::<String>)).unwrap();
+        stored.content_type = "text/html".to_string();
+        assert_eq!(v3.len(), ProofRecord::space(&stored, Some("gateway-1")));
+        let record = ProofRecord::decode(&v3).unwrap();
+        assert_eq!(record.submitter.as_deref(), Some("gateway-1"));
+        assert_eq!(record.content_type.as_deref(), Some("text/html"));
+        assert_eq!(record.storage_uri, None);
+
+        // Accounts padded by the rent strategy decode the same
+        let strategy: RentStrategy = "padded:64".parse().unwrap();
+        let mut padded = v2.clone();
+        padded.resize(strategy.space(v2.len()), 0);
+        assert_eq!(padded.len(), v2.len() + 64);
+        assert_eq!(ProofRecord::decode(&padded).unwrap().submitter.as_deref(), Some("gateway-1"));
+        assert_eq!("exact".parse::<RentStrategy>().unwrap().space(v2.len()), v2.len());
+        assert_eq!(
+            "padded".parse::<RentStrategy>(),
+            Ok(RentStrategy::Padded {
+                headroom: crate::blockchain::DEFAULT_ACCOUNT_HEADROOM
+            })
+        );
+        assert!("padded:lots".parse::<RentStrategy>().is_err());
+    }
+
+    #[test]
+    fn payer_pool_loads_directory_once_per_keypair() {
+        let dir = std::env::temp_dir().join(format!("chain-gateway-payers-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let first = Keypair::new();
+        let second = Keypair::new();
+        std::fs::write(dir.join("a.json"), serde_json::to_string(&first.to_bytes().to_vec()).unwrap()).unwrap();
+        std::fs::write(dir.join("b.json"), serde_json::to_string(&second.to_bytes().to_vec()).unwrap()).unwrap();
+        std::fs::write(dir.join("notes.txt"), "not a keypair").unwrap();
+
+        let listed = dir.join("b.json").to_string_lossy().into_owned();
+        let payers = SolanaProvider::load_payer_pool(&[dir.to_string_lossy().into_owned(), listed]);
+        std::fs::remove_dir_all(&dir).unwrap();
+        let pubkeys: Vec<Pubkey> = payers.unwrap().iter().map(|payer| payer.pubkey()).collect();
+        assert_eq!(pubkeys, vec![first.pubkey(), second.pubkey()]);
+    }
+
+    #[test]
+    fn underfunded_payer_is_cleared_once_topped_up() {
+        let payer = Payer::new(Arc::new(LocalSigner::new(Keypair::new())));
+        payer.record_balance(1_000, Some(5_000));
+        assert!(payer.recently_underfunded());
+        // A refresh without a store to judge keeps the mark until the balance grows
+        payer.record_balance(1_000, None);
+        assert!(payer.recently_underfunded());
+        payer.record_balance(10_000, None);
+        assert!(!payer.recently_underfunded());
+    }
+
+    #[test]
+    fn custom_program_errors_keep_their_code() {
+        let error = ClientError::from(TransactionError::InstructionError(2, InstructionError::Custom(6001)));
+        match BlockchainError::from(error) {
+            BlockchainError::ProgramError { instruction, code, message } => {
+                assert_eq!((instruction, code, message), (2, 6001, None));
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+
+        let errors: crate::blockchain::ProgramErrors = "6000=URL too long; 0x1771 = Hash mismatch".parse().unwrap();
+        assert_eq!(errors.message(6001), Some("Hash mismatch"));
+        assert_eq!(errors.message(6002), None);
+    }
+
+    #[test]
+    fn expired_blockhashes_are_told_apart_from_transient_failures() {
+        let expired = [
+            ClientError::from(TransactionError::BlockhashNotFound),
+            SolanaProvider::blockhash_expired(&Signature::default(), CommitmentConfig::confirmed()),
+        ];
+        for error in &expired {
+            assert!(SolanaProvider::is_blockhash_expired(error), "{}", error);
+            assert!(!SolanaProvider::is_retryable(error), "{}", error);
+        }
+
+        let transient = ClientError::from(RpcError::RpcRequestError("connection reset".to_string()));
+        assert!(SolanaProvider::is_retryable(&transient));
+        assert!(!SolanaProvider::is_blockhash_expired(&transient));
+
+        // The SDK's other client-side errors are neither resent nor taken for timeouts
+        let client_side = ClientError::from(RpcError::ForUser("AccountNotFound: pubkey=abc".to_string()));
+        assert!(!SolanaProvider::is_blockhash_expired(&client_side));
+        assert!(matches!(BlockchainError::from(client_side), BlockchainError::Other(_)));
+        let expired = SolanaProvider::blockhash_expired(&Signature::default(), CommitmentConfig::confirmed());
+        assert!(matches!(BlockchainError::from(expired), BlockchainError::ConfirmationTimeout(_)));
+
+        let rejected = ClientError::from(TransactionError::InsufficientFundsForFee);
+        assert!(!SolanaProvider::is_retryable(&rejected));
+        assert!(!SolanaProvider::is_blockhash_expired(&rejected));
+    }
+
+    #[test]
+    fn builder_config_is_validated_and_takes_explicit_payers() {
+        let program_id = "B973pRh2NL9DkdvcT8VhSJywqqueknd8JXMkVVn18T3j";
+        let error = ChainConfig::builder("http://127.0.0.1:8899", program_id)
+            .ws_url("http://127.0.0.1:8900")
+            .build()
+            .unwrap_err();
+        assert!(matches!(error, BlockchainError::InvalidConfig(_)), "unexpected error {:?}", error);
+
+        let config = ChainConfig::builder("http://127.0.0.1:8899", program_id)
+            .commitment(Commitment::Finalized)
+            .use_pda(true)
+            .build()
+            .unwrap();
+        let payer = Keypair::new();
+        let payer_pubkey = payer.pubkey();
+        let provider = SolanaProvider::with_payers(config, vec![payer]).unwrap();
+        assert_eq!(provider.next_payer().pubkey(), payer_pubkey);
+        assert_eq!(provider.client.commitment(), CommitmentConfig::finalized());
+
+        // A mistyped program ID is a config error naming it
+        let config = ChainConfig::builder("http://127.0.0.1:8899", "B973pRh2NL9Dk0O").build().unwrap();
+        let Err(BlockchainError::InvalidConfig(message)) = SolanaProvider::with_payers(config, vec![Keypair::new()]) else {
+            panic!("a malformed program ID was accepted");
+        };
+        assert!(message.contains("\"B973pRh2NL9Dk0O\""), "{}", message);
+    }
+
+    #[test]
+    fn separate_submitter_is_recorded_and_co_signs() {
+        let program_id = "B973pRh2NL9DkdvcT8VhSJywqqueknd8JXMkVVn18T3j";
+        let path = std::env::temp_dir().join(format!("chain-gateway-submitter-{}.json", std::process::id()));
+        let submitter = Keypair::new();
+        std::fs::write(&path, serde_json::to_string(&submitter.to_bytes().to_vec()).unwrap()).unwrap();
+        let path = path.to_string_lossy().into_owned();
+
+        let error = ChainConfig::builder("http://127.0.0.1:8899", program_id)
+            .submitter("gateway-1")
+            .submitter_keypair(path.as_str())
+            .build()
+            .unwrap_err();
+        assert!(matches!(error, BlockchainError::InvalidConfig(_)), "unexpected error {:?}", error);
+
+        let config = ChainConfig::builder("http://127.0.0.1:8899", program_id)
+            .submitter_keypair(path.as_str())
+            .build()
+            .unwrap();
+        let provider = SolanaProvider::with_payers(config, vec![Keypair::new()]);
+        std::fs::remove_file(&path).unwrap();
+        let provider = provider.unwrap();
+        assert_eq!(provider.submitter, Some(submitter.pubkey().to_string()));
+        let account = provider.submitter_account().unwrap();
+        assert_eq!((account.pubkey, account.is_signer, account.is_writable), (submitter.pubkey(), true, false));
+    }
+
+    #[test]
+    fn lookup_table_shrinks_transactions_of_table_accounts() {
+        let program_id = "B973pRh2NL9DkdvcT8VhSJywqqueknd8JXMkVVn18T3j";
+        let table_address = Pubkey::new_unique();
+        let config = ChainConfig::builder("http://127.0.0.1:8899", program_id)
+            .lookup_table(table_address.to_string())
+            .build()
+            .unwrap();
+        let payer = Keypair::new().pubkey();
+        let provider = SolanaProvider::with_payers(config, vec![Keypair::new()]).unwrap();
+        let proof_accounts: Vec<Pubkey> = (0..6).map(|_| Pubkey::new_unique()).collect();
+        let instructions: Vec<Instruction> = proof_accounts
+            .iter()
+            .map(|proof_account| {
+                Instruction::new_with_bytes(
+                    provider.program_id,
+                    &[0; 64],
+                    vec![
+                        AccountMeta::new(payer, true),
+                        AccountMeta::new(*proof_account, false),
+                        AccountMeta::new_readonly(system_program::ID, false),
+                    ],
+                )
+            })
+            .collect();
+
+        // Legacy until `initialize` has loaded the table
+        let legacy_size = provider.transaction_size(&payer, &instructions);
+        assert!(matches!(
+            provider.compile_message(&payer, &instructions, Hash::default()).unwrap(),
+            VersionedMessage::Legacy(_)
+        ));
+
+        *provider.lookup_table.write().unwrap() = Some(AddressLookupTableAccount {
+            key: table_address,
+            addresses: proof_accounts.iter().copied().chain([system_program::ID]).collect(),
+        });
+        let message = provider.compile_message(&payer, &instructions, Hash::default()).unwrap();
+        let VersionedMessage::V0(message) = message else {
+            panic!("expected a v0 message");
+        };
+        assert_eq!(message.account_keys, vec![payer, provider.program_id]);
+        assert_eq!(message.address_table_lookups[0].writable_indexes.len(), proof_accounts.len());
+        // Seven 32-byte keys become one-byte indexes, against about 40 bytes for the table reference
+        assert!(provider.transaction_size(&payer, &instructions) < legacy_size - 150);
+    }
+
+    #[test]
+    fn explorer_links_follow_the_cluster() {
+        let program_id = "B973pRh2NL9DkdvcT8VhSJywqqueknd8JXMkVVn18T3j";
+        let url = |network_url: &str| {
+            let config = ChainConfig::builder(network_url, program_id).build().unwrap();
+            Explorer::from_config(&config).map(|explorer| explorer.transaction_url("5sig"))
+        };
+        assert_eq!(
+            url("https://api.devnet.solana.com").as_deref(),
+            Some("https://explorer.solana.com/tx/5sig?cluster=devnet")
+        );
+        assert_eq!(
+            url("https://api.mainnet-beta.solana.com").as_deref(),
+            Some("https://explorer.solana.com/tx/5sig")
+        );
+        assert_eq!(
+            url("http://127.0.0.1:8899").as_deref(),
+            Some("https://explorer.solana.com/tx/5sig?cluster=custom&customUrl=http%3A%2F%2F127.0.0.1%3A8899")
+        );
+        assert_eq!(url("https://rpc.example.com"), None);
+
+        let config = ChainConfig::builder("https://rpc.example.com", program_id)
+            .explorer_url("https://solscan.io/tx/{tx}")
+            .build()
+            .unwrap();
+        let explorer = Explorer::from_config(&config).unwrap();
+        assert_eq!(explorer.transaction_url("5sig"), "https://solscan.io/tx/5sig");
+        assert!(ChainConfig::builder("https://rpc.example.com", program_id)
+            .explorer_url("https://solscan.io/tx/")
+            .build()
+            .is_err());
+    }
+
+    /// Signs for one key but reports another, as a misconfigured remote signer would
+    struct WrongKeySigner(Pubkey);
+
+    #[async_trait]
+    impl TransactionSigner for WrongKeySigner {
+        fn pubkey(&self) -> Pubkey {
+            self.0
+        }
+
+        async fn sign_message(&self, message: &[u8]) -> BlockchainResult<Signature> {
+            Ok(Keypair::new().sign_message(message))
+        }
+    }
+
+    #[tokio::test]
+    async fn transactions_are_signed_through_the_payer_signer() {
+        let payer = LocalSigner::new(Keypair::new());
+        let proof_account = Keypair::new();
+        let mut instruction = system_instruction::transfer(&payer.pubkey(), &proof_account.pubkey(), 1);
+        instruction.accounts[1].is_signer = true;
+        let message = VersionedMessage::Legacy(Message::new(&[instruction], Some(&payer.pubkey())));
+
+        let transaction = SolanaProvider::sign_transaction(message.clone(), &payer, &[&proof_account]).await.unwrap();
+        assert!(transaction.verify_with_results().iter().all(|verified| *verified));
+
+        let error = SolanaProvider::sign_transaction(message, &WrongKeySigner(payer.pubkey()), &[&proof_account])
+            .await
+            .unwrap_err();
+        assert!(error.to_string().contains("invalid signature"), "{}", error);
+    }
+}
@@ -0,0 +1,202 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tonic::service::Interceptor;
+use tonic::{Request, Status};
+use tracing::warn;
+
+use crate::auth::API_KEY_HEADER;
+
+/// Clients tracked at once; idle buckets are evicted first, then the least recently seen
+const MAX_CLIENTS: usize = 10_000;
+
+/// Least time a client goes unseen before its bucket is dropped. When refilling a whole
+/// burst takes longer, buckets are kept until then, so a dropped bucket is always full again.
+const IDLE_EXPIRY: Duration = Duration::from_secs(600);
+
+/// How often idle buckets are swept out
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+struct Bucket {
+    tokens: f64,
+    last_seen: Instant,
+}
+
+struct Buckets {
+    clients: HashMap<String, Bucket>,
+    last_sweep: Instant,
+}
+
+/// Token-bucket limit per client, keyed by API key or, without one, by peer IP
+#[derive(Clone)]
+pub struct RateLimitInterceptor {
+    /// Tokens added per second; `None` disables limiting
+    rate: Option<f64>,
+    burst: f64,
+    /// Idle time after which a bucket is full again and can be dropped
+    idle_expiry: Duration,
+    /// Only trust the API key as identity once authentication has vetted it;
+    /// otherwise clients could dodge the limit by rotating made-up keys
+    by_api_key: bool,
+    buckets: Arc<Mutex<Buckets>>,
+}
+
+impl RateLimitInterceptor {
+    pub fn new(rate_per_sec: f64, burst: u32, by_api_key: bool) -> Self {
+        let burst = f64::from(burst.max(1));
+        let refill = Duration::try_from_secs_f64(burst / rate_per_sec).unwrap_or(Duration::MAX);
+        Self {
+            rate: Some(rate_per_sec),
+            burst,
+            idle_expiry: refill.max(IDLE_EXPIRY),
+            by_api_key,
+            buckets: Arc::new(Mutex::new(Buckets {
+                clients: HashMap::new(),
+                last_sweep: Instant::now(),
+            })),
+        }
+    }
+
+    pub fn disabled() -> Self {
+        Self {
+            rate: None,
+            ..Self::new(0.0, 1, false)
+        }
+    }
+
+    fn client_key<T>(&self, request: &Request<T>) -> String {
+        if let Some(key) = request.metadata().get(API_KEY_HEADER).filter(|_| self.by_api_key) {
+            return format!("key:{}", String::from_utf8_lossy(key.as_bytes()));
+        }
+        match request.remote_addr() {
+            Some(addr) => format!("peer:{}", addr.ip()),
+            None => "peer:unknown".to_string(),
+        }
+    }
+
+    /// Take one token for `client` at `now`, returning false when its bucket is empty
+    fn try_acquire(&self, rate: f64, client: String, now: Instant) -> bool {
+        let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+
+        if now.duration_since(buckets.last_sweep) >= SWEEP_INTERVAL || buckets.clients.len() >= MAX_CLIENTS {
+            buckets.clients.retain(|_, bucket| now.duration_since(bucket.last_seen) < self.idle_expiry);
+            buckets.last_sweep = now;
+        }
+        if buckets.clients.len() >= MAX_CLIENTS && !buckets.clients.contains_key(&client) {
+            let least_recent = buckets
+                .clients
+                .iter()
+                .min_by_key(|(_, bucket)| bucket.last_seen)
+                .map(|(key, _)| key.clone());
+            if let Some(least_recent) = least_recent {
+                buckets.clients.remove(&least_recent);
+            }
+        }
+
+        let burst = self.burst;
+        let bucket = buckets.clients.entry(client).or_insert(Bucket {
+            tokens: burst,
+            last_seen: now,
+        });
+        let elapsed = now.duration_since(bucket.last_seen).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(burst);
+        bucket.last_seen = now;
+
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+impl Interceptor for RateLimitInterceptor {
+    fn call(&mut self, request: Request<()>) -> Result<Request<()>, Status> {
+        let Some(rate) = self.rate else {
+            return Ok(request);
+        };
+
+        let client = self.client_key(&request);
+        if self.try_acquire(rate, client, Instant::now()) {
+            Ok(request)
+        } else {
+            warn!(peer = ?request.remote_addr(), "Rate limit exceeded");
+            Err(Status::resource_exhausted("Rate limit exceeded; slow down and retry"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn acquire(limiter: &RateLimitInterceptor, client: &str, now: Instant) -> bool {
+        limiter.try_acquire(limiter.rate.unwrap(), client.to_string(), now)
+    }
+
+    fn tracks(limiter: &RateLimitInterceptor, client: &str) -> bool {
+        limiter.buckets.lock().unwrap().clients.contains_key(client)
+    }
+
+    #[test]
+    fn bursts_run_out_and_refill_over_time() {
+        let limiter = RateLimitInterceptor::new(2.0, 3, true);
+        let start = Instant::now();
+        for _ in 0..3 {
+            assert!(acquire(&limiter, "key:a", start));
+        }
+        assert!(!acquire(&limiter, "key:a", start));
+
+        // Each client has a bucket of its own
+        assert!(acquire(&limiter, "key:b", start));
+
+        // Two tokens a second: half a second buys one more request
+        let later = start + Duration::from_millis(500);
+        assert!(acquire(&limiter, "key:a", later));
+        assert!(!acquire(&limiter, "key:a", later));
+
+        // Refilling stops at the burst
+        let much_later = later + Duration::from_secs(60);
+        for _ in 0..3 {
+            assert!(acquire(&limiter, "key:a", much_later));
+        }
+        assert!(!acquire(&limiter, "key:a", much_later));
+    }
+
+    #[test]
+    fn least_recently_seen_client_is_evicted_at_capacity() {
+        let limiter = RateLimitInterceptor::new(1.0, 1, true);
+        let start = Instant::now();
+        for index in 0..MAX_CLIENTS {
+            assert!(acquire(&limiter, &format!("key:{}", index), start + Duration::from_millis(index as u64)));
+        }
+        let now = start + Duration::from_secs(11);
+        assert!(acquire(&limiter, "key:new", now));
+
+        let buckets = limiter.buckets.lock().unwrap();
+        assert_eq!(buckets.clients.len(), MAX_CLIENTS);
+        assert!(!buckets.clients.contains_key("key:0"));
+        assert!(buckets.clients.contains_key("key:1"));
+        assert!(buckets.clients.contains_key("key:new"));
+    }
+
+    #[test]
+    fn idle_clients_are_swept_once_their_bucket_is_full() {
+        let limiter = RateLimitInterceptor::new(1.0, 5, true);
+        let start = Instant::now();
+        assert!(acquire(&limiter, "key:idle", start));
+        assert!(acquire(&limiter, "key:active", start + IDLE_EXPIRY - SWEEP_INTERVAL));
+
+        assert!(acquire(&limiter, "key:other", start + IDLE_EXPIRY + SWEEP_INTERVAL));
+        assert!(!tracks(&limiter, "key:idle"));
+        assert!(tracks(&limiter, "key:active"));
+
+        // A slow refill keeps buckets until they are full again, so dropping one grants nothing
+        let limiter = RateLimitInterceptor::new(0.001, 10, true);
+        assert_eq!(limiter.idle_expiry, Duration::from_secs(10_000));
+        assert!(acquire(&limiter, "key:slow", start));
+        assert!(acquire(&limiter, "key:other", start + IDLE_EXPIRY + SWEEP_INTERVAL));
+        assert!(tracks(&limiter, "key:slow"));
+    }
+}
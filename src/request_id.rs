@@ -0,0 +1,75 @@
+use std::future::Future;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tonic::codegen::http::{self, HeaderValue};
+use tower::{Layer, Service};
+
+/// Header carrying the request ID, in gRPC metadata and on the REST endpoint
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Longest request ID taken from a caller; longer or non-printable ones are replaced
+const MAX_REQUEST_ID_LEN: usize = 128;
+
+tokio::task_local! {
+    static REQUEST_ID: String;
+}
+
+/// ID of the request the current task is handling, when it runs behind [`RequestIdLayer`]
+pub fn current() -> Option<String> {
+    REQUEST_ID.try_with(Clone::clone).ok()
+}
+
+/// Gives every request an ID: the caller's `x-request-id` when it sent a usable one, a new
+/// UUID otherwise. The ID is recorded on the current span as `request_id`, readable through
+/// [`current`] while the request is handled, and echoed in the response headers.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RequestIdLayer;
+
+impl<S> Layer<S> for RequestIdLayer {
+    type Service = RequestIdService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RequestIdService { inner }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestIdService<S> {
+    inner: S,
+}
+
+impl<S, ReqBody, ResBody> Service<http::Request<ReqBody>> for RequestIdService<S>
+where
+    S: Service<http::Request<ReqBody>, Response = http::Response<ResBody>>,
+    S::Future: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, mut request: http::Request<ReqBody>) -> Self::Future {
+        let id = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|id| !id.is_empty() && id.len() <= MAX_REQUEST_ID_LEN && id.bytes().all(|byte| byte.is_ascii_graphic()))
+            .map(str::to_string)
+            .unwrap_or_else(|| uuid::Uuid::new_v4().to_string());
+        let header = HeaderValue::from_str(&id).expect("request IDs are printable ASCII");
+        request.headers_mut().insert(REQUEST_ID_HEADER, header.clone());
+
+        let response = self.inner.call(request);
+        Box::pin(async move {
+            // Polled inside the request span, which the ID is attached to
+            tracing::Span::current().record("request_id", id.as_str());
+            let mut response = REQUEST_ID.scope(id, response).await?;
+            response.headers_mut().insert(REQUEST_ID_HEADER, header);
+            Ok(response)
+        })
+    }
+}
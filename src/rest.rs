@@ -0,0 +1,305 @@
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{ConnectInfo, DefaultBodyLimit, Request, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::middleware::{self, Next};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tonic::service::Interceptor;
+use tracing::{error, info, Instrument};
+
+use crate::audit::Caller;
+use crate::auth::ApiKeyInterceptor;
+use crate::gateway::{MyChainGateway, TRANSACTION_ID_METADATA};
+use crate::proto::{self, ContentRecord, StoreRequest};
+use crate::rate_limit::RateLimitInterceptor;
+use crate::request_id::RequestIdLayer;
+
+/// Largest JSON body accepted; a record is a few hundred bytes
+const MAX_BODY_SIZE: usize = 16 * 1024;
+
+#[derive(Clone)]
+struct RestState {
+    gateway: Arc<MyChainGateway>,
+    auth: ApiKeyInterceptor,
+    rate_limit: RateLimitInterceptor,
+}
+
+/// Body of `POST /v1/proofs`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StoreProofRequest {
+    pub url: String,
+    pub content_hash: String,
+    #[serde(default)]
+    pub content_length: u64,
+    #[serde(default)]
+    pub uid: String,
+}
+
+/// `StoreResponse` as JSON, with the transaction status by name
+#[derive(Debug, Serialize)]
+pub struct StoreProofResponse {
+    pub transaction_id: String,
+    pub account_address: String,
+    pub block_height: Option<u64>,
+    pub block_time: Option<i64>,
+    pub confirmation_time_ms: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finalization_time_ms: Option<u64>,
+    /// e.g. `TRANSACTION_STATUS_CONFIRMED`
+    pub status: &'static str,
+    pub queued: bool,
+    pub already_exists: bool,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub explorer_url: String,
+}
+
+impl From<proto::StoreResponse> for StoreProofResponse {
+    fn from(response: proto::StoreResponse) -> Self {
+        Self {
+            status: response.status().as_str_name(),
+            transaction_id: response.transaction_id,
+            account_address: response.account_address,
+            block_height: response.block_height,
+            block_time: response.block_time,
+            confirmation_time_ms: response.confirmation_time_ms,
+            finalization_time_ms: response.finalization_time_ms,
+            queued: response.queued,
+            already_exists: response.already_exists,
+            explorer_url: response.explorer_url,
+        }
+    }
+}
+
+/// Error body, carrying the gRPC code the failure would have had, e.g. `INVALID_ARGUMENT`
+#[derive(Debug, Serialize)]
+struct ErrorBody {
+    code: String,
+    message: String,
+    /// Transaction of a store that timed out awaiting confirmation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    transaction_id: Option<String>,
+}
+
+/// HTTP status for a gRPC code, as grpc-gateway maps them
+fn http_status(code: tonic::Code) -> StatusCode {
+    use tonic::Code;
+
+    match code {
+        Code::Ok => StatusCode::OK,
+        Code::InvalidArgument | Code::FailedPrecondition | Code::OutOfRange => StatusCode::BAD_REQUEST,
+        Code::Unauthenticated => StatusCode::UNAUTHORIZED,
+        Code::PermissionDenied => StatusCode::FORBIDDEN,
+        Code::NotFound => StatusCode::NOT_FOUND,
+        Code::AlreadyExists | Code::Aborted => StatusCode::CONFLICT,
+        Code::ResourceExhausted => StatusCode::TOO_MANY_REQUESTS,
+        Code::Cancelled => StatusCode::REQUEST_TIMEOUT,
+        Code::Unimplemented => StatusCode::NOT_IMPLEMENTED,
+        Code::Unavailable => StatusCode::SERVICE_UNAVAILABLE,
+        Code::DeadlineExceeded => StatusCode::GATEWAY_TIMEOUT,
+        Code::Unknown | Code::Internal | Code::DataLoss => StatusCode::INTERNAL_SERVER_ERROR,
+    }
+}
+
+/// Canonical name of a gRPC code, e.g. `INVALID_ARGUMENT` for `InvalidArgument`
+fn code_name(code: tonic::Code) -> String {
+    let mut name = String::new();
+    for (i, c) in format!("{:?}", code).chars().enumerate() {
+        if i > 0 && c.is_ascii_uppercase() {
+            name.push('_');
+        }
+        name.push(c.to_ascii_uppercase());
+    }
+    name
+}
+
+fn error_response(status: &tonic::Status) -> Response {
+    let body = ErrorBody {
+        code: code_name(status.code()),
+        message: status.message().to_string(),
+        transaction_id: status
+            .metadata()
+            .get(TRANSACTION_ID_METADATA)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string),
+    };
+    (http_status(status.code()), Json(body)).into_response()
+}
+
+/// Run the gRPC interceptors over the HTTP request, so both front ends share keys and rate limits
+fn admit(state: &RestState, headers: HeaderMap, peer: SocketAddr) -> Result<Caller, tonic::Status> {
+    let mut request = tonic::Request::from_parts(
+        tonic::metadata::MetadataMap::from_headers(headers),
+        tonic::Extensions::default(),
+        (),
+    );
+    request.extensions_mut().insert(tonic::transport::server::TcpConnectInfo {
+        local_addr: None,
+        remote_addr: Some(peer),
+    });
+    let request = state.auth.clone().call(request)?;
+    let request = state.rate_limit.clone().call(request)?;
+    Ok(Caller::of(&request))
+}
+
+async fn store_proof(
+    State(state): State<RestState>,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    headers: HeaderMap,
+    body: Result<Json<StoreProofRequest>, JsonRejection>,
+) -> Response {
+    let caller = match admit(&state, headers, peer) {
+        Ok(caller) => caller,
+        Err(status) => return error_response(&status),
+    };
+    let Json(body) = match body {
+        Ok(body) => body,
+        Err(rejection) => return error_response(&tonic::Status::invalid_argument(rejection.body_text())),
+    };
+    let request = StoreRequest {
+        record: Some(ContentRecord {
+            uid: body.uid,
+            url: body.url,
+            content_hash: body.content_hash,
+            content_length: body.content_length,
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    match state.gateway.store_request_from(&caller, "POST /v1/proofs", &request).await {
+        Ok(response) => Json(StoreProofResponse::from(response)).into_response(),
+        Err(status) => error_response(&status),
+    }
+}
+
+/// Run the request in a span like the gRPC server's, which the request ID is recorded on
+async fn request_span(request: Request, next: Next) -> Response {
+    let span = tracing::info_span!(
+        "http_request",
+        method = %request.method(),
+        path = %request.uri().path(),
+        request_id = tracing::field::Empty
+    );
+    next.run(request).instrument(span).await
+}
+
+fn router(gateway: Arc<MyChainGateway>, auth: ApiKeyInterceptor, rate_limit: RateLimitInterceptor) -> Router {
+    Router::new()
+        .route("/v1/proofs", post(store_proof))
+        .layer(DefaultBodyLimit::max(MAX_BODY_SIZE))
+        .layer(RequestIdLayer)
+        // Outermost, so the request ID is recorded on the span
+        .layer(middleware::from_fn(request_span))
+        .with_state(RestState {
+            gateway,
+            auth,
+            rate_limit,
+        })
+}
+
+/// Serve `POST /v1/proofs` on an already bound listener until `shutdown` completes, then let
+/// open requests finish. Stores go through `gateway` with the same validation, API keys and
+/// rate limits as the gRPC service.
+pub async fn serve(
+    listener: TcpListener,
+    gateway: Arc<MyChainGateway>,
+    auth: ApiKeyInterceptor,
+    rate_limit: RateLimitInterceptor,
+    shutdown: impl Future<Output = ()> + Send + 'static,
+) {
+    let app = router(gateway, auth, rate_limit);
+    if let Ok(addr) = listener.local_addr() {
+        info!(%addr, "REST gateway listening on POST /v1/proofs");
+    }
+    let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>()).with_graceful_shutdown(shutdown);
+    if let Err(e) = server.await {
+        error!(error = %e, "REST gateway failed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::providers::mock::MockProvider;
+
+    fn state(provider: &MockProvider) -> RestState {
+        let gateway = MyChainGateway::with_provider(Arc::new(provider.clone()), 4, std::time::Duration::from_secs(60), 512);
+        RestState {
+            gateway: Arc::new(gateway),
+            auth: ApiKeyInterceptor::new(vec!["key-1".to_string()]),
+            rate_limit: RateLimitInterceptor::disabled(),
+        }
+    }
+
+    async fn post_proof(state: RestState, api_key: Option<&str>, body: &str) -> (StatusCode, serde_json::Value) {
+        let mut headers = HeaderMap::new();
+        if let Some(key) = api_key {
+            headers.insert(crate::auth::API_KEY_HEADER, key.parse().unwrap());
+        }
+        let body = Json::from_bytes(body.as_bytes());
+        let response = store_proof(State(state), ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 4000))), headers, body).await;
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), MAX_BODY_SIZE).await.unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    #[tokio::test]
+    async fn post_proof_stores_through_the_gateway() {
+        let provider = MockProvider::new();
+        let body = r#"{
+            "url": "https://example.com/a",
+            "content_hash": "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824",
+            "content_length": 5,
+            "uid": "page-1"
+        }"#;
+
+        let (status, error) = post_proof(state(&provider), None, body).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert_eq!(error["code"], "UNAUTHENTICATED");
+
+        let (status, response) = post_proof(state(&provider), Some("key-1"), body).await;
+        assert_eq!(status, StatusCode::OK, "{}", response);
+        assert_eq!(response["status"], "TRANSACTION_STATUS_CONFIRMED");
+        assert!(!response["transaction_id"].as_str().unwrap().is_empty());
+        assert_eq!(provider.stored_records()[0].uid, "page-1");
+
+        let (status, error) = post_proof(state(&provider), Some("key-1"), r#"{"url": "ftp://x", "content_hash": "ab"}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error["code"], "INVALID_ARGUMENT");
+    }
+
+    #[tokio::test]
+    async fn request_ids_are_echoed_or_generated() {
+        use tower::Service;
+
+        let provider = MockProvider::new();
+        let RestState { gateway, auth, rate_limit } = state(&provider);
+        let mut app = router(gateway, auth, rate_limit);
+        let request = |request_id: Option<&str>| {
+            let mut request = axum::http::Request::post("/v1/proofs")
+                .header("content-type", "application/json")
+                .body(axum::body::Body::from(r#"{"url": "https://example.com/a", "content_hash": "ab"}"#))
+                .unwrap();
+            if let Some(request_id) = request_id {
+                request.headers_mut().insert(crate::request_id::REQUEST_ID_HEADER, request_id.parse().unwrap());
+            }
+            request.extensions_mut().insert(ConnectInfo(SocketAddr::from(([127, 0, 0, 1], 4000))));
+            request
+        };
+
+        // Rejected requests carry the ID too
+        let response = app.call(request(Some("upstream-42"))).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(response.headers()[crate::request_id::REQUEST_ID_HEADER], "upstream-42");
+
+        let response = app.call(request(None)).await.unwrap();
+        let generated = response.headers()[crate::request_id::REQUEST_ID_HEADER].to_str().unwrap();
+        assert!(uuid::Uuid::parse_str(generated).is_ok(), "{}", generated);
+    }
+}
@@ -0,0 +1,343 @@
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+#[cfg(feature = "queue")]
+use std::time::UNIX_EPOCH;
+
+use crate::proto::ContentRecord;
+
+/// Delay before a queued store that failed again is retried, doubled per failure
+pub const RETRY_INITIAL_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Upper bound on the delay between retries of one queued store
+pub const RETRY_MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// A store waiting in the queue
+#[derive(Debug, Clone)]
+pub struct QueuedStore {
+    pub id: i64,
+    pub record: ContentRecord,
+    /// Retries that have failed so far
+    pub attempts: u32,
+}
+
+/// A fire-and-forget store whose transaction has not been seen confirmed yet
+#[derive(Debug, Clone)]
+pub struct PendingStore {
+    pub id: i64,
+    pub transaction_id: String,
+    pub account_address: String,
+    pub record: ContentRecord,
+    pub submitted_at: SystemTime,
+}
+
+/// Delay before the next retry of a store that has now failed `attempts` retries
+pub fn retry_backoff(attempts: u32) -> Duration {
+    let factor = 2u32.saturating_pow(attempts.saturating_sub(1));
+    RETRY_INITIAL_BACKOFF.saturating_mul(factor).min(RETRY_MAX_BACKOFF)
+}
+
+#[cfg(feature = "queue")]
+fn unix_millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH).map(|since| since.as_millis() as i64).unwrap_or(0)
+}
+
+#[cfg(feature = "queue")]
+fn from_unix_millis(millis: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_millis(millis.max(0) as u64)
+}
+
+/// Durable queue of stores that could not be submitted because the chain was unreachable.
+/// Entries survive restarts and are only removed once stored or rejected for good, giving
+/// at-least-once delivery: a store whose transaction landed without being confirmed may be
+/// submitted twice.
+///
+/// Fire-and-forget stores are kept in the same file until their transaction is seen
+/// confirmed, so stores submitted before a crash can be reconciled after the restart.
+///
+/// Like the proof index this is a local SQLite file queried inline on the async worker.
+#[cfg(feature = "queue")]
+pub struct RetryQueue {
+    conn: std::sync::Mutex<rusqlite::Connection>,
+}
+
+/// Stand-in when the crate is built without the `queue` feature; it can never be opened
+#[cfg(not(feature = "queue"))]
+pub struct RetryQueue {
+    never: std::convert::Infallible,
+}
+
+#[cfg(feature = "queue")]
+impl RetryQueue {
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let conn = rusqlite::Connection::open(path)
+            .map_err(|e| format!("Failed to open retry queue {}: {}", path.display(), e))?;
+        conn.execute_batch(
+            "PRAGMA journal_mode = WAL;
+             CREATE TABLE IF NOT EXISTS queued_stores (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 record BLOB NOT NULL,
+                 attempts INTEGER NOT NULL DEFAULT 0,
+                 next_attempt_at INTEGER NOT NULL,
+                 last_error TEXT NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS pending_stores (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 transaction_id TEXT NOT NULL UNIQUE,
+                 account_address TEXT NOT NULL,
+                 record BLOB NOT NULL,
+                 submitted_at INTEGER NOT NULL
+             );",
+        )
+        .map_err(|e| format!("Failed to initialize retry queue {}: {}", path.display(), e))?;
+        let queue = Self {
+            conn: std::sync::Mutex::new(conn),
+        };
+        let depth = queue.refresh_depth()?;
+        tracing::info!(path = %path.display(), depth, "Opened retry queue");
+        Ok(queue)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, rusqlite::Connection> {
+        self.conn.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Queue a record for submission as soon as the chain is reachable
+    pub fn push(&self, record: &ContentRecord, error: &str) -> Result<(), String> {
+        use prost::Message;
+
+        self.lock()
+            .execute(
+                "INSERT INTO queued_stores (record, next_attempt_at, last_error) VALUES (?1, ?2, ?3)",
+                rusqlite::params![record.encode_to_vec(), unix_millis(SystemTime::now()), error],
+            )
+            .map_err(|e| format!("Failed to queue record {:?}: {}", record.uid, e))?;
+        self.refresh_depth().map(|_| ())
+    }
+
+    /// Up to `limit` entries whose next attempt is due, oldest first
+    pub fn due(&self, limit: usize) -> Result<Vec<QueuedStore>, String> {
+        use prost::Message;
+
+        let conn = self.lock();
+        let mut statement = conn
+            .prepare(
+                "SELECT id, record, attempts FROM queued_stores WHERE next_attempt_at <= ?1 ORDER BY id LIMIT ?2",
+            )
+            .map_err(|e| format!("Failed to read retry queue: {}", e))?;
+        let rows = statement
+            .query_map(rusqlite::params![unix_millis(SystemTime::now()), limit as i64], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Vec<u8>>(1)?, row.get::<_, u32>(2)?))
+            })
+            .map_err(|e| format!("Failed to read retry queue: {}", e))?;
+
+        let mut due = Vec::new();
+        for row in rows {
+            let (id, record, attempts) = row.map_err(|e| format!("Failed to read retry queue: {}", e))?;
+            let record = ContentRecord::decode(record.as_slice())
+                .map_err(|e| format!("Failed to decode queued record {}: {}", id, e))?;
+            due.push(QueuedStore { id, record, attempts });
+        }
+        Ok(due)
+    }
+
+    /// Drop an entry that was stored or can never be
+    pub fn remove(&self, id: i64) -> Result<(), String> {
+        self.lock()
+            .execute("DELETE FROM queued_stores WHERE id = ?1", [id])
+            .map_err(|e| format!("Failed to remove queued store {}: {}", id, e))?;
+        self.refresh_depth().map(|_| ())
+    }
+
+    /// Count a failed retry and push the next attempt back
+    pub fn retry_later(&self, entry: &QueuedStore, error: &str) -> Result<(), String> {
+        let attempts = entry.attempts.saturating_add(1);
+        let next_attempt_at = unix_millis(SystemTime::now() + retry_backoff(attempts));
+        self.lock()
+            .execute(
+                "UPDATE queued_stores SET attempts = ?1, next_attempt_at = ?2, last_error = ?3 WHERE id = ?4",
+                rusqlite::params![attempts, next_attempt_at, error, entry.id],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to reschedule queued store {}: {}", entry.id, e))
+    }
+
+    /// Remember a submitted transaction until `settle_pending` or `requeue_pending` resolves it
+    pub fn push_pending(
+        &self,
+        transaction_id: &str,
+        account_address: &str,
+        record: &ContentRecord,
+        submitted_at: SystemTime,
+    ) -> Result<(), String> {
+        use prost::Message;
+
+        self.lock()
+            .execute(
+                "INSERT OR REPLACE INTO pending_stores (transaction_id, account_address, record, submitted_at) VALUES (?1, ?2, ?3, ?4)",
+                rusqlite::params![transaction_id, account_address, record.encode_to_vec(), unix_millis(submitted_at)],
+            )
+            .map(|_| ())
+            .map_err(|e| format!("Failed to record pending transaction {}: {}", transaction_id, e))
+    }
+
+    /// Up to `limit` unresolved transactions, oldest first
+    pub fn pending(&self, limit: usize) -> Result<Vec<PendingStore>, String> {
+        use prost::Message;
+
+        let conn = self.lock();
+        let mut statement = conn
+            .prepare(
+                "SELECT id, transaction_id, account_address, record, submitted_at FROM pending_stores ORDER BY id LIMIT ?1",
+            )
+            .map_err(|e| format!("Failed to read pending transactions: {}", e))?;
+        let rows = statement
+            .query_map([limit as i64], |row| {
+                Ok((
+                    row.get::<_, i64>(0)?,
+                    row.get::<_, String>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, Vec<u8>>(3)?,
+                    row.get::<_, i64>(4)?,
+                ))
+            })
+            .map_err(|e| format!("Failed to read pending transactions: {}", e))?;
+
+        let mut pending = Vec::new();
+        for row in rows {
+            let (id, transaction_id, account_address, record, submitted_at) =
+                row.map_err(|e| format!("Failed to read pending transactions: {}", e))?;
+            let record = ContentRecord::decode(record.as_slice())
+                .map_err(|e| format!("Failed to decode record of pending transaction {}: {}", transaction_id, e))?;
+            pending.push(PendingStore {
+                id,
+                transaction_id,
+                account_address,
+                record,
+                submitted_at: from_unix_millis(submitted_at),
+            });
+        }
+        Ok(pending)
+    }
+
+    /// Forget a transaction that confirmed or failed for good
+    pub fn settle_pending(&self, id: i64) -> Result<(), String> {
+        self.lock()
+            .execute("DELETE FROM pending_stores WHERE id = ?1", [id])
+            .map(|_| ())
+            .map_err(|e| format!("Failed to remove pending transaction {}: {}", id, e))
+    }
+
+    /// Queue the record of a transaction that was dropped for resubmission, in place of the transaction
+    pub fn requeue_pending(&self, entry: &PendingStore, error: &str) -> Result<(), String> {
+        use prost::Message;
+
+        {
+            let mut conn = self.lock();
+            let requeue = conn.transaction().and_then(|transaction| {
+                transaction.execute(
+                    "INSERT INTO queued_stores (record, next_attempt_at, last_error) VALUES (?1, ?2, ?3)",
+                    rusqlite::params![entry.record.encode_to_vec(), unix_millis(SystemTime::now()), error],
+                )?;
+                transaction.execute("DELETE FROM pending_stores WHERE id = ?1", [entry.id])?;
+                transaction.commit()
+            });
+            requeue.map_err(|e| format!("Failed to requeue dropped transaction {}: {}", entry.transaction_id, e))?;
+        }
+        self.refresh_depth().map(|_| ())
+    }
+
+    /// Count the entries and report them on the `retry_queue_depth` gauge
+    fn refresh_depth(&self) -> Result<u64, String> {
+        let depth: i64 = self
+            .lock()
+            .query_row("SELECT COUNT(*) FROM queued_stores", [], |row| row.get(0))
+            .map_err(|e| format!("Failed to count retry queue: {}", e))?;
+        crate::metrics::metrics().retry_queue_depth.set(depth);
+        Ok(depth as u64)
+    }
+}
+
+#[cfg(not(feature = "queue"))]
+impl RetryQueue {
+    pub fn open(_path: &Path) -> Result<Self, String> {
+        Err("CHAIN_RETRY_QUEUE_PATH is set but the gateway was built without the `queue` feature".to_string())
+    }
+
+    pub fn push(&self, _record: &ContentRecord, _error: &str) -> Result<(), String> {
+        match self.never {}
+    }
+
+    pub fn due(&self, _limit: usize) -> Result<Vec<QueuedStore>, String> {
+        match self.never {}
+    }
+
+    pub fn remove(&self, _id: i64) -> Result<(), String> {
+        match self.never {}
+    }
+
+    pub fn retry_later(&self, _entry: &QueuedStore, _error: &str) -> Result<(), String> {
+        match self.never {}
+    }
+
+    pub fn push_pending(
+        &self,
+        _transaction_id: &str,
+        _account_address: &str,
+        _record: &ContentRecord,
+        _submitted_at: SystemTime,
+    ) -> Result<(), String> {
+        match self.never {}
+    }
+
+    pub fn pending(&self, _limit: usize) -> Result<Vec<PendingStore>, String> {
+        match self.never {}
+    }
+
+    pub fn settle_pending(&self, _id: i64) -> Result<(), String> {
+        match self.never {}
+    }
+
+    pub fn requeue_pending(&self, _entry: &PendingStore, _error: &str) -> Result<(), String> {
+        match self.never {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_up_to_the_cap() {
+        let delays: Vec<_> = (1..=4).map(retry_backoff).collect();
+        assert_eq!(delays, [5, 10, 20, 40].map(Duration::from_secs));
+        assert_eq!(retry_backoff(u32::MAX), RETRY_MAX_BACKOFF);
+    }
+
+    #[cfg(feature = "queue")]
+    #[test]
+    fn pending_stores_are_settled_or_requeued() {
+        let db = crate::test_util::TempDb::new("queue-pending");
+        let queue = RetryQueue::open(db.path()).unwrap();
+        let record = |uid: &str| ContentRecord {
+            uid: uid.to_string(),
+            ..Default::default()
+        };
+        let submitted_at = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+        queue.push_pending("tx-1", "account-1", &record("page-1"), submitted_at).unwrap();
+        queue.push_pending("tx-2", "account-2", &record("page-2"), submitted_at).unwrap();
+
+        let pending = queue.pending(16).unwrap();
+        assert_eq!(pending.iter().map(|entry| entry.transaction_id.as_str()).collect::<Vec<_>>(), ["tx-1", "tx-2"]);
+        assert_eq!(pending[0].submitted_at, submitted_at);
+        queue.settle_pending(pending[0].id).unwrap();
+        queue.requeue_pending(&pending[1], "transaction dropped").unwrap();
+        assert!(queue.pending(16).unwrap().is_empty());
+
+        // Requeued stores are due at once and start with no failed retries
+        let due = queue.due(16).unwrap();
+        assert_eq!((due[0].record.uid.as_str(), due[0].attempts), ("page-2", 0));
+        queue.retry_later(&due[0], "connection refused").unwrap();
+        assert!(queue.due(16).unwrap().is_empty());
+        queue.remove(due[0].id).unwrap();
+    }
+}
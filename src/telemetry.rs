@@ -0,0 +1,97 @@
+use tonic::codegen::http;
+
+/// Span for one incoming gRPC request. With the `otel` feature it continues the caller's
+/// trace when the request carries a W3C `traceparent` header. `request_id` is filled in by
+/// [`RequestIdLayer`](crate::request_id::RequestIdLayer).
+pub fn request_span(request: &http::Request<()>) -> tracing::Span {
+    let span = tracing::info_span!("grpc_request", path = %request.uri().path(), request_id = tracing::field::Empty);
+    #[cfg(feature = "otel")]
+    {
+        use opentelemetry::propagation::TextMapPropagator;
+        use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+        let parent = opentelemetry_sdk::propagation::TraceContextPropagator::new()
+            .extract(&HeaderExtractor(request.headers()));
+        span.set_parent(parent);
+    }
+    span
+}
+
+/// Reads trace context out of request headers
+#[cfg(feature = "otel")]
+struct HeaderExtractor<'a>(&'a http::HeaderMap);
+
+#[cfg(feature = "otel")]
+impl opentelemetry::propagation::Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|key| key.as_str()).collect()
+    }
+}
+
+/// Exports spans in batches to an OTLP/gRPC collector
+#[cfg(feature = "otel")]
+pub struct OtlpExporter {
+    provider: opentelemetry_sdk::trace::SdkTracerProvider,
+}
+
+/// Stand-in when the crate is built without the `otel` feature; it can never be created
+#[cfg(not(feature = "otel"))]
+pub struct OtlpExporter {
+    never: std::convert::Infallible,
+}
+
+#[cfg(feature = "otel")]
+impl OtlpExporter {
+    /// Start exporting to the collector at `endpoint`, e.g. `http://otel-collector:4317`.
+    /// Must be called inside the Tokio runtime the exporter's connection runs on.
+    pub fn new(endpoint: &str) -> Result<Self, String> {
+        use opentelemetry_otlp::WithExportConfig;
+
+        let exporter = opentelemetry_otlp::SpanExporter::builder()
+            .with_tonic()
+            .with_endpoint(endpoint)
+            .build()
+            .map_err(|e| format!("Failed to build OTLP exporter for {}: {}", crate::blockchain::redacted_url(endpoint), e))?;
+        let provider = opentelemetry_sdk::trace::SdkTracerProvider::builder()
+            .with_batch_exporter(exporter)
+            .with_resource(opentelemetry_sdk::Resource::builder().with_service_name("chain-gateway").build())
+            .build();
+        Ok(Self { provider })
+    }
+
+    /// Layer sending the subscriber's spans to the collector
+    pub fn layer<S>(&self) -> impl tracing_subscriber::Layer<S>
+    where
+        S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+    {
+        use opentelemetry::trace::TracerProvider;
+
+        tracing_opentelemetry::layer().with_tracer(self.provider.tracer("chain-gateway"))
+    }
+
+    /// Flush the spans still waiting to be exported; call once before exiting
+    pub fn shutdown(&self) {
+        if let Err(e) = self.provider.shutdown() {
+            tracing::warn!(error = %e, "Failed to flush spans to the OTLP collector");
+        }
+    }
+}
+
+#[cfg(not(feature = "otel"))]
+impl OtlpExporter {
+    pub fn new(_endpoint: &str) -> Result<Self, String> {
+        Err("CHAIN_GATEWAY_OTLP_ENDPOINT is set but the gateway was built without the `otel` feature".to_string())
+    }
+
+    pub fn layer(&self) -> tracing_subscriber::layer::Identity {
+        match self.never {}
+    }
+
+    pub fn shutdown(&self) {
+        match self.never {}
+    }
+}
@@ -0,0 +1,29 @@
+//! Fixtures shared by the unit tests of several modules
+
+use std::path::{Path, PathBuf};
+
+/// SQLite file in the temp directory, removed with its WAL files when dropped, even when
+/// the test panics. Declare it before whatever opens it, so it is dropped after.
+pub struct TempDb {
+    path: PathBuf,
+}
+
+impl TempDb {
+    /// `name` must be unique among the tests, which share the process ID
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("chain-gateway-{}-{}.sqlite", name, std::process::id()));
+        Self { path }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", self.path.display(), suffix));
+        }
+    }
+}
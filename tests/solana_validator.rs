@@ -0,0 +1,109 @@
+//! End-to-end store against a throwaway `solana-test-validator` running the proof program.
+//!
+//! Ignored by default, since it needs the Solana CLI on `PATH` and a build of the program:
+//!
+//! ```sh
+//! CHAIN_TEST_PROGRAM_SO=target/deploy/proof_program.so \
+//!     cargo test --test solana_validator -- --ignored
+//! ```
+#![cfg(feature = "solana")]
+
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::{Child, Command, Stdio};
+use std::time::Duration;
+
+use chain_gateway::blockchain::ConnectRetry;
+use chain_gateway::proto::{ContentRecord, HashAlgorithm};
+use chain_gateway::{BlockchainProvider, ChainConfig, SolanaProvider, StoreOptions};
+use solana_sdk::signature::{Keypair, Signer};
+
+/// Compiled proof program loaded into the validator at genesis
+const PROGRAM_SO_VAR: &str = "CHAIN_TEST_PROGRAM_SO";
+
+/// Validator child process, killed and its ledger removed when dropped so a failing
+/// assertion never leaves one running
+struct TestValidator {
+    child: Child,
+    ledger: PathBuf,
+    rpc_url: String,
+}
+
+impl TestValidator {
+    /// Start a validator on free local ports with `program_so` deployed at `program_id`
+    fn start(program_id: &str, program_so: &str) -> Self {
+        let ledger = std::env::temp_dir().join(format!("chain-gateway-validator-{}", std::process::id()));
+        let rpc_port = free_port();
+        let child = Command::new("solana-test-validator")
+            .arg("--reset")
+            .arg("--quiet")
+            .arg("--ledger")
+            .arg(&ledger)
+            .args(["--rpc-port", &rpc_port.to_string()])
+            .args(["--faucet-port", &free_port().to_string()])
+            .args(["--gossip-port", &free_port().to_string()])
+            .args(["--bpf-program", program_id, program_so])
+            .stdout(Stdio::null())
+            .spawn()
+            .expect("failed to start solana-test-validator; is the Solana CLI on PATH?");
+        Self {
+            child,
+            ledger,
+            rpc_url: format!("http://127.0.0.1:{}", rpc_port),
+        }
+    }
+}
+
+impl Drop for TestValidator {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        let _ = std::fs::remove_dir_all(&self.ledger);
+    }
+}
+
+/// Port nothing is listening on right now
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+#[tokio::test]
+#[ignore = "needs solana-test-validator and CHAIN_TEST_PROGRAM_SO"]
+async fn store_round_trips_through_a_local_validator() {
+    let program_so = std::env::var(PROGRAM_SO_VAR).unwrap_or_else(|_| panic!("{} must point at the program build", PROGRAM_SO_VAR));
+    let program_id = Keypair::new().pubkey().to_string();
+    let validator = TestValidator::start(&program_id, &program_so);
+
+    let config = ChainConfig::builder(&validator.rpc_url, &program_id)
+        // Startup takes a few seconds; initialize waits for the RPC port to answer
+        .connect_retry(ConnectRetry {
+            retries: 60,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_millis(500),
+            jitter: false,
+        })
+        // The fresh payer starts empty and is funded from the validator's faucet
+        .airdrop(100_000_000, 1_000_000_000)
+        .build()
+        .unwrap();
+    let provider = SolanaProvider::with_payers(config, vec![Keypair::new()]).unwrap();
+    provider.initialize().await.unwrap();
+
+    let record = ContentRecord {
+        uid: "validator-round-trip".to_string(),
+        url: "https://example.com/a".to_string(),
+        content_hash: "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824".to_string(),
+        content_length: 5,
+        hash_algorithm: HashAlgorithm::Sha256 as i32,
+        ..Default::default()
+    };
+    let result = provider.store_record(&record, &StoreOptions::default()).await.unwrap();
+    assert!(!result.transaction_id.is_empty());
+    let account = result.account_address.expect("single stores report their account");
+
+    let stored = provider.retrieve_record(&account).await.unwrap();
+    assert_eq!(stored.uid, record.uid);
+    assert_eq!(stored.url, record.url);
+    assert_eq!(stored.content_hash, record.content_hash);
+    assert_eq!(stored.content_length, record.content_length);
+}